@@ -9,6 +9,13 @@ use crate::SlidingWindow;
 pub struct CardinalityLimit {
     /// Unique identifier of the cardinality limit.
     pub id: String,
+    /// Whether this is a passive limit.
+    ///
+    /// Passive limits are tracked separately to normal limits and are not enforced, but still
+    /// evaluated. Rejections of passive limits are reported in a
+    /// [`CardinalityReport`](crate::CardinalityReport) instead of dropping items.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub passive: bool,
     /// The sliding window to enforce the cardinality limits in.
     pub window: SlidingWindow,
     /// The cardinality limit.
@@ -44,6 +51,7 @@ mod tests {
     fn test_cardinality_limit_json() {
         let limit = CardinalityLimit {
             id: "some_id".to_string(),
+            passive: false,
             window: SlidingWindow {
                 window_seconds: 3600,
                 granularity_seconds: 200,
@@ -65,4 +73,27 @@ mod tests {
         }"#;
         assert_eq!(serde_json::from_str::<CardinalityLimit>(j).unwrap(), limit);
     }
+
+    #[test]
+    fn test_cardinality_limit_passive_json() {
+        let j = r#"{
+            "id":"some_id",
+            "passive":true,
+            "window":{"windowSeconds":3600,"granularitySeconds":200},
+            "limit":1337,
+            "scope":"organization"
+        }"#;
+        let limit = serde_json::from_str::<CardinalityLimit>(j).unwrap();
+        assert!(limit.passive);
+
+        let j = serde_json::to_string(&limit).unwrap();
+        assert!(j.contains(r#""passive":true"#));
+
+        let limit = CardinalityLimit {
+            passive: false,
+            ..limit
+        };
+        let j = serde_json::to_string(&limit).unwrap();
+        assert!(!j.contains("passive"));
+    }
 }
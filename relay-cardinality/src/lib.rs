@@ -9,16 +9,22 @@
 mod config;
 mod error;
 pub mod limiter;
+mod memory;
+mod quota;
 #[cfg(feature = "redis")]
 mod redis;
+mod report;
 mod statsd;
+mod vacuum;
 mod window;
 
 pub use self::config::*;
 pub use self::error::*;
 pub use self::limiter::{CardinalityItem, CardinalityLimits, Scoping};
+pub use self::memory::{InMemoryLimiter, InMemoryLimiterOptions};
 #[cfg(feature = "redis")]
 pub use self::redis::{RedisSetLimiter, RedisSetLimiterOptions};
+pub use self::report::*;
 pub use self::window::SlidingWindow;
 
 /// Redis Set based cardinality limiter.
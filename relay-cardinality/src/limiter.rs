@@ -1,12 +1,14 @@
 //! Relay Cardinality Limiter
 
+use std::collections::BTreeMap;
+
 use hashbrown::HashSet;
 use relay_base_schema::metrics::MetricNamespace;
 use relay_base_schema::project::ProjectId;
 use relay_statsd::metric;
 
-use crate::statsd::CardinalityLimiterTimers;
-use crate::{CardinalityLimit, Error, OrganizationId, Result};
+use crate::statsd::{CardinalityLimiterCounters, CardinalityLimiterTimers};
+use crate::{CardinalityLimit, CardinalityReport, Error, OrganizationId, Result};
 
 /// Data scoping information.
 ///
@@ -20,26 +22,28 @@ pub struct Scoping {
 }
 
 /// Accumulator of all cardinality limiter rejections.
-pub trait Rejections {
+pub trait Rejections<'a> {
     /// Called for ever [`Entry`] which was rejected from the [`Limiter`].
-    fn reject(&mut self, entry_id: EntryId);
+    ///
+    /// An entry can be rejected by multiple limits, in which case this is called once per limit.
+    fn reject(&mut self, limit: &'a CardinalityLimit, entry_id: EntryId);
 }
 
 /// Limiter responsible to enforce limits.
 pub trait Limiter {
     /// Verifies cardinality limits.
     ///
-    /// Returns an iterator containing only accepted entries.
-    fn check_cardinality_limits<E, R>(
+    /// Reports every rejected entry together with the rejecting limit to `rejections`.
+    fn check_cardinality_limits<'a, E, R>(
         &self,
         scoping: Scoping,
-        limits: &[CardinalityLimit],
+        limits: &'a [CardinalityLimit],
         entries: E,
         rejections: &mut R,
     ) -> Result<()>
     where
         E: IntoIterator<Item = Entry>,
-        R: Rejections;
+        R: Rejections<'a>;
 }
 
 /// Unit of operation for the cardinality limiter.
@@ -51,6 +55,12 @@ pub trait CardinalityItem {
     ///
     /// If this method returns `None` the item is automatically rejected.
     fn namespace(&self) -> Option<MetricNamespace>;
+
+    /// Name of the item, used to attribute rejections in a [`CardinalityReport`].
+    fn name(&self) -> &str;
+
+    /// Tag keys of the item, used to attribute rejections in a [`CardinalityReport`].
+    fn tag_keys(&self) -> Vec<&str>;
 }
 
 /// A single entry to check cardinality for.
@@ -111,7 +121,7 @@ impl<T: Limiter> CardinalityLimiter<T> {
                 Some(Entry::new(EntryId(id), item.namespace()?, item.to_hash()))
             });
 
-            let mut rejections = RejectedIds::default();
+            let mut rejections = RejectionTracker::default();
             if let Err(err) =
                 self.limiter
                     .check_cardinality_limits(scoping, limits, entries, &mut rejections)
@@ -119,58 +129,97 @@ impl<T: Limiter> CardinalityLimiter<T> {
                 return Err((items, err));
             }
 
-            if !rejections.0.is_empty() {
+            if !rejections.entries.is_empty() {
                 relay_log::debug!(
                     scoping = ?scoping,
                     "rejected {} metrics due to cardinality limit",
-                    rejections.0.len(),
+                    rejections.entries.len(),
+                );
+            }
+
+            for (limit_id, entries) in &rejections.passive {
+                metric!(
+                    counter(CardinalityLimiterCounters::PassiveRejected) += entries.len() as i64,
+                    id = limit_id,
                 );
             }
 
-            Ok(CardinalityLimits::new(items, rejections))
+            Ok(CardinalityLimits::new(scoping, items, rejections))
         })
     }
 }
 
 /// Internal outcome accumulator tracking the raw value from an [`EntryId`].
 ///
-/// The result can be used directly by [`CardinalityLimits`].
+/// Rejections of enforced and passive limits are tracked separately. The result can be used
+/// directly by [`CardinalityLimits`].
 #[derive(Debug, Default)]
-struct RejectedIds(HashSet<usize>);
+struct RejectionTracker<'a> {
+    /// Entries rejected by enforced limits.
+    entries: HashSet<usize>,
+    /// Entries rejected by passive limits, grouped by the id of the limit.
+    passive: BTreeMap<&'a str, HashSet<usize>>,
+}
 
-impl Rejections for RejectedIds {
+impl<'a> Rejections<'a> for RejectionTracker<'a> {
     #[inline(always)]
-    fn reject(&mut self, entry_id: EntryId) {
-        self.0.insert(entry_id.0);
+    fn reject(&mut self, limit: &'a CardinalityLimit, entry_id: EntryId) {
+        if limit.passive {
+            self.passive
+                .entry(&limit.id)
+                .or_default()
+                .insert(entry_id.0);
+        } else {
+            self.entries.insert(entry_id.0);
+        }
     }
 }
 
 /// Result of [`CardinalityLimiter::check_cardinality_limits`].
 #[derive(Debug)]
 pub struct CardinalityLimits<T> {
+    scoping: Scoping,
     source: Vec<T>,
     rejections: HashSet<usize>,
+    passive_rejections: BTreeMap<String, HashSet<usize>>,
 }
 
 impl<T> CardinalityLimits<T> {
-    fn new(source: Vec<T>, rejections: RejectedIds) -> Self {
+    fn new(scoping: Scoping, source: Vec<T>, rejections: RejectionTracker<'_>) -> Self {
         Self {
+            scoping,
             source,
-            rejections: rejections.0,
+            rejections: rejections.entries,
+            passive_rejections: rejections
+                .passive
+                .into_iter()
+                .map(|(limit_id, entries)| (limit_id.to_owned(), entries))
+                .collect(),
         }
     }
 
+    /// Returns `true` if any item was rejected by a passive limit.
+    ///
+    /// Items rejected by passive limits are not dropped, see [`Self::passive_reports`].
+    pub fn has_passive_rejections(&self) -> bool {
+        !self.passive_rejections.is_empty()
+    }
+
     /// Recovers the original list of items passed to the cardinality limiter.
     pub fn into_source(self) -> Vec<T> {
         self.source
     }
 
     /// Returns an iterator yielding only rejected items.
+    ///
+    /// Items rejected only by passive limits are not included.
     pub fn rejected(&self) -> impl Iterator<Item = &T> {
         self.rejections.iter().filter_map(|&i| self.source.get(i))
     }
 
     /// Consumes the result and returns an iterator over all accepted items.
+    ///
+    /// Items rejected only by passive limits are accepted.
     pub fn into_accepted(self) -> Vec<T> {
         if self.rejections.is_empty() {
             return self.source;
@@ -192,6 +241,21 @@ impl<T> CardinalityLimits<T> {
     }
 }
 
+impl<T: CardinalityItem> CardinalityLimits<T> {
+    /// Creates one [`CardinalityReport`] for every passive limit that rejected items.
+    ///
+    /// Each report retains the `top_n` metric names and tag keys with the most rejections.
+    pub fn passive_reports(&self, top_n: usize) -> Vec<CardinalityReport> {
+        self.passive_rejections
+            .iter()
+            .map(|(limit_id, entries)| {
+                let items = entries.iter().filter_map(|&i| self.source.get(i));
+                CardinalityReport::new(self.scoping, limit_id, items, top_n)
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{CardinalityScope, SlidingWindow};
@@ -202,13 +266,26 @@ mod tests {
     struct Item {
         hash: u32,
         namespace: Option<MetricNamespace>,
+        name: &'static str,
+        tags: &'static [&'static str],
     }
 
     impl Item {
         fn new(hash: u32, namespace: impl Into<Option<MetricNamespace>>) -> Self {
+            Self::named(hash, namespace, "", &[])
+        }
+
+        fn named(
+            hash: u32,
+            namespace: impl Into<Option<MetricNamespace>>,
+            name: &'static str,
+            tags: &'static [&'static str],
+        ) -> Self {
             Self {
                 hash,
                 namespace: namespace.into(),
+                name,
+                tags,
             }
         }
     }
@@ -221,11 +298,20 @@ mod tests {
         fn namespace(&self) -> Option<MetricNamespace> {
             self.namespace
         }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn tag_keys(&self) -> Vec<&str> {
+            self.tags.to_vec()
+        }
     }
 
     fn build_limits() -> [CardinalityLimit; 1] {
         [CardinalityLimit {
             id: "limit".to_owned(),
+            passive: false,
             window: SlidingWindow {
                 window_seconds: 3600,
                 granularity_seconds: 360,
@@ -257,22 +343,28 @@ mod tests {
         }
 
         let limits = CardinalityLimits {
+            scoping: build_scoping(),
             source: vec!['a', 'b', 'c', 'd', 'e'],
             rejections: HashSet::from([0, 1, 3]),
+            passive_rejections: BTreeMap::new(),
         };
         assert_rejected(&limits, ['a', 'b', 'd']);
         assert_eq!(limits.into_accepted(), vec!['c', 'e']);
 
         let limits = CardinalityLimits {
+            scoping: build_scoping(),
             source: vec!['a', 'b', 'c', 'd', 'e'],
             rejections: HashSet::from([]),
+            passive_rejections: BTreeMap::new(),
         };
         assert_rejected(&limits, []);
         assert_eq!(limits.into_accepted(), vec!['a', 'b', 'c', 'd', 'e']);
 
         let limits = CardinalityLimits {
+            scoping: build_scoping(),
             source: vec!['a', 'b', 'c', 'd', 'e'],
             rejections: HashSet::from([0, 1, 2, 3, 4]),
+            passive_rejections: BTreeMap::new(),
         };
         assert_rejected(&limits, ['a', 'b', 'c', 'd', 'e']);
         assert!(limits.into_accepted().is_empty());
@@ -283,19 +375,19 @@ mod tests {
         struct RejectAllLimiter;
 
         impl Limiter for RejectAllLimiter {
-            fn check_cardinality_limits<I, T>(
+            fn check_cardinality_limits<'a, I, T>(
                 &self,
                 _scoping: Scoping,
-                _limits: &[CardinalityLimit],
+                limits: &'a [CardinalityLimit],
                 entries: I,
                 outcomes: &mut T,
             ) -> Result<()>
             where
                 I: IntoIterator<Item = Entry>,
-                T: Rejections,
+                T: Rejections<'a>,
             {
                 for entry in entries {
-                    outcomes.reject(entry.id);
+                    outcomes.reject(&limits[0], entry.id);
                 }
 
                 Ok(())
@@ -323,16 +415,16 @@ mod tests {
         struct AcceptAllLimiter;
 
         impl Limiter for AcceptAllLimiter {
-            fn check_cardinality_limits<I, T>(
+            fn check_cardinality_limits<'a, I, T>(
                 &self,
                 _scoping: Scoping,
-                _limits: &[CardinalityLimit],
+                _limits: &'a [CardinalityLimit],
                 _entries: I,
                 _outcomes: &mut T,
             ) -> Result<()>
             where
                 I: IntoIterator<Item = Entry>,
-                T: Rejections,
+                T: Rejections<'a>,
             {
                 Ok(())
             }
@@ -356,23 +448,23 @@ mod tests {
         struct RejectEvenLimiter;
 
         impl Limiter for RejectEvenLimiter {
-            fn check_cardinality_limits<I, T>(
+            fn check_cardinality_limits<'a, I, T>(
                 &self,
                 scoping: Scoping,
-                limits: &[CardinalityLimit],
+                limits: &'a [CardinalityLimit],
                 entries: I,
                 outcomes: &mut T,
             ) -> Result<()>
             where
                 I: IntoIterator<Item = Entry>,
-                T: Rejections,
+                T: Rejections<'a>,
             {
                 assert_eq!(scoping, build_scoping());
                 assert_eq!(limits, &build_limits());
 
                 for entry in entries {
                     if entry.id.0 % 2 == 0 {
-                        outcomes.reject(entry.id);
+                        outcomes.reject(&limits[0], entry.id);
                     }
                 }
 
@@ -405,4 +497,80 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_limiter_passive() {
+        struct RejectByLimitLimiter;
+
+        impl Limiter for RejectByLimitLimiter {
+            fn check_cardinality_limits<'a, I, T>(
+                &self,
+                _scoping: Scoping,
+                limits: &'a [CardinalityLimit],
+                entries: I,
+                outcomes: &mut T,
+            ) -> Result<()>
+            where
+                I: IntoIterator<Item = Entry>,
+                T: Rejections<'a>,
+            {
+                // The enforced limit rejects hash `0`, the passive limit all hashes >= `10`.
+                for entry in entries {
+                    if entry.hash == 0 {
+                        outcomes.reject(&limits[0], entry.id);
+                    }
+                    if entry.hash >= 10 {
+                        outcomes.reject(&limits[1], entry.id);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        let [enforced] = build_limits();
+        let passive = CardinalityLimit {
+            id: "passive".to_owned(),
+            passive: true,
+            ..enforced.clone()
+        };
+        let limits = [enforced, passive];
+
+        let limiter = CardinalityLimiter::new(RejectByLimitLimiter);
+
+        let items = vec![
+            Item::named(0, MetricNamespace::Custom, "c:custom/a@none", &["foo"]),
+            Item::named(1, MetricNamespace::Custom, "c:custom/a@none", &["foo"]),
+            Item::named(
+                10,
+                MetricNamespace::Custom,
+                "c:custom/b@none",
+                &["foo", "bar"],
+            ),
+            Item::named(11, MetricNamespace::Custom, "c:custom/b@none", &["bar"]),
+            Item::named(12, MetricNamespace::Custom, "c:custom/c@none", &["bar"]),
+        ];
+        let result = limiter
+            .check_cardinality_limits(build_scoping(), &limits, items.clone())
+            .unwrap();
+
+        assert!(result.has_passive_rejections());
+
+        let reports = result.passive_reports(1);
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.limit_id, "passive");
+        assert_eq!(report.organization_id, 1);
+        assert_eq!(report.rejected, 3);
+        assert_eq!(report.top_names.len(), 1);
+        assert_eq!(report.top_names[0].name, "c:custom/b@none");
+        assert_eq!(report.top_names[0].count, 2);
+        assert_eq!(report.top_tags.len(), 1);
+        assert_eq!(report.top_tags[0].name, "bar");
+        assert_eq!(report.top_tags[0].count, 3);
+
+        // Only the item rejected by the enforced limit is dropped.
+        assert_eq!(result.rejected().collect::<Vec<_>>(), vec![&items[0]]);
+        assert_eq!(result.into_accepted(), items[1..].to_vec());
+    }
 }
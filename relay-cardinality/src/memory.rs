@@ -0,0 +1,338 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use relay_common::time::UnixTimestamp;
+use relay_statsd::metric;
+
+use crate::limiter::{Entry, Limiter, Rejections, Scoping};
+use crate::quota::QuotaScoping;
+use crate::statsd::{CardinalityLimiterCounters, CardinalityLimiterTimers};
+use crate::vacuum::Vacuum;
+use crate::window::Slot;
+use crate::{CardinalityLimit, Result};
+
+/// Configuration options for the [`InMemoryLimiter`].
+pub struct InMemoryLimiterOptions {
+    /// Vacuum interval for the in memory state.
+    ///
+    /// The limiter will scan for expired slots based on this interval.
+    pub cache_vacuum_interval: Duration,
+}
+
+/// Cardinality limiter which keeps track of cardinality in process memory.
+///
+/// This limiter mirrors the semantics of the Redis limiter, but the state is not shared between
+/// Relay instances. It is meant for Relays without access to Redis and for tests.
+///
+/// For every limit, the limiter keeps one hash set per slot of the sliding window. An accepted
+/// hash is added to all slots of the window, the set of the currently active slot is used to
+/// determine the cardinality. Since hashes are only added while the active set is below the
+/// limit, every set is bounded by the cardinality limit.
+pub struct InMemoryLimiter {
+    inner: Mutex<Inner>,
+    #[cfg(test)]
+    time_offset: Duration,
+}
+
+impl InMemoryLimiter {
+    /// Creates a new [`InMemoryLimiter`].
+    pub fn new(options: InMemoryLimiterOptions) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                scopes: Default::default(),
+                vacuum: Vacuum::new(options.cache_vacuum_interval),
+            }),
+            #[cfg(test)]
+            time_offset: Duration::from_secs(0),
+        }
+    }
+}
+
+impl Limiter for InMemoryLimiter {
+    fn check_cardinality_limits<'a, E, R>(
+        &self,
+        scoping: Scoping,
+        limits: &'a [CardinalityLimit],
+        entries: E,
+        rejections: &mut R,
+    ) -> Result<()>
+    where
+        E: IntoIterator<Item = Entry>,
+        R: Rejections<'a>,
+    {
+        let timestamp = UnixTimestamp::now();
+        // Allows to fast forward time in tests.
+        #[cfg(test)]
+        let timestamp = timestamp + self.time_offset;
+
+        let limits = limits
+            .iter()
+            .filter_map(|limit| Some((limit, QuotaScoping::new(scoping, limit)?)))
+            .collect::<Vec<_>>();
+
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.vacuum(timestamp);
+
+        let mut counts = vec![(0i64, 0i64); limits.len()];
+        for entry in entries {
+            for ((limit, scope), (accepted, rejected)) in limits.iter().zip(counts.iter_mut()) {
+                if !scope.matches(&entry) {
+                    // Entry not relevant for limit.
+                    continue;
+                }
+
+                let sets = inner.scopes.entry(*scope).or_default();
+                if sets.check(scope, timestamp, entry.hash, limit.limit) {
+                    *accepted += 1;
+                } else {
+                    rejections.reject(*limit, entry.id);
+                    *rejected += 1;
+                }
+            }
+        }
+        drop(inner); // Give up the lock!
+
+        for ((limit, _), (accepted, rejected)) in limits.iter().zip(counts) {
+            metric!(
+                counter(CardinalityLimiterCounters::Accepted) += accepted,
+                id = &limit.id,
+            );
+            metric!(
+                counter(CardinalityLimiterCounters::Rejected) += rejected,
+                id = &limit.id,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Critical section of the [`InMemoryLimiter`].
+#[derive(Debug)]
+struct Inner {
+    scopes: hashbrown::HashMap<QuotaScoping, ScopedSets>,
+    vacuum: Vacuum,
+}
+
+impl Inner {
+    fn vacuum(&mut self, ts: UnixTimestamp) {
+        // Debounce the vacuuming.
+        if !self.vacuum.is_due(ts) {
+            return;
+        }
+
+        let expired = metric!(timer(CardinalityLimiterTimers::CacheVacuum), {
+            let mut expired = 0;
+            self.scopes.retain(|scope, sets| {
+                expired += sets.expire(scope.active_slot(ts));
+                !sets.is_empty()
+            });
+            expired
+        });
+        metric!(counter(CardinalityLimiterCounters::MemoryVacuum) += expired as i64);
+    }
+}
+
+/// Hash sets of all slots of a sliding window for a single scope.
+#[derive(Debug, Default)]
+struct ScopedSets {
+    // Uses hashbrown for a faster hasher `ahash`.
+    slots: BTreeMap<Slot, hashbrown::HashSet<u32>>,
+}
+
+impl ScopedSets {
+    /// Checks whether the hash is accepted and records accepted hashes in all slots.
+    fn check(&mut self, scope: &QuotaScoping, ts: UnixTimestamp, hash: u32, limit: u64) -> bool {
+        let working_set = self.slots.entry(scope.active_slot(ts)).or_default();
+
+        if working_set.contains(&hash) {
+            // Already seen before in the window, always accept.
+            return true;
+        }
+
+        if working_set.len() as u64 >= limit {
+            return false;
+        }
+
+        for slot in scope.slots(ts) {
+            self.slots.entry(slot).or_default().insert(hash);
+        }
+
+        true
+    }
+
+    /// Removes all slots older than the active slot and returns the amount of removed slots.
+    fn expire(&mut self, active_slot: Slot) -> usize {
+        let before = self.slots.len();
+        self.slots = self.slots.split_off(&active_slot);
+        before - self.slots.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use relay_base_schema::metrics::MetricNamespace;
+    use relay_base_schema::project::ProjectId;
+
+    use crate::limiter::EntryId;
+    use crate::{CardinalityScope, SlidingWindow};
+
+    use super::*;
+
+    fn build_limiter() -> InMemoryLimiter {
+        InMemoryLimiter::new(InMemoryLimiterOptions {
+            cache_vacuum_interval: Duration::from_secs(5),
+        })
+    }
+
+    fn build_scoping() -> Scoping {
+        Scoping {
+            organization_id: 1,
+            project_id: ProjectId::new(1),
+        }
+    }
+
+    fn build_limit(limit: u64) -> CardinalityLimit {
+        CardinalityLimit {
+            id: "limit".to_owned(),
+            passive: false,
+            window: SlidingWindow {
+                window_seconds: 3600,
+                granularity_seconds: 360,
+            },
+            limit,
+            scope: CardinalityScope::Organization,
+            namespace: Some(MetricNamespace::Custom),
+        }
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct Rejections(HashSet<EntryId>);
+
+    impl<'a> super::Rejections<'a> for Rejections {
+        fn reject(&mut self, _limit: &'a CardinalityLimit, entry_id: EntryId) {
+            self.0.insert(entry_id);
+        }
+    }
+
+    impl InMemoryLimiter {
+        fn test_limits<I>(&self, limits: &[CardinalityLimit], entries: I) -> Rejections
+        where
+            I: IntoIterator<Item = Entry>,
+        {
+            let mut rejections = Rejections::default();
+            self.check_cardinality_limits(build_scoping(), limits, entries, &mut rejections)
+                .unwrap();
+            rejections
+        }
+    }
+
+    fn entries(hashes: std::ops::Range<u32>, namespace: MetricNamespace) -> Vec<Entry> {
+        hashes
+            .map(|i| Entry::new(EntryId(i as usize), namespace, i))
+            .collect()
+    }
+
+    #[test]
+    fn test_limiter_accept_previously_seen() {
+        let limiter = build_limiter();
+        let mut limit = build_limit(5);
+
+        // 6 items, limit is 5 -> 1 rejection.
+        let rejected =
+            limiter.test_limits(&[limit.clone()], entries(0..6, MetricNamespace::Custom));
+        assert_eq!(rejected.0, HashSet::from([EntryId(5)]));
+
+        // Already accepted items are still accepted with a smaller limit.
+        limit.limit = 3;
+        let rejected2 =
+            limiter.test_limits(&[limit.clone()], entries(0..6, MetricNamespace::Custom));
+        assert_eq!(rejected2, rejected);
+
+        // A higher limit accepts everything.
+        limit.limit = 6;
+        let rejected3 = limiter.test_limits(&[limit], entries(0..6, MetricNamespace::Custom));
+        assert!(rejected3.0.is_empty());
+    }
+
+    #[test]
+    fn test_limiter_namespace() {
+        let limiter = build_limiter();
+        let limit = build_limit(1);
+
+        let rejected = limiter.test_limits(&[limit.clone()], entries(0..5, MetricNamespace::Spans));
+        assert!(rejected.0.is_empty());
+
+        let rejected = limiter.test_limits(&[limit], entries(0..5, MetricNamespace::Custom));
+        assert_eq!(rejected.0.len(), 4);
+    }
+
+    #[test]
+    fn test_limiter_sliding_window() {
+        let mut limiter = build_limiter();
+        let limit = build_limit(1);
+        let granularity = limit.window.granularity_seconds;
+
+        let rejected =
+            limiter.test_limits(&[limit.clone()], entries(0..1, MetricNamespace::Custom));
+        assert!(rejected.0.is_empty());
+
+        // Still within the window, the hash is remembered.
+        limiter.time_offset = Duration::from_secs(granularity);
+        let rejected =
+            limiter.test_limits(&[limit.clone()], entries(0..2, MetricNamespace::Custom));
+        assert_eq!(rejected.0, HashSet::from([EntryId(1)]));
+
+        // Moved past the window, the old hash expired.
+        limiter.time_offset = Duration::from_secs(limit.window.window_seconds + granularity);
+        let rejected = limiter.test_limits(&[limit], entries(1..3, MetricNamespace::Custom));
+        assert_eq!(rejected.0, HashSet::from([EntryId(2)]));
+    }
+
+    #[test]
+    fn test_limiter_passive_separate_state() {
+        let limiter = build_limiter();
+        let enforced = build_limit(5);
+        let passive = CardinalityLimit {
+            id: "passive".to_owned(),
+            passive: true,
+            limit: 2,
+            ..enforced.clone()
+        };
+
+        // The passive limit rejects hashes without claiming space in the enforced limit.
+        let rejected = limiter.test_limits(
+            &[passive.clone(), enforced.clone()],
+            entries(0..5, MetricNamespace::Custom),
+        );
+        assert_eq!(
+            rejected.0,
+            HashSet::from([EntryId(2), EntryId(3), EntryId(4)])
+        );
+
+        let rejected = limiter.test_limits(&[enforced], entries(0..6, MetricNamespace::Custom));
+        assert_eq!(rejected.0, HashSet::from([EntryId(5)]));
+        assert_eq!(limiter.inner.lock().unwrap().scopes.len(), 2);
+    }
+
+    #[test]
+    fn test_limiter_vacuum() {
+        let mut limiter = build_limiter();
+        let limit = build_limit(10);
+
+        limiter.test_limits(&[limit.clone()], entries(0..5, MetricNamespace::Custom));
+        assert_eq!(limiter.inner.lock().unwrap().scopes.len(), 1);
+
+        // Fast forward past the window, all slots expire and the scope is removed.
+        limiter.time_offset = Duration::from_secs(limit.window.window_seconds * 2);
+        limiter.test_limits(&[limit], entries(0..0, MetricNamespace::Custom));
+        assert!(limiter.inner.lock().unwrap().scopes.is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use std::hash::Hasher as _;
+
+use hash32::{FnvHasher, Hasher as _};
+use relay_base_schema::metrics::MetricNamespace;
+use relay_base_schema::project::ProjectId;
+use relay_common::time::UnixTimestamp;
+
+use crate::limiter::{Entry, Scoping};
+use crate::window::Slot;
+use crate::{CardinalityLimit, CardinalityScope, OrganizationId, SlidingWindow};
+
+/// A quota scoping extracted from a [`CardinalityLimit`] and a [`Scoping`].
+///
+/// Every limit is tracked separately, limits with the same scope do not share their state.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct QuotaScoping {
+    /// A stable hash of the limit's id, see [`limit_hash`].
+    pub limit_hash: u32,
+    pub window: SlidingWindow,
+    pub namespace: Option<MetricNamespace>,
+    pub organization_id: Option<OrganizationId>,
+    pub project_id: Option<ProjectId>,
+}
+
+impl QuotaScoping {
+    /// Creates a new [`QuotaScoping`] from a [`Scoping`] and [`CardinalityLimit`].
+    ///
+    /// Returns `None` for limits with scope [`CardinalityScope::Unknown`].
+    pub fn new(scoping: Scoping, limit: &CardinalityLimit) -> Option<Self> {
+        let (organization_id, project_id) = match limit.scope {
+            CardinalityScope::Organization => (Some(scoping.organization_id), None),
+            // Invalid/unknown scope -> ignore the limit.
+            CardinalityScope::Unknown => return None,
+        };
+
+        Some(Self {
+            limit_hash: limit_hash(&limit.id),
+            window: limit.window,
+            namespace: limit.namespace,
+            organization_id,
+            project_id,
+        })
+    }
+
+    /// Wether the scoping applies to the passed entry.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        self.namespace.is_none() || self.namespace == Some(entry.namespace)
+    }
+
+    /// Returns all slots of the sliding window for a specific timestamp.
+    pub fn slots(&self, timestamp: UnixTimestamp) -> impl Iterator<Item = Slot> {
+        self.window.iter(timestamp)
+    }
+
+    /// Returns the currently active slot of the sliding window.
+    pub fn active_slot(&self, timestamp: UnixTimestamp) -> Slot {
+        self.window.active_slot(timestamp)
+    }
+}
+
+/// Returns a stable hash of a limit id.
+///
+/// The hash identifies the state of a limit in memory without copying the id.
+pub fn limit_hash(id: &str) -> u32 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(id.as_bytes());
+    hasher.finish32()
+}
@@ -5,8 +5,9 @@ use std::time::Duration;
 use relay_common::time::UnixTimestamp;
 use relay_statsd::metric;
 
-use crate::redis::QuotaScoping;
+use crate::quota::QuotaScoping;
 use crate::statsd::{CardinalityLimiterCounters, CardinalityLimiterTimers};
+use crate::vacuum::Vacuum;
 use crate::window::Slot;
 
 /// Cached outcome, wether the item can be accepted, rejected or the cache has no information about
@@ -33,8 +34,7 @@ impl Cache {
         Self {
             inner: RwLock::new(Inner {
                 cache: Default::default(),
-                vacuum_interval,
-                last_vacuum: UnixTimestamp::from_secs(0),
+                vacuum: Vacuum::new(vacuum_interval),
             }),
         }
     }
@@ -146,18 +146,15 @@ impl<'a> CacheUpdate<'a> {
 #[derive(Debug)]
 struct Inner {
     cache: hashbrown::HashMap<QuotaScoping, ScopedCache>,
-    vacuum_interval: Duration,
-    last_vacuum: UnixTimestamp,
+    vacuum: Vacuum,
 }
 
 impl Inner {
     fn vacuum(&mut self, ts: UnixTimestamp) {
         // Debounce the vacuuming.
-        let secs_since_last_vacuum = ts.as_secs().saturating_sub(self.last_vacuum.as_secs());
-        if secs_since_last_vacuum < self.vacuum_interval.as_secs() {
+        if !self.vacuum.is_due(ts) {
             return;
         }
-        self.last_vacuum = ts;
 
         let expired = metric!(timer(CardinalityLimiterTimers::CacheVacuum), {
             self.cache
@@ -218,6 +215,7 @@ mod tests {
         let cache = Cache::new(Duration::from_secs(180));
 
         let scope = QuotaScoping {
+            limit_hash: 0,
             window: SlidingWindow {
                 window_seconds: 100,
                 granularity_seconds: 10,
@@ -282,6 +280,7 @@ mod tests {
         let cache = Cache::new(Duration::from_secs(180));
 
         let scope1 = QuotaScoping {
+            limit_hash: 0,
             window: SlidingWindow {
                 window_seconds: 100,
                 granularity_seconds: 10,
@@ -326,6 +325,7 @@ mod tests {
         let cache = Cache::new(vacuum_interval);
 
         let scope1 = QuotaScoping {
+            limit_hash: 0,
             window: SlidingWindow {
                 window_seconds: vacuum_interval.as_secs() * 10,
                 granularity_seconds: vacuum_interval.as_secs() * 2,
@@ -5,20 +5,22 @@ use relay_statsd::metric;
 
 use crate::{
     limiter::{Entry, EntryId, Limiter, Rejections, Scoping},
+    quota::QuotaScoping,
     redis::{
         cache::{Cache, CacheOutcome},
         script::{CardinalityScript, Status},
     },
     statsd::{CardinalityLimiterCounters, CardinalityLimiterHistograms, CardinalityLimiterTimers},
     window::Slot,
-    CardinalityLimit, CardinalityScope, OrganizationId, Result, SlidingWindow,
+    CardinalityLimit, Result,
 };
-use relay_base_schema::metrics::MetricNamespace;
-use relay_base_schema::project::ProjectId;
 use relay_common::time::UnixTimestamp;
 
 /// Key prefix used for Redis keys.
-const KEY_PREFIX: &str = "relay:cardinality";
+///
+/// The version is part of the prefix and must be increased when the layout of the keys changes.
+/// Version 2 tracks every limit in separate sets.
+const KEY_PREFIX: &str = "relay:cardinality:v2";
 
 /// Configuration options for the [`RedisSetLimiter`].
 pub struct RedisSetLimiterOptions {
@@ -70,7 +72,7 @@ impl RedisSetLimiter {
 
         let keys = scope
             .slots(timestamp)
-            .map(|slot| scope.into_redis_key(slot));
+            .map(|slot| scope.into_redis_key(state.id, slot));
         let hashes = entries.iter().map(|entry| entry.hash);
 
         // The expiry is a off by `window.granularity_seconds`,
@@ -92,16 +94,16 @@ impl RedisSetLimiter {
 }
 
 impl Limiter for RedisSetLimiter {
-    fn check_cardinality_limits<E, R>(
+    fn check_cardinality_limits<'a, E, R>(
         &self,
         scoping: Scoping,
-        limits: &[CardinalityLimit],
+        limits: &'a [CardinalityLimit],
         entries: E,
         rejections: &mut R,
     ) -> Result<()>
     where
         E: IntoIterator<Item = Entry>,
-        R: Rejections,
+        R: Rejections<'a>,
     {
         let timestamp = UnixTimestamp::now();
         // Allows to fast forward time in tests.
@@ -126,7 +128,7 @@ impl Limiter for RedisSetLimiter {
                     }
                     CacheOutcome::Rejected => {
                         // Rejected, add it to the rejected list and move on.
-                        rejections.reject(entry.id);
+                        rejections.reject(state.cardinality_limit, entry.id);
                         state.cache_hit();
                         state.rejected();
                     }
@@ -158,7 +160,7 @@ impl Limiter for RedisSetLimiter {
             let mut cache = self.cache.update(state.scope, timestamp); // Acquire a write lock.
             for (entry, status) in results {
                 if status.is_rejected() {
-                    rejections.reject(entry.id);
+                    rejections.reject(state.cardinality_limit, entry.id);
                     state.rejected();
                 } else {
                     cache.accept(entry.hash);
@@ -172,51 +174,16 @@ impl Limiter for RedisSetLimiter {
     }
 }
 
-/// A quota scoping extracted from a [`CardinalityLimit`] and a [`Scoping`].
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
-pub struct QuotaScoping {
-    pub window: SlidingWindow,
-    pub namespace: Option<MetricNamespace>,
-    pub organization_id: Option<OrganizationId>,
-    pub project_id: Option<ProjectId>,
-}
-
 impl QuotaScoping {
-    /// Creates a new [`QuotaScoping`] from a [`Scoping`] and [`CardinalityLimit`].
-    ///
-    /// Returns `None` for limits with scope [`CardinalityScope::Unknown`].
-    pub fn new(scoping: Scoping, limit: &CardinalityLimit) -> Option<Self> {
-        let (organization_id, project_id) = match limit.scope {
-            CardinalityScope::Organization => (Some(scoping.organization_id), None),
-            // Invalid/unknown scope -> ignore the limit.
-            CardinalityScope::Unknown => return None,
-        };
-
-        Some(Self {
-            window: limit.window,
-            namespace: limit.namespace,
-            organization_id,
-            project_id,
-        })
-    }
-
-    /// Wether the scoping applies to the passed entry.
-    pub fn matches(&self, entry: &Entry) -> bool {
-        self.namespace.is_none() || self.namespace == Some(entry.namespace)
-    }
-
-    /// Returns all slots of the sliding window for a specific timestamp.
-    pub fn slots(&self, timestamp: UnixTimestamp) -> impl Iterator<Item = Slot> {
-        self.window.iter(timestamp)
-    }
-
-    /// Turns the scoping into a Redis key for the passed slot.
-    fn into_redis_key(self, slot: Slot) -> String {
+    /// Turns the scoping into a Redis key for the passed limit id and slot.
+    fn into_redis_key(self, limit_id: &str, slot: Slot) -> String {
         let organization_id = self.organization_id.unwrap_or(0);
         let project_id = self.project_id.map(|p| p.value()).unwrap_or(0);
         let namespace = self.namespace.map(|ns| ns.as_str()).unwrap_or("");
 
-        format!("{KEY_PREFIX}:scope-{{{organization_id}-{project_id}-{namespace}}}-{slot}")
+        format!(
+            "{KEY_PREFIX}:scope-{{{organization_id}-{project_id}-{namespace}}}-{limit_id}-{slot}"
+        )
     }
 }
 
@@ -224,6 +191,8 @@ impl QuotaScoping {
 struct LimitState<'a> {
     /// Id of the original limit.
     pub id: &'a str,
+    /// The original limit.
+    pub cardinality_limit: &'a CardinalityLimit,
     /// Entries which are relevant for the quota.
     pub entries: Vec<RedisEntry>,
     /// Scoping of the quota.
@@ -241,6 +210,7 @@ impl<'a> LimitState<'a> {
     pub fn new(scoping: Scoping, limit: &'a CardinalityLimit) -> Option<Self> {
         Some(Self {
             id: &limit.id,
+            cardinality_limit: limit,
             entries: Vec::new(),
             scope: QuotaScoping::new(scoping, limit)?,
             limit: limit.limit,
@@ -378,8 +348,8 @@ mod tests {
     #[derive(Debug, Default, PartialEq, Eq)]
     struct Rejections(HashSet<EntryId>);
 
-    impl super::Rejections for Rejections {
-        fn reject(&mut self, entry_id: EntryId) {
+    impl<'a> super::Rejections<'a> for Rejections {
+        fn reject(&mut self, _limit: &'a CardinalityLimit, entry_id: EntryId) {
             self.0.insert(entry_id);
         }
     }
@@ -468,6 +438,7 @@ mod tests {
         let scoping = new_scoping(&limiter);
         let mut limit = CardinalityLimit {
             id: "limit".to_owned(),
+            passive: false,
             window: SlidingWindow {
                 window_seconds: 3600,
                 granularity_seconds: 360,
@@ -500,6 +471,7 @@ mod tests {
 
         let limits = &[CardinalityLimit {
             id: "limit".to_owned(),
+            passive: false,
             window: SlidingWindow {
                 window_seconds: 3600,
                 granularity_seconds: 360,
@@ -529,6 +501,7 @@ mod tests {
         let scoping = new_scoping(&limiter);
         let limits = &[CardinalityLimit {
             id: "limit".to_owned(),
+            passive: false,
             window: SlidingWindow {
                 window_seconds: 3600,
                 granularity_seconds: 360,
@@ -557,6 +530,7 @@ mod tests {
         };
         let limits = &[CardinalityLimit {
             id: "limit".to_owned(),
+            passive: false,
             window,
             limit: 1,
             scope: CardinalityScope::Organization,
@@ -599,6 +573,7 @@ mod tests {
 
         let limits = &[CardinalityLimit {
             id: "limit".to_owned(),
+            passive: false,
             window: SlidingWindow {
                 window_seconds: 3600,
                 granularity_seconds: 360,
@@ -630,6 +605,7 @@ mod tests {
         let limits = &[
             CardinalityLimit {
                 id: "limit1".to_owned(),
+                passive: false,
                 window: SlidingWindow {
                     window_seconds: 3600,
                     granularity_seconds: 360,
@@ -640,6 +616,7 @@ mod tests {
             },
             CardinalityLimit {
                 id: "limit2".to_owned(),
+                passive: false,
                 window: SlidingWindow {
                     window_seconds: 3600,
                     granularity_seconds: 360,
@@ -650,6 +627,7 @@ mod tests {
             },
             CardinalityLimit {
                 id: "limit3".to_owned(),
+                passive: false,
                 window: SlidingWindow {
                     window_seconds: 3600,
                     granularity_seconds: 360,
@@ -660,6 +638,7 @@ mod tests {
             },
             CardinalityLimit {
                 id: "unknown_skipped".to_owned(),
+                passive: false,
                 window: SlidingWindow {
                     window_seconds: 3600,
                     granularity_seconds: 360,
@@ -706,6 +685,7 @@ mod tests {
         };
         let limits = &[CardinalityLimit {
             id: "limit".to_owned(),
+            passive: false,
             window,
             limit: 100,
             scope: CardinalityScope::Organization,
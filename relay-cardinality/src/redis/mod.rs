@@ -2,6 +2,4 @@ mod cache;
 mod limiter;
 mod script;
 
-use self::limiter::*;
-
 pub use self::limiter::{RedisSetLimiter, RedisSetLimiterOptions};
@@ -0,0 +1,108 @@
+use hashbrown::HashMap;
+use relay_base_schema::project::ProjectId;
+use relay_common::time::UnixTimestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::limiter::{CardinalityItem, Scoping};
+use crate::OrganizationId;
+
+/// Report of all items rejected by a passive [`CardinalityLimit`](crate::CardinalityLimit).
+///
+/// Passive limits do not drop items, instead the rejected items are summarized in a report,
+/// which lists the metric names and tag keys that contribute the most new hashes to the limit.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CardinalityReport {
+    /// The organization the report belongs to.
+    pub organization_id: OrganizationId,
+    /// The project the report belongs to.
+    pub project_id: ProjectId,
+    /// Id of the passive limit that rejected the items.
+    pub limit_id: String,
+    /// Time at which the limit was checked.
+    pub timestamp: UnixTimestamp,
+    /// Total amount of items rejected by the limit.
+    pub rejected: u64,
+    /// Metric names with the most rejected items, ordered by descending count.
+    pub top_names: Vec<CardinalityOffender>,
+    /// Tag keys with the most rejected items, ordered by descending count.
+    pub top_tags: Vec<CardinalityOffender>,
+}
+
+impl CardinalityReport {
+    /// Creates a new report for a limit from all items it rejected.
+    ///
+    /// Only the `top_n` metric names and tag keys are retained.
+    pub(crate) fn new<'a, T, I>(scoping: Scoping, limit_id: &str, items: I, top_n: usize) -> Self
+    where
+        T: CardinalityItem + 'a,
+        I: IntoIterator<Item = &'a T>,
+    {
+        let mut rejected = 0;
+        let mut names = HashMap::<&str, u64>::new();
+        let mut tags = HashMap::<&str, u64>::new();
+
+        for item in items {
+            rejected += 1;
+            *names.entry(item.name()).or_default() += 1;
+            for key in item.tag_keys() {
+                *tags.entry(key).or_default() += 1;
+            }
+        }
+
+        Self {
+            organization_id: scoping.organization_id,
+            project_id: scoping.project_id,
+            limit_id: limit_id.to_owned(),
+            timestamp: UnixTimestamp::now(),
+            rejected,
+            top_names: CardinalityOffender::top_n(names, top_n),
+            top_tags: CardinalityOffender::top_n(tags, top_n),
+        }
+    }
+}
+
+/// A metric name or tag key contributing rejected items to a [`CardinalityReport`].
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CardinalityOffender {
+    /// The metric name or tag key.
+    pub name: String,
+    /// Amount of rejected items attributed to this name.
+    pub count: u64,
+}
+
+impl CardinalityOffender {
+    /// Returns the `n` offenders with the highest counts.
+    ///
+    /// Ties are broken by name to keep the reports stable.
+    fn top_n(counts: HashMap<&str, u64>, n: usize) -> Vec<Self> {
+        let mut offenders = counts.into_iter().collect::<Vec<_>>();
+        offenders.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        offenders
+            .into_iter()
+            .take(n)
+            .map(|(name, count)| Self {
+                name: name.to_owned(),
+                count,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offenders_top_n() {
+        let counts = HashMap::from([("a", 1), ("b", 3), ("c", 3), ("d", 2)]);
+
+        let top = CardinalityOffender::top_n(counts, 3);
+        let top = top
+            .iter()
+            .map(|o| (o.name.as_str(), o.count))
+            .collect::<Vec<_>>();
+
+        assert_eq!(top, vec![("b", 3), ("c", 3), ("d", 2)]);
+    }
+}
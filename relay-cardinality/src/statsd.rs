@@ -6,13 +6,11 @@ pub enum CardinalityLimiterCounters {
     ///
     /// This metric is tagged with:
     ///  - `scope`: The scope of check operation.
-    #[cfg(feature = "redis")]
     Accepted,
     /// Incremented for every rejected item by the cardinality limiter.
     ///
     /// This metric is tagged with:
     ///  - `scope`: The scope of check operation.
-    #[cfg(feature = "redis")]
     Rejected,
     /// Incremented for every hash which was served from the in memory cache.
     ///
@@ -29,14 +27,21 @@ pub enum CardinalityLimiterCounters {
     /// Amount of entries removed from the cache via periodic cleanups.
     #[cfg(feature = "redis")]
     RedisCacheVacuum,
+    /// Amount of expired slots removed from the in memory limiter via periodic cleanups.
+    MemoryVacuum,
+    /// Incremented for every item rejected by a passive limit.
+    ///
+    /// Items rejected by passive limits are not dropped.
+    ///
+    /// This metric is tagged with:
+    ///  - `id`: The id of the passive limit.
+    PassiveRejected,
 }
 
 impl CounterMetric for CardinalityLimiterCounters {
     fn name(&self) -> &'static str {
         match *self {
-            #[cfg(feature = "redis")]
             Self::Accepted => "cardinality.limiter.accepted",
-            #[cfg(feature = "redis")]
             Self::Rejected => "cardinality.limiter.rejected",
             #[cfg(feature = "redis")]
             Self::RedisCacheHit => "cardinality.limiter.redis.cache_hit",
@@ -44,6 +49,8 @@ impl CounterMetric for CardinalityLimiterCounters {
             Self::RedisCacheMiss => "cardinality.limiter.redis.cache_miss",
             #[cfg(feature = "redis")]
             Self::RedisCacheVacuum => "cardinality.limiter.redis.cache_vacuum",
+            Self::MemoryVacuum => "cardinality.limiter.memory.vacuum",
+            Self::PassiveRejected => "cardinality.limiter.passive_rejected",
         }
     }
 }
@@ -58,8 +65,7 @@ pub enum CardinalityLimiterTimers {
     #[cfg(feature = "redis")]
     Redis,
     /// Timer tracking the amount of time spent removing expired values
-    /// from the cardinality cache.
-    #[cfg(feature = "redis")]
+    /// from the cardinality cache or the in memory limiter.
     CacheVacuum,
 }
 
@@ -69,7 +75,6 @@ impl TimerMetric for CardinalityLimiterTimers {
             CardinalityLimiterTimers::CardinalityLimiter => "cardinality.limiter.duration",
             #[cfg(feature = "redis")]
             CardinalityLimiterTimers::Redis => "cardinality.limiter.redis.duration",
-            #[cfg(feature = "redis")]
             CardinalityLimiterTimers::CacheVacuum => {
                 "cardinality.limiter.redis.cache_vacuum.duration"
             }
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use relay_common::time::UnixTimestamp;
+
+/// Debounces periodic cleanups of expired in-memory state.
+///
+/// Shared by all in-memory structures of the cardinality limiter, which need to scan for
+/// expired sliding window slots.
+#[derive(Debug)]
+pub struct Vacuum {
+    interval: Duration,
+    last_vacuum: UnixTimestamp,
+}
+
+impl Vacuum {
+    /// Creates a new [`Vacuum`] which is due at most once per `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_vacuum: UnixTimestamp::from_secs(0),
+        }
+    }
+
+    /// Returns `true` if a vacuum is due at the passed timestamp.
+    ///
+    /// When a vacuum is due, the timestamp is recorded as the last vacuum.
+    pub fn is_due(&mut self, ts: UnixTimestamp) -> bool {
+        let secs_since_last_vacuum = ts.as_secs().saturating_sub(self.last_vacuum.as_secs());
+        if secs_since_last_vacuum < self.interval.as_secs() {
+            return false;
+        }
+
+        self.last_vacuum = ts;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vacuum_debounce() {
+        let mut vacuum = Vacuum::new(Duration::from_secs(30));
+        let now = UnixTimestamp::from_secs(1_000);
+
+        assert!(vacuum.is_due(now));
+        assert!(!vacuum.is_due(now));
+        assert!(!vacuum.is_due(now + Duration::from_secs(29)));
+        assert!(vacuum.is_due(now + Duration::from_secs(30)));
+    }
+}
@@ -916,6 +916,22 @@ struct Cache {
     eviction_interval: u32,
     /// Interval for fetching new global configs from the upstream, in seconds.
     global_config_fetch_interval: u32,
+    /// Interval for synchronizing reservoir sampling rules with the upstream, in seconds.
+    ///
+    /// Relays without access to Redis report the local consumption of reservoir rules to the
+    /// upstream and receive the consumption of all Relays back. Defaults to `0`, which disables the
+    /// synchronization.
+    reservoir_sync_interval: u32,
+    /// Maximum time in seconds the upstream may hold a request for project config updates.
+    ///
+    /// If set, Relay continuously long-polls the upstream for changes to inbound filters, quotas,
+    /// sampling rules, and transaction name rules of cached projects, and applies them without
+    /// waiting for the project states to expire. This must be lower than `http.timeout`. Defaults
+    /// to `0`, which disables the updates.
+    ///
+    /// Only Sentry serves project config updates. Enable this only if the upstream is Sentry and
+    /// not another Relay.
+    project_updates_timeout: u32,
 }
 
 impl Default for Cache {
@@ -933,6 +949,8 @@ impl Default for Cache {
             file_interval: 10,                // 10 seconds
             eviction_interval: 60,            // 60 seconds
             global_config_fetch_interval: 10, // 10 seconds
+            reservoir_sync_interval: 0,       // disabled
+            project_updates_timeout: 0,       // disabled
         }
     }
 }
@@ -1279,6 +1297,107 @@ pub struct AwsConfig {
 pub struct GeoIpConfig {
     /// The path to GeoIP database.
     path: Option<PathBuf>,
+    /// The path to a GeoIP ASN or ISP database.
+    ///
+    /// If configured, events are enriched with the autonomous system number and the ISP of the
+    /// user's IP address.
+    asn_path: Option<PathBuf>,
+    /// The path to a GeoIP Anonymous IP database.
+    ///
+    /// If configured, events from VPNs, proxies, and hosting providers are flagged in `user.geo`.
+    anonymous_ip_path: Option<PathBuf>,
+}
+
+/// Configuration for resolving minified JavaScript stack traces with source maps.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct SourceMaps {
+    /// The path to a directory containing source maps.
+    ///
+    /// Source maps are looked up by the project ID and release of the event, and the path of the
+    /// minified file's URL with a `.map` extension. For example, the source map for
+    /// `https://example.com/static/app.min.js` in release `1.0` of project `42` is read from
+    /// `42/1.0/static/app.min.js.map` in this directory.
+    pub path: Option<PathBuf>,
+    /// Fetches source maps that are not available locally from the upstream.
+    ///
+    /// Source maps are fetched in the background from `/api/0/relays/sourcemaps/` by the event's
+    /// project, release, and distribution. Events are not held back while fetching, so only
+    /// subsequent events are resolved. Relays do not serve this endpoint, so fetching is disabled
+    /// when the upstream responds with `404 Not Found`.
+    ///
+    /// Defaults to `false`.
+    pub fetch_upstream: bool,
+    /// The maximum size of parsed source maps kept in memory.
+    ///
+    /// Defaults to 100 MiB.
+    pub max_cache_size: ByteSize,
+    /// The time in seconds after which cached source maps are loaded again.
+    ///
+    /// This also applies to source maps that were not found. Defaults to 300 seconds, 5 minutes.
+    pub cache_expiry: u32,
+}
+
+impl Default for SourceMaps {
+    fn default() -> Self {
+        Self {
+            path: None,
+            fetch_upstream: false,
+            max_cache_size: ByteSize::mebibytes(100),
+            cache_expiry: 300,
+        }
+    }
+}
+
+/// Configuration for symbolicating native stack traces with local debug files.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct Symbols {
+    /// The path to a directory containing debug files.
+    ///
+    /// Breakpad symbol files are looked up in the layout of a Breakpad symbol store, for example
+    /// `libapp.so/<BREAKPAD_ID>/libapp.so.sym`. ELF debug files are looked up by their build ID
+    /// in `.build-id/<xx>/<rest>.debug`.
+    pub path: Option<PathBuf>,
+    /// The maximum size of parsed debug files kept in memory.
+    ///
+    /// Defaults to 500 MiB.
+    pub max_cache_size: ByteSize,
+    /// The time in seconds after which cached debug files are loaded again.
+    ///
+    /// This also applies to debug files that were not found. Defaults to 300 seconds, 5 minutes.
+    pub cache_expiry: u32,
+}
+
+impl Default for Symbols {
+    fn default() -> Self {
+        Self {
+            path: None,
+            max_cache_size: ByteSize::mebibytes(500),
+            cache_expiry: 300,
+        }
+    }
+}
+
+/// Configuration for deduplicating repeated events.
+///
+/// Deduplication is enabled per project. This only bounds the memory used by all projects.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct Deduplication {
+    /// The maximum number of event IDs and fingerprints remembered across all projects.
+    ///
+    /// Once the limit is reached, the least recently seen entries are evicted. Defaults to
+    /// 100,000.
+    pub cache_size: usize,
+}
+
+impl Default for Deduplication {
+    fn default() -> Self {
+        Self {
+            cache_size: 100_000,
+        }
+    }
 }
 
 /// Cardinality Limiter configuration options.
@@ -1291,12 +1410,26 @@ pub struct CardinalityLimiter {
     ///
     /// Defaults to 180 seconds, 3 minutes.
     pub cache_vacuum_interval: u64,
+    /// Maximum number of metric names and tag keys listed in reports of passive limits.
+    ///
+    /// Defaults to 10.
+    pub report_top_n: usize,
+    /// Enforces cardinality limits in Relays without processing.
+    ///
+    /// Cardinality is tracked in memory of this Relay instance and not shared with other Relays,
+    /// which makes the enforced limits approximate. Processing Relays always enforce cardinality
+    /// limits through Redis.
+    ///
+    /// Defaults to `false`.
+    pub local_enforcement: bool,
 }
 
 impl Default for CardinalityLimiter {
     fn default() -> Self {
         Self {
             cache_vacuum_interval: 180,
+            report_top_n: 10,
+            local_enforcement: false,
         }
     }
 }
@@ -1338,6 +1471,12 @@ struct ConfigValues {
     #[serde(default)]
     geoip: GeoIpConfig,
     #[serde(default)]
+    sourcemaps: SourceMaps,
+    #[serde(default)]
+    symbols: Symbols,
+    #[serde(default)]
+    deduplication: Deduplication,
+    #[serde(default)]
     cardinality_limiter: CardinalityLimiter,
 }
 
@@ -1907,6 +2046,34 @@ impl Config {
         Duration::from_secs(self.values.cache.global_config_fetch_interval.into())
     }
 
+    /// Returns the interval for synchronizing reservoir sampling rules with the upstream.
+    ///
+    /// Returns `None` if the synchronization is disabled. Processing Relays synchronize reservoirs
+    /// through Redis and never report to the upstream.
+    pub fn reservoir_sync_interval(&self) -> Option<Duration> {
+        if self.processing_enabled() {
+            return None;
+        }
+
+        match self.values.cache.reservoir_sync_interval {
+            0 => None,
+            secs => Some(Duration::from_secs(secs.into())),
+        }
+    }
+
+    /// Returns the time the upstream may hold a request for project config updates.
+    ///
+    /// Returns `None` if project config updates are disabled. The timeout is capped one second
+    /// below the timeout of upstream requests.
+    pub fn project_updates_timeout(&self) -> Option<Duration> {
+        let secs = match self.values.cache.project_updates_timeout {
+            0 => return None,
+            secs => secs.min(self.values.http.timeout.saturating_sub(1)).max(1),
+        };
+
+        Some(Duration::from_secs(secs.into()))
+    }
+
     /// Returns the path of the buffer file if the `cache.persistent_envelope_buffer.path` is configured.
     pub fn spool_envelopes_path(&self) -> Option<PathBuf> {
         self.values
@@ -2096,6 +2263,61 @@ impl Config {
             .or(self.values.processing.geoip_path.as_deref())
     }
 
+    /// The path to the GeoIp ASN or ISP database used to enrich events with network operators.
+    pub fn geoip_asn_path(&self) -> Option<&Path> {
+        self.values.geoip.asn_path.as_deref()
+    }
+
+    /// The path to the GeoIp Anonymous IP database used to flag anonymous IP addresses.
+    pub fn geoip_anonymous_ip_path(&self) -> Option<&Path> {
+        self.values.geoip.anonymous_ip_path.as_deref()
+    }
+
+    /// Returns `true` if minified JavaScript stack traces should be resolved with source maps.
+    pub fn sourcemaps_enabled(&self) -> bool {
+        self.sourcemaps_path().is_some() || self.sourcemaps_fetch_upstream()
+    }
+
+    /// The path to the directory containing source maps.
+    pub fn sourcemaps_path(&self) -> Option<&Path> {
+        self.values.sourcemaps.path.as_deref()
+    }
+
+    /// Returns `true` if source maps should be fetched from the upstream.
+    pub fn sourcemaps_fetch_upstream(&self) -> bool {
+        self.values.sourcemaps.fetch_upstream
+    }
+
+    /// The maximum size in bytes of parsed source maps kept in memory.
+    pub fn sourcemaps_max_cache_size(&self) -> usize {
+        self.values.sourcemaps.max_cache_size.as_bytes()
+    }
+
+    /// The time after which cached source maps are loaded again.
+    pub fn sourcemaps_cache_expiry(&self) -> Duration {
+        Duration::from_secs(self.values.sourcemaps.cache_expiry.into())
+    }
+
+    /// The path to the directory containing debug files for native symbolication.
+    pub fn symbols_path(&self) -> Option<&Path> {
+        self.values.symbols.path.as_deref()
+    }
+
+    /// The maximum size in bytes of parsed debug files kept in memory.
+    pub fn symbols_max_cache_size(&self) -> usize {
+        self.values.symbols.max_cache_size.as_bytes()
+    }
+
+    /// The time after which cached debug files are loaded again.
+    pub fn symbols_cache_expiry(&self) -> Duration {
+        Duration::from_secs(self.values.symbols.cache_expiry.into())
+    }
+
+    /// The maximum number of event IDs and fingerprints remembered for deduplication.
+    pub fn deduplication_cache_size(&self) -> usize {
+        self.values.deduplication.cache_size
+    }
+
     /// Maximum future timestamp of ingested data.
     ///
     /// Events past this timestamp will be adjusted to `now()`. Sessions will be dropped.
@@ -2159,6 +2381,18 @@ impl Config {
         Duration::from_secs(self.values.cardinality_limiter.cache_vacuum_interval)
     }
 
+    /// Maximum number of metric names and tag keys listed in cardinality reports.
+    ///
+    /// Cardinality reports are emitted for passive cardinality limits.
+    pub fn cardinality_limiter_report_top_n(&self) -> usize {
+        self.values.cardinality_limiter.report_top_n
+    }
+
+    /// Returns `true` if cardinality limits are enforced in memory by non-processing Relays.
+    pub fn cardinality_limiter_local_enforcement(&self) -> bool {
+        self.values.cardinality_limiter.local_enforcement
+    }
+
     /// Creates an [`AggregatorConfig`] that is compatible with every other aggregator.
     ///
     /// A lossless aggregator can be put in front of any of the configured aggregators without losing data that the configured aggregator would keep.
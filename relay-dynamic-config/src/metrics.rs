@@ -26,6 +26,11 @@ pub struct Metrics {
     /// Note that removing tags does not drop the overall metric bucket.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub denied_tags: Vec<TagBlock>,
+    /// Applies data scrubbing to the tag values of all metric buckets before aggregation.
+    ///
+    /// Tag values are scrubbed with the project's PII config and datascrubbing settings.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub scrub_tag_values: bool,
 }
 
 impl Metrics {
@@ -34,6 +39,7 @@ impl Metrics {
         self.cardinality_limits.is_empty()
             && self.denied_names.is_empty()
             && self.denied_tags.is_empty()
+            && !self.scrub_tag_values
     }
 }
 
@@ -3,7 +3,7 @@ use std::collections::BTreeSet;
 use relay_auth::PublicKey;
 use relay_base_schema::spans::SpanAttribute;
 use relay_event_normalization::{
-    BreakdownsConfig, MeasurementsConfig, PerformanceScoreConfig, SpanDescriptionRule,
+    BreakdownsConfig, MeasurementsConfig, PerformanceScoreConfig, SpanDescriptionRule, TagRule,
     TransactionNameRule,
 };
 use relay_filter::FiltersConfig;
@@ -92,6 +92,12 @@ pub struct ProjectConfig {
     /// Configuration for metrics.
     #[serde(default, skip_serializing_if = "skip_metrics")]
     pub metrics: ErrorBoundary<Metrics>,
+    /// Configuration for dropping repeated error events.
+    #[serde(skip_serializing_if = "DeduplicationConfig::is_disabled")]
+    pub deduplication: DeduplicationConfig,
+    /// Rules for renaming, deriving, and removing tags during event normalization.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tag_rules: Vec<TagRule>,
 }
 
 impl ProjectConfig {
@@ -133,6 +139,120 @@ impl Default for ProjectConfig {
             tx_name_ready: false,
             span_description_rules: None,
             metrics: Default::default(),
+            deduplication: DeduplicationConfig::default(),
+            tag_rules: Vec::new(),
+        }
+    }
+}
+
+/// Configuration for dropping repeated error events.
+///
+/// Events are considered duplicates if they have the same event ID, or the same exceptions and
+/// top stack frames, as an event of the same project that was seen within the window.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DeduplicationConfig {
+    /// Whether repeated events are dropped.
+    pub is_enabled: bool,
+    /// The time in seconds after the first event within which repeated events are dropped.
+    ///
+    /// Defaults to 60 seconds.
+    pub window: u64,
+}
+
+impl DeduplicationConfig {
+    /// Returns `true` if deduplication is disabled.
+    pub fn is_disabled(&self) -> bool {
+        !self.is_enabled
+    }
+}
+
+impl Default for DeduplicationConfig {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            window: 60,
+        }
+    }
+}
+
+/// A partial update of a [`ProjectConfig`] pushed by the upstream.
+///
+/// Each section replaces the respective field of the project config entirely. Sections that are
+/// not set remain unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProjectConfigPatch {
+    /// Replaces [`ProjectConfig::filter_settings`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_settings: Option<FiltersConfig>,
+    /// Replaces [`ProjectConfig::quotas`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quotas: Option<Vec<Quota>>,
+    /// Replaces [`ProjectConfig::sampling`].
+    ///
+    /// To disable sampling, send a sampling config without rules.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<ErrorBoundary<SamplingConfig>>,
+    /// Replaces [`ProjectConfig::tx_name_rules`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_name_rules: Option<Vec<TransactionNameRule>>,
+}
+
+impl ProjectConfigPatch {
+    /// Returns `true` if the patch does not contain any sections.
+    pub fn is_empty(&self) -> bool {
+        self.filter_settings.is_none()
+            && self.quotas.is_none()
+            && self.sampling.is_none()
+            && self.tx_name_rules.is_none()
+    }
+
+    /// Merges a newer patch into this one.
+    ///
+    /// Sections set in `other` take precedence over sections in `self`.
+    pub fn merge(&mut self, other: Self) {
+        let Self {
+            filter_settings,
+            quotas,
+            sampling,
+            tx_name_rules,
+        } = other;
+
+        if filter_settings.is_some() {
+            self.filter_settings = filter_settings;
+        }
+        if quotas.is_some() {
+            self.quotas = quotas;
+        }
+        if sampling.is_some() {
+            self.sampling = sampling;
+        }
+        if tx_name_rules.is_some() {
+            self.tx_name_rules = tx_name_rules;
+        }
+    }
+
+    /// Applies the patch to a project config and sanitizes the replaced sections.
+    pub fn apply(self, config: &mut ProjectConfig) {
+        if let Some(filter_settings) = self.filter_settings {
+            config.filter_settings = filter_settings;
+        }
+
+        if let Some(mut quotas) = self.quotas {
+            quotas.retain(Quota::is_valid);
+            config.quotas = quotas;
+        }
+
+        if let Some(mut sampling) = self.sampling {
+            if let ErrorBoundary::Ok(ref mut sampling_config) = sampling {
+                sampling_config.normalize();
+            }
+            config.sampling = Some(sampling);
+        }
+
+        if let Some(tx_name_rules) = self.tx_name_rules {
+            config.tx_name_rules = tx_name_rules;
         }
     }
 }
@@ -196,8 +316,60 @@ pub struct LimitedProjectConfig {
     /// relays that might still need them.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub span_description_rules: Option<Vec<SpanDescriptionRule>>,
+    #[serde(skip_serializing_if = "DeduplicationConfig::is_disabled")]
+    pub deduplication: DeduplicationConfig,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tag_rules: Vec<TagRule>,
 }
 
 fn is_false(value: &bool) -> bool {
     !*value
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_patch() {
+        let mut config: ProjectConfig = serde_json::from_value(serde_json::json!({
+            "filterSettings": {"webCrawlers": {"isEnabled": true}},
+            "quotas": [{"id": "a", "limit": 0, "reasonCode": "a"}],
+        }))
+        .unwrap();
+
+        let patch: ProjectConfigPatch = serde_json::from_value(serde_json::json!({
+            "filterSettings": {"localhost": {"isEnabled": true}},
+        }))
+        .unwrap();
+
+        patch.apply(&mut config);
+
+        assert!(config.filter_settings.localhost.is_enabled);
+        assert!(!config.filter_settings.web_crawlers.is_enabled);
+        // Sections that are not in the patch remain unchanged.
+        assert_eq!(config.quotas.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let mut patch: ProjectConfigPatch = serde_json::from_value(serde_json::json!({
+            "filterSettings": {"localhost": {"isEnabled": true}},
+            "quotas": [],
+        }))
+        .unwrap();
+
+        let newer: ProjectConfigPatch = serde_json::from_value(serde_json::json!({
+            "filterSettings": {"webCrawlers": {"isEnabled": true}},
+        }))
+        .unwrap();
+
+        patch.merge(newer);
+
+        let filter_settings = patch.filter_settings.unwrap();
+        assert!(filter_settings.web_crawlers.is_enabled);
+        assert!(!filter_settings.localhost.is_enabled);
+        assert_eq!(patch.quotas.map(|quotas| quotas.len()), Some(0));
+        assert!(patch.sampling.is_none());
+    }
+}
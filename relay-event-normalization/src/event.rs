@@ -15,7 +15,7 @@ use relay_event_schema::processor::{self, MaxChars, ProcessingAction, Processing
 use relay_event_schema::protocol::{
     AsPair, Context, ContextInner, Contexts, DeviceClass, Event, EventType, Exception, Headers,
     IpAddr, Level, LogEntry, Measurement, Measurements, NelContext, Request, SpanAttribute,
-    SpanStatus, Tags, Timestamp, User,
+    SpanStatus, TagEntry, Tags, Timestamp, User,
 };
 use relay_protocol::{Annotated, Empty, Error, ErrorKind, Meta, Object, Value};
 use smallvec::SmallVec;
@@ -26,7 +26,7 @@ use crate::utils::{self, MAX_DURATION_MOBILE_MS};
 use crate::{
     breakdowns, legacy, mechanism, schema, span, stacktrace, transactions, trimming, user_agent,
     BreakdownsConfig, DynamicMeasurementsConfig, GeoIpLookup, PerformanceScoreConfig,
-    RawUserAgentInfo, SpanDescriptionRule, TransactionNameConfig,
+    RawUserAgentInfo, SpanDescriptionRule, TagRule, TransactionNameConfig,
 };
 
 /// Configuration for [`normalize_event`].
@@ -104,6 +104,12 @@ pub struct NormalizationConfig<'a> {
     ///
     /// See the event schema for size declarations.
     pub enable_trimming: bool,
+
+    /// Project-defined rules for normalizing tags.
+    ///
+    /// The rules are applied after contexts have been normalized, so that tags can be derived from
+    /// inferred contexts.
+    pub tag_rules: &'a [TagRule],
 }
 
 impl<'a> Default for NormalizationConfig<'a> {
@@ -125,6 +131,7 @@ impl<'a> Default for NormalizationConfig<'a> {
             geoip_lookup: Default::default(),
             enable_trimming: false,
             measurements: None,
+            tag_rules: &[],
         }
     }
 }
@@ -228,6 +235,13 @@ fn normalize(event: &mut Event, meta: &mut Meta, config: &NormalizationConfig) {
     // Some contexts need to be normalized before metrics extraction takes place.
     normalize_contexts(&mut event.contexts);
 
+    if !config.tag_rules.is_empty() {
+        crate::apply_tag_rules(event, config.tag_rules);
+        if let Some(tags) = event.tags.value_mut() {
+            validate_tags(&mut tags.0);
+        }
+    }
+
     if config.normalize_spans && event.ty.value() == Some(&EventType::Transaction) {
         // XXX(iker): span normalization runs in the store processor, but
         // the exclusive time is required for span metrics. Most of
@@ -465,6 +479,23 @@ fn normalize_event_tags(event: &mut Event) {
         }
     });
 
+    validate_tags(tags);
+
+    let server_name = std::mem::take(&mut event.server_name);
+    if server_name.value().is_some() {
+        let tag_name = "server_name".to_string();
+        tags.insert(tag_name, server_name);
+    }
+
+    let site = std::mem::take(&mut event.site);
+    if site.value().is_some() {
+        let tag_name = "site".to_string();
+        tags.insert(tag_name, site);
+    }
+}
+
+/// Replaces empty and overlong tag keys and values with errors.
+fn validate_tags(tags: &mut [Annotated<TagEntry>]) {
     for tag in tags.iter_mut() {
         let _ = processor::apply(tag, |tag, _| {
             if let Some(key) = tag.key() {
@@ -486,18 +517,6 @@ fn normalize_event_tags(event: &mut Event) {
             Ok(())
         });
     }
-
-    let server_name = std::mem::take(&mut event.server_name);
-    if server_name.value().is_some() {
-        let tag_name = "server_name".to_string();
-        tags.insert(tag_name, server_name);
-    }
-
-    let site = std::mem::take(&mut event.site);
-    if site.value().is_some() {
-        let tag_name = "site".to_string();
-        tags.insert(tag_name, site);
-    }
 }
 
 // Reads device specs (family, memory, cpu, etc) from context and sets the device.class tag to high,
@@ -13,20 +13,86 @@ type ReaderType = Vec<u8>;
 /// An error in the `GeoIpLookup`.
 pub type GeoIpError = maxminddb::MaxMindDBError;
 
+fn open_reader<P>(path: P) -> Result<maxminddb::Reader<ReaderType>, GeoIpError>
+where
+    P: AsRef<Path>,
+{
+    #[cfg(feature = "mmap")]
+    let reader = maxminddb::Reader::open_mmap(path)?;
+    #[cfg(not(feature = "mmap"))]
+    let reader = maxminddb::Reader::open_readfile(path)?;
+    Ok(reader)
+}
+
+/// Looks up a record in a maxminddb file, returning `None` if the address is not found.
+fn lookup_record<'a, T>(
+    reader: &'a maxminddb::Reader<ReaderType>,
+    ip_address: std::net::IpAddr,
+) -> Result<Option<T>, GeoIpError>
+where
+    T: serde::Deserialize<'a>,
+{
+    match reader.lookup(ip_address) {
+        Ok(record) => Ok(Some(record)),
+        Err(GeoIpError::AddressNotFoundError(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
 /// A geo ip lookup helper based on maxmind db files.
-pub struct GeoIpLookup(maxminddb::Reader<ReaderType>);
+///
+/// The location is looked up in a City database. An ASN or ISP database provides the network
+/// operator, and an Anonymous IP database flags VPNs, proxies, and hosting providers. Each of the
+/// databases is optional, so that they can be opened independently.
+#[derive(Default)]
+pub struct GeoIpLookup {
+    city: Option<maxminddb::Reader<ReaderType>>,
+    asn: Option<maxminddb::Reader<ReaderType>>,
+    anonymous_ip: Option<maxminddb::Reader<ReaderType>>,
+}
 
 impl GeoIpLookup {
-    /// Opens a maxminddb file by path.
+    /// Opens a maxminddb City file by path.
     pub fn open<P>(path: P) -> Result<Self, GeoIpError>
     where
         P: AsRef<Path>,
     {
-        #[cfg(feature = "mmap")]
-        let reader = maxminddb::Reader::open_mmap(path)?;
-        #[cfg(not(feature = "mmap"))]
-        let reader = maxminddb::Reader::open_readfile(path)?;
-        Ok(GeoIpLookup(reader))
+        let mut lookup = Self::default();
+        lookup.open_city(path)?;
+        Ok(lookup)
+    }
+
+    /// Opens a City maxminddb file by path, which fills the location fields of [`Geo`].
+    pub fn open_city<P>(&mut self, path: P) -> Result<(), GeoIpError>
+    where
+        P: AsRef<Path>,
+    {
+        self.city = Some(open_reader(path)?);
+        Ok(())
+    }
+
+    /// Opens an ASN or ISP maxminddb file by path, which fills [`Geo::asn`] and [`Geo::isp`].
+    pub fn open_asn<P>(&mut self, path: P) -> Result<(), GeoIpError>
+    where
+        P: AsRef<Path>,
+    {
+        self.asn = Some(open_reader(path)?);
+        Ok(())
+    }
+
+    /// Opens an Anonymous IP maxminddb file by path, which fills [`Geo::is_anonymous`] and
+    /// [`Geo::is_hosting_provider`].
+    pub fn open_anonymous_ip<P>(&mut self, path: P) -> Result<(), GeoIpError>
+    where
+        P: AsRef<Path>,
+    {
+        self.anonymous_ip = Some(open_reader(path)?);
+        Ok(())
+    }
+
+    /// Returns `true` if no database has been opened.
+    pub fn is_empty(&self) -> bool {
+        self.city.is_none() && self.asn.is_none() && self.anonymous_ip.is_none()
     }
 
     /// Looks up an IP address.
@@ -37,13 +103,41 @@ impl GeoIpLookup {
             Err(_) => return Ok(None),
         };
 
-        let city: maxminddb::geoip2::City = match self.0.lookup(ip_address) {
-            Ok(x) => x,
-            Err(GeoIpError::AddressNotFoundError(_)) => return Ok(None),
-            Err(e) => return Err(e),
+        let mut geo = match self.city {
+            Some(ref reader) => lookup_record::<maxminddb::geoip2::City>(reader, ip_address)?
+                .map(|city| Self::city_geo(&city)),
+            None => None,
         };
 
-        Ok(Some(Geo {
+        if let Some(ref reader) = self.asn {
+            // ISP databases are a superset of ASN databases.
+            if let Some(isp) = lookup_record::<maxminddb::geoip2::Isp>(reader, ip_address)? {
+                let geo = geo.get_or_insert_with(Geo::default);
+                geo.asn = Annotated::from(isp.autonomous_system_number.map(u64::from));
+                geo.isp = Annotated::from(
+                    isp.isp
+                        .or(isp.autonomous_system_organization)
+                        .map(str::to_owned),
+                );
+            }
+        }
+
+        if let Some(ref reader) = self.anonymous_ip {
+            // The database only contains anonymous IPs, and flags default to `false`.
+            let record = lookup_record::<maxminddb::geoip2::AnonymousIp>(reader, ip_address)?;
+            if let Some(record) = record {
+                let geo = geo.get_or_insert_with(Geo::default);
+                geo.is_anonymous = Annotated::new(record.is_anonymous.unwrap_or(false));
+                geo.is_hosting_provider =
+                    Annotated::new(record.is_hosting_provider.unwrap_or(false));
+            }
+        }
+
+        Ok(geo)
+    }
+
+    fn city_geo(city: &maxminddb::geoip2::City) -> Geo {
+        Geo {
             country_code: Annotated::from(
                 city.country
                     .as_ref()
@@ -69,7 +163,7 @@ impl GeoIpLookup {
                     .and_then(|country| Some(country.names.as_ref()?.get("en")?.to_string())),
             ),
             ..Default::default()
-        }))
+        }
     }
 }
 
@@ -78,3 +172,63 @@ impl fmt::Debug for GeoIpLookup {
         f.debug_struct("GeoIpLookup").finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_asn() {
+        let mut lookup = GeoIpLookup::default();
+        lookup
+            .open_asn("tests/fixtures/GeoLite2-ASN-Test.mmdb")
+            .unwrap();
+
+        let geo = lookup.lookup("1.128.0.1").unwrap().unwrap();
+        assert_eq!(geo.asn.value(), Some(&1221));
+        assert_eq!(geo.isp.as_str(), Some("Telstra Pty Ltd"));
+        assert!(geo.country_code.value().is_none());
+        assert!(geo.is_anonymous.value().is_none());
+
+        assert_eq!(lookup.lookup("2.125.160.216").unwrap(), None);
+    }
+
+    #[test]
+    fn test_lookup_anonymous_ip() {
+        let mut lookup = GeoIpLookup::default();
+        lookup
+            .open_anonymous_ip("tests/fixtures/GeoIP2-Anonymous-IP-Test.mmdb")
+            .unwrap();
+
+        let geo = lookup.lookup("1.2.0.1").unwrap().unwrap();
+        assert_eq!(geo.is_anonymous.value(), Some(&true));
+        assert_eq!(geo.is_hosting_provider.value(), Some(&false));
+
+        let geo = lookup.lookup("71.160.223.45").unwrap().unwrap();
+        assert_eq!(geo.is_anonymous.value(), Some(&true));
+        assert_eq!(geo.is_hosting_provider.value(), Some(&true));
+
+        assert_eq!(lookup.lookup("1.128.0.1").unwrap(), None);
+    }
+
+    #[test]
+    fn test_lookup_combined() {
+        let mut lookup = GeoIpLookup::open("tests/fixtures/GeoIP2-Enterprise-Test.mmdb").unwrap();
+        lookup
+            .open_anonymous_ip("tests/fixtures/GeoIP2-Anonymous-IP-Test.mmdb")
+            .unwrap();
+
+        let geo = lookup.lookup("81.2.69.160").unwrap().unwrap();
+        assert_eq!(geo.country_code.as_str(), Some("GB"));
+        assert_eq!(geo.city.as_str(), Some("London"));
+        assert_eq!(geo.is_anonymous.value(), Some(&true));
+        assert_eq!(geo.is_hosting_provider.value(), Some(&true));
+    }
+
+    #[test]
+    fn test_empty_lookup() {
+        let lookup = GeoIpLookup::default();
+        assert!(lookup.is_empty());
+        assert_eq!(lookup.lookup("1.128.0.1").unwrap(), None);
+    }
+}
@@ -0,0 +1,493 @@
+//! Computes grouping hashes of error events.
+//!
+//! Events with the same grouping hash belong to the same issue. This implements a subset of the
+//! default grouping strategies in Sentry:
+//!
+//!  - A custom `fingerprint` sent by the client takes precedence. It can refer to the default
+//!    grouping with the `{{ default }}` variable.
+//!  - Exceptions group by their type and the in-app frames of their stack traces. If no frame is
+//!    marked as in-app, all frames are used. The exception value is only used if there is no
+//!    stack trace.
+//!  - Events without exceptions group by the stack trace of the crashed thread or the event.
+//!  - All other events group by their message, with variable parts such as numbers and UUIDs
+//!    removed.
+//!
+//! The resulting hashes are stable across Relay versions, but they do not match the hashes
+//! computed by Sentry.
+
+use std::borrow::Cow;
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use relay_event_schema::protocol::{Event, Exception, Frame, RawStacktrace, Thread};
+use relay_protocol::Annotated;
+use serde_json::Value;
+
+/// Matches variable parts of messages and file names that are replaced before hashing.
+static PARAMETERS_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+    (?P<uuid>\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b) |
+    (?P<hash>\b[0-9a-fA-F]{8,}\b) |
+    (?P<hex>\b0[xX][0-9a-fA-F]+\b) |
+    (?P<int>\b\d+\b)
+    ",
+    )
+    .unwrap()
+});
+
+/// The grouping strategy selected by the grouping config of a project.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupingStrategy {
+    /// The default strategy of the `newstyle` grouping configs.
+    Newstyle,
+}
+
+impl GroupingStrategy {
+    /// Returns the strategy for the `id` of a project's grouping config.
+    ///
+    /// Returns `None` for legacy and unknown grouping configs, which are only supported by
+    /// Sentry.
+    pub fn from_config(config: &Value) -> Option<Self> {
+        let id = config.get("id")?.as_str()?;
+        id.starts_with("newstyle:").then_some(Self::Newstyle)
+    }
+}
+
+/// Computes the grouping hash of an event.
+///
+/// Returns `None` if the event does not contain any information to group by.
+pub fn compute_hash(event: &Event, strategy: GroupingStrategy) -> Option<String> {
+    let components = match strategy {
+        GroupingStrategy::Newstyle => match event.fingerprint.value() {
+            Some(fingerprint) if !is_default_fingerprint(fingerprint) => {
+                fingerprint_components(event, fingerprint)
+            }
+            _ => default_components(event),
+        },
+    };
+
+    if components.is_empty() {
+        return None;
+    }
+
+    let mut context = md5::Context::new();
+    for component in &components {
+        context.consume(component.as_bytes());
+        context.consume(b"\n");
+    }
+
+    Some(format!("{:x}", context.compute()))
+}
+
+/// Returns the name of a fingerprint variable, such as `default` for `{{ default }}`.
+fn variable_name(value: &str) -> Option<String> {
+    let name = value.strip_prefix("{{")?.strip_suffix("}}")?;
+    Some(name.trim().to_lowercase())
+}
+
+/// Returns `true` if the fingerprint only consists of the default grouping.
+fn is_default_fingerprint(fingerprint: &[String]) -> bool {
+    match fingerprint {
+        [value] => variable_name(value).as_deref() == Some("default"),
+        _ => fingerprint.is_empty(),
+    }
+}
+
+/// Expands the variables of a custom fingerprint.
+fn fingerprint_components(event: &Event, fingerprint: &[String]) -> Vec<String> {
+    let mut components = Vec::new();
+
+    for value in fingerprint {
+        let Some(name) = variable_name(value) else {
+            components.push(value.clone());
+            continue;
+        };
+
+        match name.as_str() {
+            "default" => components.extend(default_components(event)),
+            "transaction" => components.push(
+                event
+                    .transaction
+                    .as_str()
+                    .unwrap_or("<no-transaction>")
+                    .to_owned(),
+            ),
+            "level" => components.push(
+                event
+                    .level
+                    .value()
+                    .map_or("<no-level>", |level| level.name())
+                    .to_owned(),
+            ),
+            "type" | "error.type" => components.push(
+                primary_exception(event)
+                    .and_then(|exception| exception.ty.as_str())
+                    .unwrap_or("<no-type>")
+                    .to_owned(),
+            ),
+            "value" | "error.value" => components.push(
+                primary_exception(event)
+                    .and_then(|exception| exception.value.value())
+                    .map_or("<no-value>", |value| value.as_str())
+                    .to_owned(),
+            ),
+            "message" => components.push(
+                message(event)
+                    .map_or(Cow::Borrowed("<no-message>"), parameterize)
+                    .into_owned(),
+            ),
+            _ => components.push(value.clone()),
+        }
+    }
+
+    components
+}
+
+/// Returns the components of the default grouping strategy.
+fn default_components(event: &Event) -> Vec<String> {
+    let mut components = Vec::new();
+
+    let exceptions = event
+        .exceptions
+        .value()
+        .and_then(|values| values.values.value());
+    for exception in exceptions
+        .into_iter()
+        .flatten()
+        .filter_map(Annotated::value)
+    {
+        exception_components(exception, &mut components);
+    }
+
+    if components.is_empty() {
+        let stacktrace = crashed_thread(event)
+            .and_then(|thread| thread.stacktrace.value())
+            .or_else(|| event.stacktrace.value());
+
+        if let Some(stacktrace) = stacktrace {
+            stacktrace_components(stacktrace, &mut components);
+        }
+    }
+
+    if components.is_empty() {
+        if let Some(message) = message(event) {
+            components.push(format!("message:{}", parameterize(message)));
+        }
+    }
+
+    components
+}
+
+fn exception_components(exception: &Exception, components: &mut Vec<String>) {
+    if let Some(ty) = exception.ty.as_str() {
+        components.push(format!("type:{ty}"));
+    }
+
+    let has_frames = exception.stacktrace.value().map_or(false, |stacktrace| {
+        stacktrace_components(stacktrace, components)
+    });
+
+    if !has_frames {
+        if let Some(value) = exception.value.value() {
+            components.push(format!("value:{}", parameterize(value.as_str())));
+        }
+    }
+}
+
+/// Adds a component for every frame that contributes to grouping.
+///
+/// Returns `true` if at least one frame contributed.
+fn stacktrace_components(stacktrace: &RawStacktrace, components: &mut Vec<String>) -> bool {
+    let Some(frames) = stacktrace.frames.value() else {
+        return false;
+    };
+
+    let frames: Vec<&Frame> = frames.iter().filter_map(Annotated::value).collect();
+    let has_in_app = frames.iter().any(|frame| is_in_app(frame));
+
+    let count = components.len();
+    for frame in frames {
+        if has_in_app && !is_in_app(frame) {
+            continue;
+        }
+
+        if let Some(component) = frame_component(frame) {
+            components.push(component);
+        }
+    }
+
+    components.len() > count
+}
+
+fn is_in_app(frame: &Frame) -> bool {
+    frame.in_app.value() == Some(&true)
+}
+
+/// Returns the grouping component of a frame, made of its module or file name and its function.
+fn frame_component(frame: &Frame) -> Option<String> {
+    let location = match frame.module.as_str() {
+        Some(module) => Some(module.to_owned()),
+        None => frame
+            .filename
+            .value()
+            .map(|filename| normalize_filename(filename.as_str())),
+    };
+
+    let function = frame
+        .function
+        .as_str()
+        .map(str::trim)
+        .filter(|function| !function.is_empty() && *function != "?");
+
+    if location.is_none() && function.is_none() {
+        return None;
+    }
+
+    Some(format!(
+        "frame:{}:{}",
+        location.as_deref().unwrap_or_default(),
+        function.unwrap_or_default()
+    ))
+}
+
+/// Removes the query string and generated hashes from a file name.
+///
+/// Bundlers often add content hashes to file names, which change with every release.
+fn normalize_filename(filename: &str) -> String {
+    let path = filename
+        .split(|c| c == '?' || c == '#')
+        .next()
+        .unwrap_or(filename);
+
+    parameterize(path).into_owned()
+}
+
+/// Returns the exception that caused the event, which is the last one in the chain.
+fn primary_exception(event: &Event) -> Option<&Exception> {
+    let exceptions = event.exceptions.value()?.values.value()?;
+    exceptions.iter().rev().find_map(Annotated::value)
+}
+
+/// Returns the crashed thread, or the only thread if there is just one.
+fn crashed_thread(event: &Event) -> Option<&Thread> {
+    let threads = event.threads.value()?.values.value()?;
+    let mut threads = threads.iter().filter_map(Annotated::value);
+
+    match threads
+        .clone()
+        .find(|thread| thread.crashed.value() == Some(&true))
+    {
+        Some(thread) => Some(thread),
+        None => match (threads.next(), threads.next()) {
+            (Some(thread), None) => Some(thread),
+            _ => None,
+        },
+    }
+}
+
+/// Returns the message of the event, preferring the template over the formatted message.
+fn message(event: &Event) -> Option<&str> {
+    let logentry = event.logentry.value()?;
+    logentry
+        .message
+        .value()
+        .or_else(|| logentry.formatted.value())
+        .map(|message| message.as_ref())
+}
+
+/// Replaces numbers, UUIDs, and hashes in a string with placeholders.
+fn parameterize(value: &str) -> Cow<'_, str> {
+    PARAMETERS_REGEX.replace_all(value, |captures: &Captures| {
+        if captures.name("uuid").is_some() {
+            "<uuid>"
+        } else if captures.name("hash").is_some() {
+            "<hash>"
+        } else if captures.name("hex").is_some() {
+            "<hex>"
+        } else {
+            "<int>"
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    fn hash(json: &str) -> Option<String> {
+        let event = Annotated::<Event>::from_json(json).unwrap();
+        compute_hash(event.value().unwrap(), GroupingStrategy::Newstyle)
+    }
+
+    #[test]
+    fn test_strategy_from_config() {
+        let config = serde_json::json!({"id": "newstyle:2023-01-11", "enhancements": ""});
+        assert_eq!(
+            GroupingStrategy::from_config(&config),
+            Some(GroupingStrategy::Newstyle)
+        );
+
+        let config = serde_json::json!({"id": "legacy:2019-03-12"});
+        assert_eq!(GroupingStrategy::from_config(&config), None);
+    }
+
+    #[test]
+    fn test_exception_in_app_frames() {
+        let event = r#"{
+            "exception": {"values": [{
+                "type": "ValueError",
+                "value": "invalid literal 42",
+                "stacktrace": {"frames": [
+                    {"module": "django.core", "function": "dispatch", "in_app": false},
+                    {"module": "app.views", "function": "index", "in_app": true}
+                ]}
+            }]}
+        }"#;
+
+        // Changing frames outside of the app or the exception value does not change the hash.
+        let other = r#"{
+            "exception": {"values": [{
+                "type": "ValueError",
+                "value": "invalid literal 43",
+                "stacktrace": {"frames": [
+                    {"module": "django.core.handlers", "function": "run", "in_app": false},
+                    {"module": "app.views", "function": "index", "in_app": true}
+                ]}
+            }]}
+        }"#;
+
+        assert_eq!(hash(event), hash(other));
+        assert!(hash(event).is_some());
+    }
+
+    #[test]
+    fn test_exception_type() {
+        let event = r#"{
+            "exception": {"values": [{
+                "type": "ValueError",
+                "stacktrace": {"frames": [{"module": "app.views", "function": "index"}]}
+            }]}
+        }"#;
+
+        let other = r#"{
+            "exception": {"values": [{
+                "type": "KeyError",
+                "stacktrace": {"frames": [{"module": "app.views", "function": "index"}]}
+            }]}
+        }"#;
+
+        assert_ne!(hash(event), hash(other));
+    }
+
+    #[test]
+    fn test_exception_value_without_stacktrace() {
+        let event = r#"{"exception": {"values": [{"type": "E", "value": "user 12 not found"}]}}"#;
+        let other = r#"{"exception": {"values": [{"type": "E", "value": "user 13 not found"}]}}"#;
+        let different = r#"{"exception": {"values": [{"type": "E", "value": "timeout"}]}}"#;
+
+        assert_eq!(hash(event), hash(other));
+        assert_ne!(hash(event), hash(different));
+    }
+
+    #[test]
+    fn test_filename_hashes() {
+        let event = r#"{
+            "exception": {"values": [{
+                "type": "TypeError",
+                "stacktrace": {"frames": [
+                    {"filename": "/assets/main.3f2a9b1c7d.js?v=1", "function": "render"}
+                ]}
+            }]}
+        }"#;
+
+        let other = r#"{
+            "exception": {"values": [{
+                "type": "TypeError",
+                "stacktrace": {"frames": [
+                    {"filename": "/assets/main.84c0e2f5aa.js", "function": "render"}
+                ]}
+            }]}
+        }"#;
+
+        assert_eq!(hash(event), hash(other));
+    }
+
+    #[test]
+    fn test_crashed_thread() {
+        let event = r#"{
+            "threads": {"values": [
+                {"id": 1, "stacktrace": {"frames": [{"function": "idle"}]}},
+                {"id": 2, "crashed": true, "stacktrace": {"frames": [{"function": "crash"}]}}
+            ]}
+        }"#;
+
+        let other = r#"{
+            "threads": {"values": [
+                {"id": 1, "stacktrace": {"frames": [{"function": "poll"}]}},
+                {"id": 2, "crashed": true, "stacktrace": {"frames": [{"function": "crash"}]}}
+            ]}
+        }"#;
+
+        assert_eq!(hash(event), hash(other));
+    }
+
+    #[test]
+    fn test_message_template() {
+        let event = r#"{"logentry": {"message": "Failed to load %s", "params": ["a"]}}"#;
+        let other = r#"{"logentry": {"message": "Failed to load %s", "params": ["b"]}}"#;
+        assert_eq!(hash(event), hash(other));
+
+        let event = r#"{"logentry": {"formatted": "Request 1234 took 0x1f ms"}}"#;
+        let other = r#"{"logentry": {"formatted": "Request 5678 took 0x2e ms"}}"#;
+        assert_eq!(hash(event), hash(other));
+    }
+
+    #[test]
+    fn test_empty_event() {
+        assert_eq!(hash("{}"), None);
+    }
+
+    #[test]
+    fn test_custom_fingerprint() {
+        let event = r#"{
+            "fingerprint": ["database-unavailable"],
+            "logentry": {"formatted": "connection refused"}
+        }"#;
+
+        let other = r#"{
+            "fingerprint": ["database-unavailable"],
+            "logentry": {"formatted": "timeout"}
+        }"#;
+
+        assert_eq!(hash(event), hash(other));
+    }
+
+    #[test]
+    fn test_fingerprint_variables() {
+        let default = r#"{
+            "fingerprint": ["{{ default }}"],
+            "logentry": {"formatted": "connection refused"}
+        }"#;
+
+        let plain = r#"{"logentry": {"formatted": "connection refused"}}"#;
+        assert_eq!(hash(default), hash(plain));
+
+        let event = r#"{
+            "fingerprint": ["{{ default }}", "{{ transaction }}"],
+            "transaction": "/users",
+            "logentry": {"formatted": "connection refused"}
+        }"#;
+
+        let other = r#"{
+            "fingerprint": ["{{default}}", "{{ transaction }}"],
+            "transaction": "/orders",
+            "logentry": {"formatted": "connection refused"}
+        }"#;
+
+        assert_ne!(hash(event), hash(plain));
+        assert_ne!(hash(event), hash(other));
+    }
+}
@@ -30,6 +30,7 @@ mod remove_other;
 mod schema;
 mod stacktrace;
 mod statsd;
+mod tag_rules;
 mod timestamp;
 mod transactions;
 mod trimming;
@@ -39,12 +40,16 @@ pub use validation::{
     validate_event_timestamps, validate_span, validate_transaction, EventValidationConfig,
     TransactionValidationConfig,
 };
+pub mod grouping;
 pub mod replay;
+pub mod sourcemap;
+pub mod symbolication;
 pub use event::{normalize_event, normalize_measurements, NormalizationConfig};
 pub use normalize::breakdowns::*;
 pub use normalize::*;
 pub use remove_other::RemoveOtherProcessor;
 pub use schema::SchemaProcessor;
+pub use tag_rules::*;
 pub use timestamp::TimestampProcessor;
 pub use transactions::*;
 pub use trimming::TrimmingProcessor;
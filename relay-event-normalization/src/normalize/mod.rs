@@ -1534,6 +1534,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tag_rules_validated() {
+        let json = json!({
+            "contexts": {"custom": {"type": "custom", "value": "x".repeat(300)}}
+        });
+        let mut event = Annotated::<Event>::from_json(&json.to_string()).unwrap();
+
+        let rules: Vec<crate::TagRule> = serde_json::from_value(json!([
+            {"type": "fromContext", "context": "custom", "field": "value", "tag": "derived"}
+        ]))
+        .unwrap();
+
+        normalize_event(
+            &mut event,
+            &NormalizationConfig {
+                tag_rules: &rules,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            get_value!(event.tags!).0.get("derived"),
+            Some(&Annotated::from_error(
+                Error::new(ErrorKind::ValueTooLong),
+                None
+            ))
+        );
+    }
+
     #[test]
     fn test_too_long_distribution() {
         let json = r#"{
@@ -0,0 +1,710 @@
+//! Resolution of minified JavaScript stack frames with source maps.
+//!
+//! This implements the subset of the [Source Map Revision 3] format required to map locations in
+//! minified files back to their original sources. Index maps with `sections` are not supported.
+//!
+//! [Source Map Revision 3]: https://sourcemaps.info/spec.html
+
+use std::sync::Arc;
+
+use relay_event_schema::processor::{
+    self, ProcessValue, ProcessingResult, ProcessingState, Processor,
+};
+use relay_event_schema::protocol::{Event, Frame, FrameData, Stacktrace};
+use relay_protocol::{Annotated, Meta};
+use serde::Deserialize;
+
+use crate::stacktrace::filename_from_abs_path;
+
+/// Prefix that may precede the JSON payload of source maps to prevent XSSI attacks.
+const XSSI_PREFIX: &[u8] = b")]}'";
+
+/// Number of lines of source context added before and after the resolved line.
+const CONTEXT_LINES: usize = 5;
+
+/// Maximum number of characters in a line of source context.
+///
+/// Longer lines are trimmed around the resolved column.
+const MAX_LINE_LENGTH: usize = 140;
+
+/// Marker inserted where a line of source context was trimmed.
+const SNIP: &str = "{snip}";
+
+/// An error returned when parsing a [`SourceMap`].
+#[derive(Debug, thiserror::Error)]
+pub enum SourceMapError {
+    /// The source map is not valid JSON.
+    #[error("invalid source map")]
+    Json(#[from] serde_json::Error),
+    /// The source map has a version other than 3.
+    #[error("unsupported source map version {0}")]
+    UnsupportedVersion(u32),
+    /// The source map is an index map with sections.
+    #[error("index source maps are not supported")]
+    IndexMap,
+    /// The `mappings` of the source map contain an invalid segment.
+    #[error("invalid source map mappings at offset {0}")]
+    InvalidMappings(usize),
+}
+
+/// The JSON representation of a source map.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    version: u32,
+    #[serde(default)]
+    source_root: Option<String>,
+    #[serde(default)]
+    sources: Vec<Option<String>>,
+    #[serde(default)]
+    sources_content: Vec<Option<String>>,
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(default)]
+    mappings: String,
+    #[serde(default)]
+    sections: Option<serde_json::Value>,
+}
+
+/// A decoded segment of the source map's mappings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Token {
+    dst_line: u32,
+    dst_col: u32,
+    /// Index of the source, line, and column in the original source.
+    src: Option<(u32, u32, u32)>,
+    name: Option<u32>,
+}
+
+/// A location in an original source resolved from a [`SourceMap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLocation<'a> {
+    /// The path or URL of the original source.
+    pub source: &'a str,
+    /// The zero-based line in the original source.
+    pub line: u32,
+    /// The zero-based column in the original source.
+    pub column: u32,
+    /// The original name of the symbol at this location, if known.
+    pub name: Option<&'a str>,
+    /// The contents of the original source, if embedded in the source map.
+    pub contents: Option<&'a str>,
+}
+
+/// A parsed source map.
+#[derive(Debug)]
+pub struct SourceMap {
+    url: Option<String>,
+    sources: Vec<String>,
+    sources_content: Vec<Option<String>>,
+    names: Vec<String>,
+    tokens: Vec<Token>,
+}
+
+impl SourceMap {
+    /// Parses a source map from its JSON representation.
+    pub fn from_slice(slice: &[u8]) -> Result<Self, SourceMapError> {
+        let slice = match slice.strip_prefix(XSSI_PREFIX) {
+            Some(rest) => rest
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(&[][..], |pos| &rest[pos + 1..]),
+            None => slice,
+        };
+
+        Self::from_raw(serde_json::from_slice(slice)?)
+    }
+
+    fn from_raw(raw: RawSourceMap) -> Result<Self, SourceMapError> {
+        if raw.version != 3 {
+            return Err(SourceMapError::UnsupportedVersion(raw.version));
+        }
+
+        if raw.sections.is_some() {
+            return Err(SourceMapError::IndexMap);
+        }
+
+        let source_root = raw.source_root.as_deref().unwrap_or_default();
+        let sources = raw
+            .sources
+            .into_iter()
+            .map(|source| join_source_root(source_root, source.unwrap_or_default()))
+            .collect::<Vec<_>>();
+
+        let tokens = decode_mappings(&raw.mappings, sources.len(), raw.names.len())?;
+
+        Ok(Self {
+            url: None,
+            sources,
+            sources_content: raw.sources_content,
+            names: raw.names,
+            tokens,
+        })
+    }
+
+    /// Sets the URL from which the source map was loaded.
+    ///
+    /// The URL is written to the `sourcemap` field of the data of resolved frames.
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Returns the URL from which the source map was loaded, if known.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// Returns the approximate number of bytes used by the parsed source map.
+    pub fn size(&self) -> usize {
+        let strings = self.url.iter().chain(&self.sources).chain(&self.names);
+        let contents = self.sources_content.iter().flatten();
+
+        std::mem::size_of::<Self>()
+            + strings.chain(contents).map(String::capacity).sum::<usize>()
+            + self.sources.capacity() * std::mem::size_of::<String>()
+            + self.sources_content.capacity() * std::mem::size_of::<Option<String>>()
+            + self.names.capacity() * std::mem::size_of::<String>()
+            + self.tokens.capacity() * std::mem::size_of::<Token>()
+    }
+
+    /// Looks up the original location of a zero-based line and column in the minified file.
+    ///
+    /// Returns `None` if the location is not covered by a mapping on the same line.
+    pub fn lookup(&self, line: u32, column: u32) -> Option<SourceLocation<'_>> {
+        let index = self
+            .tokens
+            .partition_point(|token| (token.dst_line, token.dst_col) <= (line, column));
+
+        let token = self.tokens.get(index.checked_sub(1)?)?;
+        if token.dst_line != line {
+            return None;
+        }
+
+        let (src_id, src_line, src_col) = token.src?;
+        let src_id = src_id as usize;
+
+        Some(SourceLocation {
+            source: &self.sources[src_id],
+            line: src_line,
+            column: src_col,
+            name: token.name.map(|id| self.names[id as usize].as_str()),
+            contents: self.sources_content.get(src_id).and_then(Option::as_deref),
+        })
+    }
+}
+
+/// Prepends the source root to a relative source path.
+fn join_source_root(source_root: &str, source: String) -> String {
+    if source_root.is_empty() || source.starts_with('/') || source.contains("://") {
+        return source;
+    }
+
+    format!("{}/{}", source_root.trim_end_matches('/'), source)
+}
+
+/// Returns the value of a base64 digit.
+fn base64_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+/// Decodes a single base64 VLQ value starting at `pos`.
+fn decode_vlq(bytes: &[u8], pos: &mut usize) -> Result<i64, SourceMapError> {
+    let mut value = 0i64;
+    let mut shift = 0;
+
+    loop {
+        let digit = bytes
+            .get(*pos)
+            .copied()
+            .and_then(base64_value)
+            .ok_or(SourceMapError::InvalidMappings(*pos))?;
+
+        // VLQ values in source maps are limited to 32 bits.
+        if shift > 30 {
+            return Err(SourceMapError::InvalidMappings(*pos));
+        }
+
+        *pos += 1;
+        value += i64::from(digit & 0b11111) << shift;
+        shift += 5;
+
+        if digit & 0b100000 == 0 {
+            break;
+        }
+    }
+
+    let magnitude = value >> 1;
+    Ok(if value & 1 == 1 {
+        -magnitude
+    } else {
+        magnitude
+    })
+}
+
+/// Applies a relative VLQ value to the previous absolute value.
+fn apply_delta(value: u32, delta: i64, pos: usize) -> Result<u32, SourceMapError> {
+    u32::try_from(i64::from(value) + delta).map_err(|_| SourceMapError::InvalidMappings(pos))
+}
+
+/// Decodes the `mappings` of a source map into tokens sorted by their minified location.
+fn decode_mappings(
+    mappings: &str,
+    num_sources: usize,
+    num_names: usize,
+) -> Result<Vec<Token>, SourceMapError> {
+    let bytes = mappings.as_bytes();
+    let mut tokens = Vec::new();
+
+    let mut pos = 0;
+    let mut dst_line = 0;
+    let mut dst_col = 0;
+    let mut src_id = 0;
+    let mut src_line = 0;
+    let mut src_col = 0;
+    let mut name_id = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b';' => {
+                dst_line += 1;
+                dst_col = 0;
+                pos += 1;
+                continue;
+            }
+            b',' => {
+                pos += 1;
+                continue;
+            }
+            _ => (),
+        }
+
+        let start = pos;
+        let mut fields = [0i64; 5];
+        let mut len = 0;
+
+        while pos < bytes.len() && bytes[pos] != b',' && bytes[pos] != b';' {
+            if len == fields.len() {
+                return Err(SourceMapError::InvalidMappings(pos));
+            }
+            fields[len] = decode_vlq(bytes, &mut pos)?;
+            len += 1;
+        }
+
+        dst_col = apply_delta(dst_col, fields[0], start)?;
+
+        let mut token = Token {
+            dst_line,
+            dst_col,
+            src: None,
+            name: None,
+        };
+
+        match len {
+            1 => (),
+            4 | 5 => {
+                src_id = apply_delta(src_id, fields[1], start)?;
+                src_line = apply_delta(src_line, fields[2], start)?;
+                src_col = apply_delta(src_col, fields[3], start)?;
+
+                if src_id as usize >= num_sources {
+                    return Err(SourceMapError::InvalidMappings(start));
+                }
+                token.src = Some((src_id, src_line, src_col));
+
+                if len == 5 {
+                    name_id = apply_delta(name_id, fields[4], start)?;
+                    if name_id as usize >= num_names {
+                        return Err(SourceMapError::InvalidMappings(start));
+                    }
+                    token.name = Some(name_id);
+                }
+            }
+            _ => return Err(SourceMapError::InvalidMappings(start)),
+        }
+
+        tokens.push(token);
+    }
+
+    tokens.sort_by_key(|token| (token.dst_line, token.dst_col));
+    Ok(tokens)
+}
+
+/// Trims a line of source context to [`MAX_LINE_LENGTH`] characters around the given column.
+fn trim_line(line: &str, column: usize) -> String {
+    let len = line.chars().count();
+    if len <= MAX_LINE_LENGTH {
+        return line.to_owned();
+    }
+
+    let start = column
+        .saturating_sub(MAX_LINE_LENGTH / 2)
+        .min(len - MAX_LINE_LENGTH);
+    let end = start + MAX_LINE_LENGTH;
+
+    let mut trimmed = String::new();
+    if start > 0 {
+        trimmed.push_str(SNIP);
+        trimmed.push(' ');
+    }
+    trimmed.extend(line.chars().skip(start).take(MAX_LINE_LENGTH));
+    if end < len {
+        trimmed.push(' ');
+        trimmed.push_str(SNIP);
+    }
+
+    trimmed
+}
+
+/// Returns `true` if frames of the given platform can be resolved with source maps.
+fn is_js_platform(platform: Option<&str>) -> bool {
+    matches!(platform, Some("javascript" | "node"))
+}
+
+/// Resolves a single minified frame with the given source map.
+///
+/// Returns `true` if the frame was resolved. The minified location and function name are retained
+/// in the frame's `data`.
+pub fn resolve_frame(frame: &mut Frame, source_map: &SourceMap) -> bool {
+    // Line and column numbers in frames are one-based, but zero-based in source maps.
+    let (Some(&lineno), Some(&colno)) = (frame.lineno.value(), frame.colno.value()) else {
+        return false;
+    };
+    let (Some(line), Some(column)) = (lineno.checked_sub(1), colno.checked_sub(1)) else {
+        return false;
+    };
+    let (Ok(line), Ok(column)) = (u32::try_from(line), u32::try_from(column)) else {
+        return false;
+    };
+
+    let Some(location) = source_map.lookup(line, column) else {
+        return false;
+    };
+
+    let data = frame.data.get_or_insert_with(FrameData::default);
+    data.orig_lineno = Annotated::new(lineno);
+    data.orig_colno = Annotated::new(colno);
+    if let Some(abs_path) = frame.abs_path.value() {
+        data.orig_filename = Annotated::new(abs_path.as_str().to_owned());
+    }
+    if let Some(function) = frame.function.value() {
+        data.orig_function = Annotated::new(function.clone());
+    }
+    if let Some(url) = source_map.url() {
+        data.sourcemap = Annotated::new(url.to_owned());
+    }
+
+    if let Some(name) = location.name {
+        frame.raw_function = std::mem::take(&mut frame.function);
+        frame.function = Annotated::new(name.to_owned());
+    }
+
+    frame.abs_path = Annotated::new(location.source.into());
+    frame.filename = Annotated::new(filename_from_abs_path(location.source).into());
+    frame.lineno = Annotated::new(u64::from(location.line) + 1);
+    frame.colno = Annotated::new(u64::from(location.column) + 1);
+
+    if let Some(contents) = location.contents {
+        let lines = contents.lines().collect::<Vec<_>>();
+        let line = location.line as usize;
+        let column = location.column as usize;
+
+        if let Some(context_line) = lines.get(line) {
+            let pre_start = line.saturating_sub(CONTEXT_LINES);
+            let post_end = (line + 1 + CONTEXT_LINES).min(lines.len());
+
+            let context = |lines: &[&str]| {
+                lines
+                    .iter()
+                    .map(|line| Annotated::new(trim_line(line, 0)))
+                    .collect()
+            };
+
+            frame.pre_context = Annotated::new(context(&lines[pre_start..line]));
+            frame.context_line = Annotated::new(trim_line(context_line, column));
+            frame.post_context = Annotated::new(context(&lines[line + 1..post_end]));
+        }
+    }
+
+    true
+}
+
+/// Resolves minified JavaScript frames in all stack traces of an event.
+///
+/// The lookup function receives the absolute path of a minified file and returns the source map
+/// for it, if available. Frames are resolved if either the frame or the event has the
+/// `javascript` or `node` platform. Raw stack traces are left untouched.
+pub struct SourceMapProcessor<F> {
+    lookup: F,
+    platform: Option<String>,
+    resolved: usize,
+}
+
+impl<F> SourceMapProcessor<F>
+where
+    F: FnMut(&str) -> Option<Arc<SourceMap>>,
+{
+    /// Creates a new processor with the given source map lookup.
+    pub fn new(lookup: F) -> Self {
+        Self {
+            lookup,
+            platform: None,
+            resolved: 0,
+        }
+    }
+
+    /// Returns the number of frames resolved by this processor.
+    pub fn resolved(&self) -> usize {
+        self.resolved
+    }
+
+    fn process_frame_with_lookup(&mut self, frame: &mut Frame) {
+        let platform = frame.platform.as_str().or(self.platform.as_deref());
+        if !is_js_platform(platform) {
+            return;
+        }
+
+        let Some(abs_path) = frame.abs_path.value() else {
+            return;
+        };
+
+        if let Some(source_map) = (self.lookup)(abs_path.as_str()) {
+            if resolve_frame(frame, &source_map) {
+                self.resolved += 1;
+            }
+        }
+    }
+}
+
+impl<F> Processor for SourceMapProcessor<F>
+where
+    F: FnMut(&str) -> Option<Arc<SourceMap>>,
+{
+    fn process_event(
+        &mut self,
+        event: &mut Event,
+        _meta: &mut Meta,
+        state: &ProcessingState<'_>,
+    ) -> ProcessingResult {
+        self.platform = event.platform.value().cloned();
+        event.process_child_values(self, state)
+    }
+
+    fn process_stacktrace(
+        &mut self,
+        stacktrace: &mut Stacktrace,
+        _meta: &mut Meta,
+        _state: &ProcessingState<'_>,
+    ) -> ProcessingResult {
+        if let Some(frames) = stacktrace.frames.value_mut() {
+            for frame in frames.iter_mut() {
+                processor::apply(frame, |frame, _| {
+                    self.process_frame_with_lookup(frame);
+                    Ok(())
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use relay_event_schema::processor::process_value;
+    use relay_event_schema::protocol::{Exception, RawStacktrace, Values};
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    /// A source map for `function a(b,c){return b+c}` minified from `src/app.js`.
+    const SOURCE_MAP: &str = r#"{
+        "version": 3,
+        "file": "app.min.js",
+        "sourceRoot": "webpack:///",
+        "sources": ["src/app.js"],
+        "sourcesContent": ["// Adds two numbers.\nfunction add(left, right) {\n  return left + right;\n}\n"],
+        "names": ["add", "left", "right"],
+        "mappings": "AACA,SAASA,EAAIC,EAAMC,GACjB,OAAOD,EAAOC"
+    }"#;
+
+    fn source_map() -> SourceMap {
+        SourceMap::from_slice(SOURCE_MAP.as_bytes()).unwrap()
+    }
+
+    fn minified_frame() -> Frame {
+        Frame {
+            function: Annotated::new("a".to_owned()),
+            abs_path: Annotated::new("https://example.com/static/app.min.js".into()),
+            lineno: Annotated::new(1),
+            colno: Annotated::new(10),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_vlq() {
+        let mut pos = 0;
+        assert_eq!(decode_vlq(b"A", &mut pos).unwrap(), 0);
+        pos = 0;
+        assert_eq!(decode_vlq(b"D", &mut pos).unwrap(), -1);
+        pos = 0;
+        assert_eq!(decode_vlq(b"gB", &mut pos).unwrap(), 16);
+        assert_eq!(pos, 2);
+        pos = 0;
+        assert!(decode_vlq(b"g", &mut pos).is_err());
+    }
+
+    #[test]
+    fn test_lookup() {
+        let source_map = source_map();
+
+        let location = source_map.lookup(0, 9).unwrap();
+        assert_eq!(location.source, "webpack:///src/app.js");
+        assert_eq!((location.line, location.column), (1, 9));
+        assert_eq!(location.name, Some("add"));
+        assert!(location.contents.is_some());
+
+        let location = source_map.lookup(0, 17).unwrap();
+        assert_eq!((location.line, location.column), (2, 2));
+        assert_eq!(location.name, None);
+
+        let location = source_map.lookup(0, 23).unwrap();
+        assert_eq!((location.line, location.column), (2, 9));
+        assert_eq!(location.name, Some("left"));
+
+        assert!(source_map.lookup(1, 0).is_none());
+    }
+
+    #[test]
+    fn test_xssi_prefix() {
+        let json = format!(")]}}'\n{SOURCE_MAP}");
+        assert!(SourceMap::from_slice(json.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_source_maps() {
+        let version = r#"{"version": 2, "sources": [], "mappings": ""}"#;
+        assert!(matches!(
+            SourceMap::from_slice(version.as_bytes()),
+            Err(SourceMapError::UnsupportedVersion(2))
+        ));
+
+        let index = r#"{"version": 3, "sections": []}"#;
+        assert!(matches!(
+            SourceMap::from_slice(index.as_bytes()),
+            Err(SourceMapError::IndexMap)
+        ));
+
+        let source = r#"{"version": 3, "sources": [], "mappings": "AAAA"}"#;
+        assert!(matches!(
+            SourceMap::from_slice(source.as_bytes()),
+            Err(SourceMapError::InvalidMappings(0))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_frame() {
+        let mut frame = minified_frame();
+        assert!(resolve_frame(&mut frame, &source_map()));
+
+        assert_eq!(frame.function.as_str(), Some("add"));
+        assert_eq!(frame.raw_function.as_str(), Some("a"));
+        assert_eq!(
+            frame.abs_path.value().map(|p| p.as_str()),
+            Some("webpack:///src/app.js")
+        );
+        assert_eq!(frame.lineno.value(), Some(&2));
+        assert_eq!(frame.colno.value(), Some(&10));
+        assert_eq!(
+            frame.context_line.as_str(),
+            Some("function add(left, right) {")
+        );
+        assert_eq!(
+            frame.pre_context.value().unwrap()[0].as_str(),
+            Some("// Adds two numbers.")
+        );
+        assert_eq!(frame.post_context.value().unwrap().len(), 2);
+
+        let data = frame.data.value().unwrap();
+        assert_eq!(data.orig_function.as_str(), Some("a"));
+        assert_eq!(data.orig_lineno.value(), Some(&1));
+        assert_eq!(data.orig_colno.value(), Some(&10));
+        assert_eq!(data.sourcemap.value(), None);
+    }
+
+    #[test]
+    fn test_resolve_frame_sourcemap_url() {
+        let source_map = source_map().with_url("~/static/app.min.js.map");
+
+        let mut frame = minified_frame();
+        assert!(resolve_frame(&mut frame, &source_map));
+
+        let data = frame.data.value().unwrap();
+        assert_eq!(data.sourcemap.as_str(), Some("~/static/app.min.js.map"));
+    }
+
+    #[test]
+    fn test_resolve_frame_without_mapping() {
+        let mut frame = minified_frame();
+        frame.lineno = Annotated::new(5);
+
+        assert!(!resolve_frame(&mut frame, &source_map()));
+        assert_eq!(frame.function.as_str(), Some("a"));
+        assert!(frame.data.value().is_none());
+    }
+
+    #[test]
+    fn test_trim_line() {
+        let line = "x".repeat(300);
+
+        let trimmed = trim_line(&line, 150);
+        assert!(trimmed.starts_with("{snip} "));
+        assert!(trimmed.ends_with(" {snip}"));
+        assert_eq!(trimmed.len(), MAX_LINE_LENGTH + 2 * (SNIP.len() + 1));
+
+        assert!(!trim_line(&line, 0).starts_with("{snip}"));
+        assert_eq!(trim_line("short", 0), "short");
+    }
+
+    #[test]
+    fn test_processor_platform() {
+        let source_map = Arc::new(source_map());
+
+        let stacktrace = |frame: Frame| {
+            Annotated::new(Stacktrace(RawStacktrace {
+                frames: Annotated::new(vec![Annotated::new(frame)]),
+                ..Default::default()
+            }))
+        };
+
+        let mut event = Annotated::new(Event {
+            platform: Annotated::new("python".to_owned()),
+            exceptions: Annotated::new(Values::new(vec![
+                Annotated::new(Exception {
+                    stacktrace: stacktrace(minified_frame()),
+                    ..Default::default()
+                }),
+                Annotated::new(Exception {
+                    stacktrace: stacktrace(Frame {
+                        platform: Annotated::new("javascript".to_owned()),
+                        ..minified_frame()
+                    }),
+                    ..Default::default()
+                }),
+            ])),
+            ..Default::default()
+        });
+
+        let mut processor = SourceMapProcessor::new(|_: &str| Some(source_map.clone()));
+        process_value(&mut event, &mut processor, ProcessingState::root()).unwrap();
+        assert_eq!(processor.resolved(), 1);
+    }
+}
@@ -27,24 +27,31 @@ pub fn process_non_raw_frame(frame: &mut Annotated<Frame>) {
         }
 
         if frame.filename.value().is_empty() {
-            if let Some(abs_path) = frame.abs_path.value_mut() {
-                frame.filename = Annotated::new(abs_path.clone());
-
-                if is_url(abs_path.as_str()) {
-                    if let Ok(url) = Url::parse(abs_path.as_str()) {
-                        let path = url.path();
-
-                        if !path.is_empty() && path != "/" {
-                            frame.filename = Annotated::new(path.into());
-                        }
-                    }
-                }
+            if let Some(abs_path) = frame.abs_path.value() {
+                frame.filename = Annotated::new(filename_from_abs_path(abs_path.as_str()).into());
             }
         }
         Ok(())
     });
 }
 
+/// Returns the filename for a frame's absolute path.
+///
+/// For URLs, this is the path component of the URL. Otherwise, the absolute path is returned.
+pub fn filename_from_abs_path(abs_path: &str) -> String {
+    if is_url(abs_path) {
+        if let Ok(url) = Url::parse(abs_path) {
+            let path = url.path();
+
+            if !path.is_empty() && path != "/" {
+                return path.to_owned();
+            }
+        }
+    }
+
+    abs_path.to_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use similar_asserts::assert_eq;
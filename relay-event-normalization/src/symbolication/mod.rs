@@ -0,0 +1,621 @@
+//! Symbolication of native stack frames with local debug files.
+//!
+//! Debug files are parsed with `symbolic` into a [`SymbolFile`], which maps addresses relative to
+//! the start of an image to function names and source locations. Breakpad symbol files (`.sym`)
+//! and ELF, Mach-O, and PE object files are supported. Function names are read from the debug
+//! information or the symbol table, and source locations from the debug information, if present.
+//!
+//! Inline frames are not resolved, and function names are not demangled.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use relay_event_schema::processor::{
+    self, ProcessValue, ProcessingResult, ProcessingState, Processor,
+};
+use relay_event_schema::protocol::{
+    Addr, DebugId, DebugImage, Event, Frame, InstructionAddrAdjustment, Stacktrace,
+};
+use relay_protocol::{Annotated, Meta};
+use symbolic_debuginfo::{Object, ObjectError};
+
+/// An error returned when parsing a [`SymbolFile`].
+#[derive(Debug, thiserror::Error)]
+#[error("invalid debug file")]
+pub struct SymbolError(#[from] ObjectError);
+
+/// A function or public symbol in a debug file.
+#[derive(Debug)]
+struct Function {
+    address: u64,
+    /// The size of the function, if known.
+    ///
+    /// Symbols without a size extend up to the next symbol.
+    size: Option<u64>,
+    name: String,
+}
+
+/// A range of instructions that originate from a single source line.
+#[derive(Debug)]
+struct LineRecord {
+    address: u64,
+    size: u64,
+    line: u64,
+    /// Index into the file names of the [`SymbolFile`].
+    file: usize,
+}
+
+/// Symbol information for an address resolved from a [`SymbolFile`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SymbolInfo<'a> {
+    /// The name of the function containing the address.
+    pub function: &'a str,
+    /// The start address of the function relative to the image.
+    pub symbol_addr: u64,
+    /// The path of the source file, if known.
+    pub filename: Option<&'a str>,
+    /// The line in the source file, if known.
+    pub lineno: Option<u64>,
+}
+
+/// Functions and source locations parsed from a debug file.
+#[derive(Debug, Default)]
+pub struct SymbolFile {
+    functions: Vec<Function>,
+    lines: Vec<LineRecord>,
+    files: Vec<String>,
+}
+
+impl SymbolFile {
+    /// Parses a Breakpad symbol file or an object file.
+    pub fn parse(data: &[u8]) -> Result<Self, SymbolError> {
+        let object = Object::parse(data)?;
+
+        let mut functions = Vec::new();
+        let mut lines = Vec::new();
+        let mut files = Vec::new();
+        let mut file_indexes = HashMap::new();
+
+        let session = object.debug_session()?;
+        for function in session.functions() {
+            let function = function?;
+
+            for line in &function.lines {
+                let Some(size) = line.size else {
+                    continue;
+                };
+
+                let file = *file_indexes.entry(line.file.path_str()).or_insert_with_key(
+                    |path: &String| {
+                        files.push(path.clone());
+                        files.len() - 1
+                    },
+                );
+
+                lines.push(LineRecord {
+                    address: line.address,
+                    size,
+                    line: line.line,
+                    file,
+                });
+            }
+
+            functions.push(Function {
+                address: function.address,
+                size: Some(function.size).filter(|&size| size > 0),
+                name: function.name.as_str().to_owned(),
+            });
+        }
+
+        // Public symbols cover functions without debug information.
+        let symbol_map = object.symbol_map();
+        for symbol in &symbol_map {
+            if let Some(name) = symbol.name() {
+                functions.push(Function {
+                    address: symbol.address,
+                    size: Some(symbol.size).filter(|&size| size > 0),
+                    name: name.to_owned(),
+                });
+            }
+        }
+
+        Ok(Self::new(functions, lines, files))
+    }
+
+    fn new(mut functions: Vec<Function>, mut lines: Vec<LineRecord>, files: Vec<String>) -> Self {
+        // Prefer functions with a known size over public symbols at the same address.
+        functions.sort_by_key(|function| (function.address, function.size.is_none()));
+        functions.dedup_by_key(|function| function.address);
+
+        let mut next_address = None;
+        for function in functions.iter_mut().rev() {
+            if function.size.is_none() {
+                function.size = next_address.map(|next| next - function.address);
+            }
+            next_address = Some(function.address);
+        }
+
+        lines.sort_by_key(|line| line.address);
+
+        Self {
+            functions,
+            lines,
+            files,
+        }
+    }
+
+    /// Returns `true` if this file contains no functions.
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// Returns the approximate number of bytes used by the parsed symbols.
+    pub fn size(&self) -> usize {
+        let names = self
+            .functions
+            .iter()
+            .map(|function| function.name.capacity());
+        let files = self.files.iter().map(String::capacity);
+
+        std::mem::size_of::<Self>()
+            + names.chain(files).sum::<usize>()
+            + self.functions.capacity() * std::mem::size_of::<Function>()
+            + self.lines.capacity() * std::mem::size_of::<LineRecord>()
+            + self.files.capacity() * std::mem::size_of::<String>()
+    }
+
+    /// Looks up the function and source location of an address relative to the image.
+    pub fn lookup(&self, address: u64) -> Option<SymbolInfo<'_>> {
+        let index = self
+            .functions
+            .partition_point(|function| function.address <= address);
+        let function = self.functions.get(index.checked_sub(1)?)?;
+
+        if function
+            .size
+            .map_or(false, |size| address - function.address >= size)
+        {
+            return None;
+        }
+
+        let index = self.lines.partition_point(|line| line.address <= address);
+        let line = index
+            .checked_sub(1)
+            .and_then(|index| self.lines.get(index))
+            .filter(|line| address - line.address < line.size && line.line > 0);
+
+        Some(SymbolInfo {
+            function: &function.name,
+            symbol_addr: function.address,
+            filename: line.map(|line| self.files[line.file].as_str()),
+            lineno: line.map(|line| line.line),
+        })
+    }
+}
+
+/// A native image loaded into the process, taken from the debug meta of an event.
+#[derive(Clone, Debug)]
+pub struct NativeImage {
+    /// The path of the image's code file.
+    pub code_file: Option<String>,
+    /// The identifier of the image's code file, such as the ELF build ID.
+    pub code_id: Option<String>,
+    /// The path of the image's debug file.
+    pub debug_file: Option<String>,
+    /// The identifier of the image's debug file.
+    pub debug_id: Option<DebugId>,
+    /// The address at which the image is loaded.
+    pub image_addr: u64,
+    /// The size of the image in memory.
+    pub image_size: u64,
+}
+
+impl NativeImage {
+    /// Returns the native image for a debug image, or `None` if the image is not native or lacks
+    /// its memory range.
+    pub fn from_debug_image(image: &DebugImage) -> Option<Self> {
+        let image = match image {
+            DebugImage::Symbolic(image)
+            | DebugImage::MachO(image)
+            | DebugImage::Elf(image)
+            | DebugImage::Pe(image)
+            | DebugImage::Wasm(image) => image,
+            _ => return None,
+        };
+
+        Some(Self {
+            code_file: image.code_file.value().map(|path| path.as_str().to_owned()),
+            code_id: image.code_id.value().map(|id| id.0.to_string()),
+            debug_file: image
+                .debug_file
+                .value()
+                .map(|path| path.as_str().to_owned()),
+            debug_id: image.debug_id.value().cloned(),
+            image_addr: image.image_addr.value()?.0,
+            image_size: *image.image_size.value()?,
+        })
+    }
+
+    /// Returns the file name of the image's debug file, falling back to its code file.
+    pub fn debug_file_name(&self) -> Option<&str> {
+        let path = self.debug_file.as_deref().or(self.code_file.as_deref())?;
+        Some(basename(path)).filter(|name| !name.is_empty())
+    }
+
+    /// Returns `true` if the address lies within this image.
+    fn contains(&self, address: u64) -> bool {
+        address >= self.image_addr && address - self.image_addr < self.image_size
+    }
+}
+
+/// Returns the file name of a path that may use Unix or Windows separators.
+fn basename(path: &str) -> &str {
+    path.rsplit(|c| c == '/' || c == '\\')
+        .next()
+        .unwrap_or(path)
+}
+
+/// Resolves native frames in all stack traces of an event.
+///
+/// Frames are matched to the images listed in the event's debug meta by their `instruction_addr`.
+/// The lookup function receives the image and returns its symbols, if available. Resolved frames
+/// receive their function, symbol, package, and source location.
+pub struct SymbolicationProcessor<F> {
+    lookup: F,
+    images: Vec<NativeImage>,
+    resolved: usize,
+}
+
+impl<F> SymbolicationProcessor<F>
+where
+    F: FnMut(&NativeImage) -> Option<Arc<SymbolFile>>,
+{
+    /// Creates a new processor with the given symbol lookup.
+    pub fn new(lookup: F) -> Self {
+        Self {
+            lookup,
+            images: Vec::new(),
+            resolved: 0,
+        }
+    }
+
+    /// Returns the number of frames resolved by this processor.
+    pub fn resolved(&self) -> usize {
+        self.resolved
+    }
+
+    fn symbolicate_frame(&mut self, frame: &mut Frame, adjust: bool) {
+        let Some(&Addr(address)) = frame.instruction_addr.value() else {
+            return;
+        };
+
+        // Return addresses point to the instruction after the call.
+        let address = if adjust {
+            address.saturating_sub(1)
+        } else {
+            address
+        };
+
+        let Some(image) = self.images.iter().find(|image| image.contains(address)) else {
+            return;
+        };
+
+        let Some(symbols) = (self.lookup)(image) else {
+            return;
+        };
+
+        let Some(info) = symbols.lookup(address - image.image_addr) else {
+            return;
+        };
+
+        // Values sent by the SDK take precedence. Function names are not demangled, so the raw
+        // name also serves as the function name.
+        fill(&mut frame.symbol, Some(info.function.to_owned()));
+        fill(&mut frame.function, Some(info.function.to_owned()));
+        fill(
+            &mut frame.symbol_addr,
+            Some(Addr(image.image_addr + info.symbol_addr)),
+        );
+        fill(&mut frame.package, image.code_file.clone());
+
+        if frame.abs_path.value().is_none() && frame.filename.value().is_none() {
+            fill(&mut frame.abs_path, info.filename.map(Into::into));
+            fill(
+                &mut frame.filename,
+                info.filename.map(|f| basename(f).into()),
+            );
+        }
+
+        fill(&mut frame.lineno, info.lineno);
+
+        self.resolved += 1;
+    }
+}
+
+/// Sets the value of a frame field, unless it already has one.
+fn fill<T>(field: &mut Annotated<T>, value: Option<T>) {
+    if field.value().is_none() && value.is_some() {
+        field.set_value(value);
+    }
+}
+
+impl<F> Processor for SymbolicationProcessor<F>
+where
+    F: FnMut(&NativeImage) -> Option<Arc<SymbolFile>>,
+{
+    fn process_event(
+        &mut self,
+        event: &mut Event,
+        _meta: &mut Meta,
+        state: &ProcessingState<'_>,
+    ) -> ProcessingResult {
+        let images = event
+            .debug_meta
+            .value()
+            .and_then(|debug_meta| debug_meta.images.value());
+
+        self.images = images
+            .into_iter()
+            .flatten()
+            .filter_map(|image| NativeImage::from_debug_image(image.value()?))
+            .collect();
+
+        if self.images.is_empty() {
+            return Ok(());
+        }
+
+        event.process_child_values(self, state)
+    }
+
+    fn process_stacktrace(
+        &mut self,
+        stacktrace: &mut Stacktrace,
+        _meta: &mut Meta,
+        _state: &ProcessingState<'_>,
+    ) -> ProcessingResult {
+        let adjustment = stacktrace.instruction_addr_adjustment.value().cloned();
+
+        if let Some(frames) = stacktrace.frames.value_mut() {
+            // Frames are ordered from the outermost call, so the last frame is the crashing one.
+            let len = frames.len();
+            for (index, frame) in frames.iter_mut().enumerate() {
+                let adjust = match adjustment {
+                    Some(InstructionAddrAdjustment::None) => false,
+                    Some(InstructionAddrAdjustment::All) => true,
+                    _ => index + 1 < len,
+                };
+
+                processor::apply(frame, |frame, _| {
+                    self.symbolicate_frame(frame, adjust);
+                    Ok(())
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use relay_event_schema::processor::process_value;
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    const SYMBOLS: &str = "\
+MODULE Linux x86_64 B0B1AB7D5E8C4E6DB8F1F2C5A6B7C8D90 libapp.so
+INFO CODE_ID 7DABB1B08C5E6D4EB8F1F2C5A6B7C8D9
+FILE 0 /src/app/main.c
+FILE 1 /src/app/util.c
+FUNC 1000 20 0 main
+1000 10 12 0
+1010 10 13 0
+FUNC m 1020 10 0 helper(int)
+1020 10 7 1
+PUBLIC 1100 0 exported_symbol
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+";
+
+    fn symbols() -> SymbolFile {
+        SymbolFile::parse(SYMBOLS.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_lookup() {
+        let symbols = symbols();
+
+        let info = symbols.lookup(0x1014).unwrap();
+        assert_eq!(info.function, "main");
+        assert_eq!(info.symbol_addr, 0x1000);
+        assert_eq!(info.filename, Some("/src/app/main.c"));
+        assert_eq!(info.lineno, Some(13));
+
+        let info = symbols.lookup(0x1020).unwrap();
+        assert_eq!(info.function, "helper(int)");
+        assert_eq!(info.filename, Some("/src/app/util.c"));
+
+        // Public symbols extend to the end of the image and have no line information.
+        let info = symbols.lookup(0x2000).unwrap();
+        assert_eq!(info.function, "exported_symbol");
+        assert_eq!(info.lineno, None);
+
+        assert!(symbols.lookup(0x1030).is_none());
+        assert!(symbols.lookup(0x500).is_none());
+    }
+
+    #[test]
+    fn test_size() {
+        let symbols = symbols();
+        assert!(symbols.size() > SymbolFile::default().size());
+    }
+
+    #[test]
+    fn test_invalid_breakpad() {
+        let data = "MODULE Linux x86_64 ID libapp.so\nFUNC zz 20 0 main\n";
+        assert!(SymbolFile::parse(data.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_object() {
+        assert!(SymbolFile::parse(b"not an object file").is_err());
+    }
+
+    #[test]
+    fn test_basename() {
+        assert_eq!(basename("/src/app/main.c"), "main.c");
+        assert_eq!(basename("C:\\src\\main.c"), "main.c");
+        assert_eq!(basename("main.c"), "main.c");
+    }
+
+    #[test]
+    fn test_processor() {
+        let mut event = Annotated::<Event>::from_json(
+            r#"{
+                "platform": "native",
+                "debug_meta": {
+                    "images": [{
+                        "type": "elf",
+                        "code_file": "/usr/lib/libapp.so",
+                        "debug_id": "7dabb1b0-8c5e-6d4e-b8f1-f2c5a6b7c8d9",
+                        "image_addr": "0x7f0000000000",
+                        "image_size": 65536
+                    }]
+                },
+                "exception": {
+                    "values": [{
+                        "type": "SIGSEGV",
+                        "stacktrace": {
+                            "frames": [
+                                {"instruction_addr": "0x7f0000001015"},
+                                {"instruction_addr": "0x7f0000001020"},
+                                {"instruction_addr": "0x7f1000000000"}
+                            ]
+                        }
+                    }]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let symbols = Arc::new(symbols());
+        let mut processor = SymbolicationProcessor::new(|image: &NativeImage| {
+            assert_eq!(image.code_file.as_deref(), Some("/usr/lib/libapp.so"));
+            Some(symbols.clone())
+        });
+        process_value(&mut event, &mut processor, ProcessingState::root()).unwrap();
+        assert_eq!(processor.resolved(), 2);
+
+        let frames = event
+            .value()
+            .unwrap()
+            .exceptions
+            .value()
+            .unwrap()
+            .values
+            .value()
+            .unwrap()[0]
+            .value()
+            .unwrap()
+            .stacktrace
+            .value()
+            .unwrap()
+            .frames
+            .value()
+            .unwrap();
+
+        // The return address of the calling frame is adjusted into the call instruction.
+        let caller = frames[0].value().unwrap();
+        assert_eq!(caller.function.as_str(), Some("main"));
+        assert_eq!(caller.lineno.value(), Some(&13));
+        assert_eq!(caller.filename.value().map(|f| f.as_str()), Some("main.c"));
+        assert_eq!(caller.symbol_addr.value(), Some(&Addr(0x7f0000001000)));
+        assert_eq!(caller.package.as_str(), Some("/usr/lib/libapp.so"));
+
+        // The return address at the start of `helper` is adjusted into the end of `main`.
+        let adjusted = frames[1].value().unwrap();
+        assert_eq!(adjusted.function.as_str(), Some("main"));
+
+        // The crashing frame lies outside of all images.
+        let crashing = frames[2].value().unwrap();
+        assert!(crashing.function.value().is_none());
+    }
+
+    #[test]
+    fn test_processor_keeps_sdk_values() {
+        let mut event = Annotated::<Event>::from_json(
+            r#"{
+                "platform": "native",
+                "debug_meta": {
+                    "images": [{
+                        "type": "elf",
+                        "code_file": "/usr/lib/libapp.so",
+                        "debug_id": "7dabb1b0-8c5e-6d4e-b8f1-f2c5a6b7c8d9",
+                        "image_addr": "0x7f0000000000",
+                        "image_size": 65536
+                    }]
+                },
+                "threads": {
+                    "values": [{
+                        "stacktrace": {
+                            "frames": [{
+                                "instruction_addr": "0x7f0000001015",
+                                "function": "app::main",
+                                "filename": "main.cpp"
+                            }]
+                        }
+                    }]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let symbols = Arc::new(symbols());
+        let mut processor = SymbolicationProcessor::new(|_: &NativeImage| Some(symbols.clone()));
+        process_value(&mut event, &mut processor, ProcessingState::root()).unwrap();
+        assert_eq!(processor.resolved(), 1);
+
+        let frame = event
+            .value()
+            .unwrap()
+            .threads
+            .value()
+            .unwrap()
+            .values
+            .value()
+            .unwrap()[0]
+            .value()
+            .unwrap()
+            .stacktrace
+            .value()
+            .unwrap()
+            .frames
+            .value()
+            .unwrap()[0]
+            .value()
+            .unwrap();
+
+        assert_eq!(frame.function.as_str(), Some("app::main"));
+        assert_eq!(frame.symbol.as_str(), Some("main"));
+        assert_eq!(frame.filename.value().map(|f| f.as_str()), Some("main.cpp"));
+        assert!(frame.abs_path.value().is_none());
+        assert_eq!(frame.lineno.value(), Some(&13));
+    }
+
+    #[test]
+    fn test_from_debug_image() {
+        let image = Annotated::<DebugImage>::from_json(
+            r#"{
+                "type": "elf",
+                "code_file": "/usr/lib/libapp.so",
+                "code_id": "7dabb1b08c5e6d4eb8f1f2c5a6b7c8d9",
+                "debug_id": "7dabb1b0-8c5e-6d4e-b8f1-f2c5a6b7c8d9",
+                "image_addr": "0x1000"
+            }"#,
+        )
+        .unwrap();
+
+        // Images without a size cannot be matched to frames.
+        assert!(NativeImage::from_debug_image(image.value().unwrap()).is_none());
+    }
+}
@@ -0,0 +1,323 @@
+//! Project-defined rules for normalizing event tags.
+
+use once_cell::sync::OnceCell;
+use regex::Regex;
+use relay_common::glob3::GlobPatterns;
+use relay_event_schema::protocol::{Contexts, Event, TagEntry, Tags};
+use relay_protocol::{Annotated, Error, IntoValue, Value};
+use serde::{Deserialize, Serialize};
+
+/// A rule that normalizes the tags of an event.
+///
+/// Rules are applied in order during event normalization, after contexts have been normalized.
+/// Tags created or changed by rules are validated like tags sent by the client.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TagRule {
+    /// Renames a tag, replacing the value of the target tag if it exists.
+    Rename {
+        /// The name of the tag to rename.
+        from: String,
+        /// The new name of the tag.
+        to: String,
+    },
+
+    /// Merges several tags into one.
+    ///
+    /// The target tag keeps its value if it exists. Otherwise, it receives the value of the first
+    /// source tag that exists. All source tags are removed.
+    Merge {
+        /// The names of the tags to merge, in order of precedence.
+        from: Vec<String>,
+        /// The name of the merged tag.
+        to: String,
+    },
+
+    /// Converts the values of all matching tags to lowercase.
+    Lowercase {
+        /// Glob patterns for the names of the tags.
+        tags: GlobPatterns,
+    },
+
+    /// Sets a tag from a field of a context.
+    FromContext(ContextTagRule),
+
+    /// Removes all matching tags.
+    Drop {
+        /// Glob patterns for the names of the tags.
+        tags: GlobPatterns,
+    },
+
+    /// Unsupported rule for forward compatibility.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Sets a tag from a field of a context, see [`TagRule::FromContext`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ContextTagRule {
+    /// The key of the context, for example `os`.
+    pub context: String,
+
+    /// The path to the field within the context, separated by dots.
+    pub field: String,
+
+    /// A regular expression applied to the field value.
+    ///
+    /// If the expression contains a capture group, the tag receives the first group. Otherwise,
+    /// it receives the entire match. No tag is set if the expression does not match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+
+    /// The name of the tag to set.
+    pub tag: String,
+
+    #[serde(skip)]
+    regex: OnceCell<Result<Regex, regex::Error>>,
+}
+
+impl ContextTagRule {
+    /// Returns the tag value for a field value, or `None` if the pattern does not match.
+    fn extract(&self, value: String) -> Result<Option<String>, &regex::Error> {
+        let Some(ref pattern) = self.pattern else {
+            return Ok(Some(value));
+        };
+
+        let regex = self.regex.get_or_init(|| Regex::new(pattern)).as_ref()?;
+
+        let Some(captures) = regex.captures(&value) else {
+            return Ok(None);
+        };
+
+        let capture = captures.get(1).or_else(|| captures.get(0));
+        Ok(capture.map(|m| m.as_str().to_owned()))
+    }
+}
+
+/// Reads a field of a context as a string.
+///
+/// Returns `None` if the context or field does not exist, or if the field is not a string,
+/// number, or boolean.
+fn context_field(contexts: Option<&Contexts>, key: &str, field: &str) -> Option<String> {
+    let context = contexts?.get_key(key)?;
+    let mut value = IntoValue::into_value(context.clone());
+
+    for segment in field.split('.') {
+        value = match value {
+            Value::Object(mut object) => object.remove(segment)?.into_value()?,
+            _ => return None,
+        };
+    }
+
+    match value {
+        Value::String(string) => Some(string),
+        Value::Bool(boolean) => Some(boolean.to_string()),
+        Value::I64(number) => Some(number.to_string()),
+        Value::U64(number) => Some(number.to_string()),
+        Value::F64(number) => Some(number.to_string()),
+        Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+/// Returns `true` if the name of the tag matches any of the patterns.
+fn is_match(entry: &Annotated<TagEntry>, patterns: &GlobPatterns) -> bool {
+    entry
+        .value()
+        .and_then(|entry| entry.key())
+        .map_or(false, |key| patterns.is_match(key))
+}
+
+/// Applies the tag rules of a project to the event.
+///
+/// Rules that cannot be applied, such as rules with invalid patterns, are skipped and recorded as
+/// errors in the metadata of the event's tags.
+pub fn apply_tag_rules(event: &mut Event, rules: &[TagRule]) {
+    if rules.is_empty() {
+        return;
+    }
+
+    let contexts = event.contexts.value();
+    let tags = &mut event.tags.value_mut().get_or_insert_with(Tags::default).0;
+    let mut errors = Vec::new();
+
+    for rule in rules {
+        match rule {
+            TagRule::Rename { from, to } => {
+                if let Some(value) = tags.remove(from) {
+                    tags.insert(to.clone(), value);
+                }
+            }
+            TagRule::Merge { from, to } => {
+                let mut merged = tags.remove(to).filter(|value| value.value().is_some());
+                for source in from {
+                    let value = tags.remove(source);
+                    if merged.is_none() {
+                        merged = value.filter(|value| value.value().is_some());
+                    }
+                }
+
+                if let Some(value) = merged {
+                    tags.insert(to.clone(), value);
+                }
+            }
+            TagRule::Lowercase { tags: patterns } => {
+                for entry in tags.iter_mut() {
+                    if !is_match(entry, patterns) {
+                        continue;
+                    }
+
+                    if let Some(value) = entry
+                        .value_mut()
+                        .as_mut()
+                        .and_then(|e| e.1.value_mut().as_mut())
+                    {
+                        *value = value.to_lowercase();
+                    }
+                }
+            }
+            TagRule::FromContext(rule) => {
+                let Some(value) = context_field(contexts, &rule.context, &rule.field) else {
+                    continue;
+                };
+
+                match rule.extract(value) {
+                    Ok(Some(value)) => {
+                        tags.insert(rule.tag.clone(), Annotated::new(value));
+                    }
+                    Ok(None) => (),
+                    Err(error) => errors.push(Error::invalid(format!(
+                        "invalid pattern in tag rule for {}: {error}",
+                        rule.tag
+                    ))),
+                }
+            }
+            TagRule::Drop { tags: patterns } => {
+                tags.retain(|entry| !is_match(entry, patterns));
+            }
+            TagRule::Unknown => (),
+        }
+    }
+
+    for error in errors {
+        event.tags.meta_mut().add_error(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(value: serde_json::Value) -> Vec<TagRule> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn event(json: &str) -> Annotated<Event> {
+        Annotated::<Event>::from_json(json).unwrap()
+    }
+
+    fn tag<'a>(event: &'a Annotated<Event>, key: &str) -> Option<&'a str> {
+        event
+            .value()?
+            .tags
+            .value()?
+            .0
+            .get_value(key)
+            .map(String::as_str)
+    }
+
+    #[test]
+    fn test_rename_and_merge() {
+        let mut event = event(r#"{"tags": {"old": "a", "srv": "b", "host": "c"}}"#);
+        let rules = rules(serde_json::json!([
+            {"type": "rename", "from": "old", "to": "new"},
+            {"type": "merge", "from": ["srv", "host"], "to": "server"}
+        ]));
+
+        apply_tag_rules(event.value_mut().as_mut().unwrap(), &rules);
+
+        assert_eq!(tag(&event, "old"), None);
+        assert_eq!(tag(&event, "new"), Some("a"));
+        assert_eq!(tag(&event, "srv"), None);
+        assert_eq!(tag(&event, "host"), None);
+        assert_eq!(tag(&event, "server"), Some("b"));
+    }
+
+    #[test]
+    fn test_merge_keeps_target() {
+        let mut event = event(r#"{"tags": {"server": "a", "host": "b"}}"#);
+        let rules = rules(serde_json::json!([
+            {"type": "merge", "from": ["host"], "to": "server"}
+        ]));
+
+        apply_tag_rules(event.value_mut().as_mut().unwrap(), &rules);
+
+        assert_eq!(tag(&event, "server"), Some("a"));
+        assert_eq!(tag(&event, "host"), None);
+    }
+
+    #[test]
+    fn test_lowercase_and_drop() {
+        let mut event = event(
+            r#"{"tags": {"region": "EU-West", "Mode": "DEBUG", "internal.id": "1", "internal.ts": "2"}}"#,
+        );
+        let rules = rules(serde_json::json!([
+            {"type": "lowercase", "tags": ["region"]},
+            {"type": "drop", "tags": ["internal.*"]}
+        ]));
+
+        apply_tag_rules(event.value_mut().as_mut().unwrap(), &rules);
+
+        assert_eq!(tag(&event, "region"), Some("eu-west"));
+        assert_eq!(tag(&event, "Mode"), Some("DEBUG"));
+        assert_eq!(tag(&event, "internal.id"), None);
+        assert_eq!(tag(&event, "internal.ts"), None);
+    }
+
+    #[test]
+    fn test_from_context() {
+        let mut event = event(
+            r#"{
+                "contexts": {
+                    "os": {"type": "os", "name": "Android", "version": "14.1"},
+                    "app": {"type": "app", "app_build": 42},
+                    "custom": {"type": "custom", "nested": {"flavor": "beta"}}
+                }
+            }"#,
+        );
+        let rules = rules(serde_json::json!([
+            {"type": "fromContext", "context": "os", "field": "version", "pattern": "^(\\d+)\\.", "tag": "os.major"},
+            {"type": "fromContext", "context": "app", "field": "app_build", "tag": "build"},
+            {"type": "fromContext", "context": "custom", "field": "nested.flavor", "tag": "flavor"},
+            {"type": "fromContext", "context": "os", "field": "name", "pattern": "^iOS", "tag": "ios"},
+            {"type": "fromContext", "context": "device", "field": "model", "tag": "model"}
+        ]));
+
+        apply_tag_rules(event.value_mut().as_mut().unwrap(), &rules);
+
+        assert_eq!(tag(&event, "os.major"), Some("14"));
+        assert_eq!(tag(&event, "build"), Some("42"));
+        assert_eq!(tag(&event, "flavor"), Some("beta"));
+        assert_eq!(tag(&event, "ios"), None);
+        assert_eq!(tag(&event, "model"), None);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let mut event = event(r#"{"contexts": {"os": {"type": "os", "name": "Linux"}}}"#);
+        let rules = rules(serde_json::json!([
+            {"type": "fromContext", "context": "os", "field": "name", "pattern": "(", "tag": "os"}
+        ]));
+
+        apply_tag_rules(event.value_mut().as_mut().unwrap(), &rules);
+
+        let tags = &event.value().unwrap().tags;
+        assert!(tags.meta().has_errors());
+        assert_eq!(tag(&event, "os"), None);
+    }
+
+    #[test]
+    fn test_unknown_rule() {
+        let rules = rules(serde_json::json!([{"type": "unsupported", "tag": "a"}]));
+        assert!(matches!(rules[..], [TagRule::Unknown]));
+    }
+}
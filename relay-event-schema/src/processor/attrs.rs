@@ -53,6 +53,8 @@ pub enum ValueType {
     Minidump,
     HeapMemory,
     StackMemory,
+    AppleCrashReport,
+    UnrealContext,
 }
 
 impl ValueType {
@@ -89,6 +91,8 @@ relay_common::derive_fromstr_and_display!(ValueType, UnknownValueTypeError, {
     ValueType::Minidump => "minidump",
     ValueType::HeapMemory => "heap_memory",
     ValueType::StackMemory => "stack_memory",
+    ValueType::AppleCrashReport => "apple_crash_report",
+    ValueType::UnrealContext => "unreal_context",
 });
 
 /// The maximum length of a field.
@@ -4,7 +4,9 @@ use std::str::FromStr;
 use relay_common::time;
 #[cfg(feature = "jsonschema")]
 use relay_jsonschema_derive::JsonSchema;
-use relay_protocol::{Annotated, Array, Empty, FromValue, Getter, IntoValue, Object, Val, Value};
+use relay_protocol::{
+    Annotated, Array, Empty, FromValue, Getter, GetterIter, IntoValue, Object, Val, Value,
+};
 #[cfg(feature = "jsonschema")]
 use schemars::{gen::SchemaGenerator, schema::Schema};
 use sentry_release_parser::Release as ParsedRelease;
@@ -443,6 +445,13 @@ pub struct Event {
     #[metastructure(omit_from_schema)] // deprecated
     pub checksum: Annotated<String>,
 
+    /// Grouping hash computed by Relay from the project's grouping config.
+    ///
+    /// Events with the same grouping hash belong to the same issue.
+    #[metastructure(max_chars = "hash")]
+    #[metastructure(omit_from_schema)] // not part of external schema
+    pub grouping_hash: Annotated<String>,
+
     /// CSP (security) reports.
     #[metastructure(legacy_alias = "sentry.interfaces.Csp")]
     #[metastructure(omit_from_schema)] // we only document error events for now
@@ -640,6 +649,7 @@ impl Getter for Event {
             "transaction" => self.transaction.as_str()?.into(),
             "logger" => self.logger.as_str()?.into(),
             "platform" => self.platform.as_str().unwrap_or("other").into(),
+            "grouping_hash" => self.grouping_hash.as_str()?.into(),
 
             // Fields in top level structures (called "interfaces" in Sentry)
             "user.email" => or_none(&self.user.value()?.email)?.into(),
@@ -658,6 +668,17 @@ impl Getter for Event {
                 .into(),
             "user.geo.region" => self.user.value()?.geo.value()?.region.as_str()?.into(),
             "user.geo.subdivision" => self.user.value()?.geo.value()?.subdivision.as_str()?.into(),
+            "user.geo.asn" => self.user.value()?.geo.value()?.asn.value()?.into(),
+            "user.geo.isp" => self.user.value()?.geo.value()?.isp.as_str()?.into(),
+            "user.geo.is_anonymous" => self.user.value()?.geo.value()?.is_anonymous.value()?.into(),
+            "user.geo.is_hosting_provider" => self
+                .user
+                .value()?
+                .geo
+                .value()?
+                .is_hosting_provider
+                .value()?
+                .into(),
             "request.method" => self.request.value()?.method.as_str()?.into(),
             "request.url" => self.request.value()?.url.as_str()?.into(),
             "transaction.source" => self
@@ -791,12 +812,27 @@ impl Getter for Event {
             }
         })
     }
+
+    fn get_iter(&self, path: &str) -> Option<GetterIter<'_>> {
+        match path.strip_prefix("event.")? {
+            "exception.values" => {
+                let exceptions = self.exceptions.value()?.values.value()?;
+                Some(Box::new(
+                    exceptions
+                        .iter()
+                        .filter_map(Annotated::value)
+                        .map(|exception| exception as &dyn Getter),
+                ))
+            }
+            path => self.extra_at(path.strip_prefix("extra.")?)?.get_iter(""),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use chrono::{TimeZone, Utc};
-    use relay_protocol::{ErrorKind, Map, Meta};
+    use relay_protocol::{ErrorKind, Map, Meta, RuleCondition};
     use similar_asserts::assert_eq;
     use uuid::uuid;
 
@@ -1258,4 +1294,62 @@ mod tests {
         assert_eq!(None, event.get_value("event.user.segment"));
         assert_eq!(None, event.get_value("event.transaction"));
     }
+
+    #[test]
+    fn test_field_value_provider_event_exceptions() {
+        let event = Annotated::<Event>::from_json(
+            r#"{
+                "exception": {
+                    "values": [
+                        {"type": "TypeError", "value": "x is undefined"},
+                        {
+                            "type": "ChunkLoadError",
+                            "value": "Loading chunk 3 failed",
+                            "mechanism": {"type": "onerror", "handled": false}
+                        }
+                    ]
+                },
+                "extra": {
+                    "retries": [1, 2, 3]
+                }
+            }"#,
+        )
+        .unwrap()
+        .into_value()
+        .unwrap();
+
+        let types: Vec<_> = event
+            .get_iter("event.exception.values")
+            .unwrap()
+            .map(|exception| exception.get_value("type"))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                Some(Val::String("TypeError")),
+                Some(Val::String("ChunkLoadError"))
+            ]
+        );
+
+        let condition = RuleCondition::any(
+            "event.exception.values",
+            RuleCondition::glob("type", "Chunk*"),
+        );
+        assert!(condition.matches(&event));
+
+        let condition = RuleCondition::every(
+            "event.exception.values",
+            RuleCondition::eq("mechanism.handled", false),
+        );
+        assert!(!condition.matches(&event));
+
+        let condition =
+            RuleCondition::every("event.extra.retries", RuleCondition::range("", 1.0, 4.0));
+        assert!(condition.matches(&event));
+
+        assert!(event.get_iter("event.release").is_none());
+        assert!(Event::default()
+            .get_iter("event.exception.values")
+            .is_none());
+    }
 }
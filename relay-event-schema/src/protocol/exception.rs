@@ -1,6 +1,6 @@
 #[cfg(feature = "jsonschema")]
 use relay_jsonschema_derive::JsonSchema;
-use relay_protocol::{Annotated, Empty, FromValue, IntoValue, Object, Value};
+use relay_protocol::{Annotated, Empty, FromValue, Getter, IntoValue, Object, Val, Value};
 
 use crate::processor::ProcessValue;
 use crate::protocol::{JsonLenientString, Mechanism, RawStacktrace, Stacktrace, ThreadId};
@@ -72,6 +72,21 @@ pub struct Exception {
     pub other: Object<Value>,
 }
 
+/// Exceptions are items of `event.exception.values` and use paths relative to the exception.
+impl Getter for Exception {
+    fn get_value(&self, path: &str) -> Option<Val<'_>> {
+        Some(match path {
+            "type" => self.ty.as_str()?.into(),
+            "value" => self.value.as_str()?.into(),
+            "module" => self.module.as_str()?.into(),
+            "mechanism.type" => self.mechanism.value()?.ty.as_str()?.into(),
+            "mechanism.handled" => self.mechanism.value()?.handled.value()?.into(),
+            "mechanism.synthetic" => self.mechanism.value()?.synthetic.value()?.into(),
+            _ => return None,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use relay_protocol::Map;
@@ -27,11 +27,12 @@
 
 #[cfg(feature = "jsonschema")]
 use relay_jsonschema_derive::JsonSchema;
-use relay_protocol::{Annotated, Array, Empty, FromValue, IntoValue};
+use relay_protocol::{Annotated, Array, Empty, FromValue, Getter, GetterIter, IntoValue, Val};
 
 use crate::processor::ProcessValue;
 use crate::protocol::{
-    ClientSdkInfo, Contexts, EventId, LenientString, Request, Tags, Timestamp, User,
+    BrowserContext, ClientSdkInfo, Contexts, DefaultContext, DeviceContext, EventId, LenientString,
+    OsContext, Request, Tags, Timestamp, User,
 };
 use uuid::Uuid;
 
@@ -220,6 +221,71 @@ pub struct Replay {
     pub sdk: Annotated<ClientSdkInfo>,
 }
 
+impl Replay {
+    /// Returns a reference to the context if it exists in its default key.
+    fn context<C: DefaultContext>(&self) -> Option<&C> {
+        self.contexts.value()?.get()
+    }
+}
+
+impl Getter for Replay {
+    fn get_value(&self, path: &str) -> Option<Val<'_>> {
+        Some(match path.strip_prefix("replay.")? {
+            "replay_type" => self.replay_type.as_str()?.into(),
+            "segment_id" => self.segment_id.value()?.into(),
+            "platform" => self.platform.as_str()?.into(),
+            "release" => self.release.as_str()?.into(),
+            "dist" => self.dist.as_str()?.into(),
+            "environment" => self.environment.as_str()?.into(),
+            "user.id" => self.user.value()?.id.as_str()?.into(),
+            "user.email" => self.user.value()?.email.as_str()?.into(),
+            "user.ip_address" => self.user.value()?.ip_address.as_str()?.into(),
+            "user.geo.country_code" => self
+                .user
+                .value()?
+                .geo
+                .value()?
+                .country_code
+                .as_str()?
+                .into(),
+            "request.url" => self.request.value()?.url.as_str()?.into(),
+            "sdk.name" => self.sdk.value()?.name.as_str()?.into(),
+            "sdk.version" => self.sdk.value()?.version.as_str()?.into(),
+            "contexts.browser.name" => self.context::<BrowserContext>()?.name.as_str()?.into(),
+            "contexts.device.family" => self.context::<DeviceContext>()?.family.as_str()?.into(),
+            "contexts.os.name" => self.context::<OsContext>()?.name.as_str()?.into(),
+            path => {
+                if let Some(rest) = path.strip_prefix("tags.") {
+                    self.tags.value()?.get(rest)?.into()
+                } else if let Some(rest) = path.strip_prefix("request.headers.") {
+                    self.request
+                        .value()?
+                        .headers
+                        .value()?
+                        .get_header(rest)?
+                        .into()
+                } else {
+                    return None;
+                }
+            }
+        })
+    }
+
+    fn get_iter(&self, path: &str) -> Option<GetterIter<'_>> {
+        match path.strip_prefix("replay.")? {
+            "urls" => {
+                let urls = self.urls.value()?;
+                Some(Box::new(
+                    urls.iter()
+                        .filter_map(Annotated::value)
+                        .map(|url| url as &dyn Getter),
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::{TimeZone, Utc};
@@ -2,6 +2,7 @@ use std::fmt::{self, Display};
 use std::time::SystemTime;
 
 use chrono::{DateTime, Utc};
+use relay_protocol::{Getter, Val};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -145,6 +146,19 @@ pub struct SessionAttributes {
     pub user_agent: Option<String>,
 }
 
+impl SessionAttributes {
+    /// Returns the attribute at a path relative to the session, such as `release`.
+    fn get_attribute(&self, path: &str) -> Option<Val<'_>> {
+        Some(match path {
+            "release" => self.release.as_str().into(),
+            "environment" => self.environment.as_deref()?.into(),
+            "ip_address" => self.ip_address.as_ref()?.as_str().into(),
+            "user_agent" => self.user_agent.as_deref()?.into(),
+            _ => return None,
+        })
+    }
+}
+
 fn default_sequence() -> u64 {
     SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -353,6 +367,27 @@ impl SessionAggregates {
     }
 }
 
+impl Getter for SessionUpdate {
+    fn get_value(&self, path: &str) -> Option<Val<'_>> {
+        Some(match path.strip_prefix("session.")? {
+            "distinct_id" => self.distinct_id.as_deref()?.into(),
+            "init" => self.init.into(),
+            "duration" => self.duration?.into(),
+            "status" => self.status.as_str().into(),
+            "errors" => self.errors.into(),
+            path => return self.attributes.get_attribute(path),
+        })
+    }
+}
+
+/// Session aggregates only expose their shared attributes, such as `session.release`.
+impl Getter for SessionAggregates {
+    fn get_value(&self, path: &str) -> Option<Val<'_>> {
+        self.attributes
+            .get_attribute(path.strip_prefix("session.")?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1,6 +1,8 @@
 #[cfg(feature = "jsonschema")]
 use relay_jsonschema_derive::JsonSchema;
-use relay_protocol::{Annotated, Empty, FromValue, Getter, IntoValue, Object, Val, Value};
+use relay_protocol::{
+    Annotated, Empty, FromValue, Getter, GetterIter, IntoValue, Object, Val, Value,
+};
 
 use crate::processor::ProcessValue;
 use crate::protocol::{
@@ -166,6 +168,15 @@ impl Getter for Span {
             }
         })
     }
+
+    fn get_iter(&self, path: &str) -> Option<GetterIter<'_>> {
+        let key = path.strip_prefix("span.data.")?;
+        let escaped = key.replace("\\.", "\0");
+        let (root, rest) = escaped.split_once('.').unwrap_or((escaped.as_str(), ""));
+
+        let value = self.data.value()?.get(&root.replace('\0', "."))?.value()?;
+        value.get_iter(&rest.replace('\0', "\\."))
+    }
 }
 
 #[cfg(test)]
@@ -174,6 +185,7 @@ mod tests {
     use chrono::{TimeZone, Utc};
     use insta::assert_debug_snapshot;
     use relay_base_schema::metrics::{InformationUnit, MetricUnit};
+    use relay_protocol::RuleCondition;
     use similar_asserts::assert_eq;
 
     use super::*;
@@ -248,6 +260,32 @@ mod tests {
         assert_eq!(span.get_value("span.data.x"), None);
     }
 
+    #[test]
+    fn test_getter_span_data_array() {
+        let span = Annotated::<Span>::from_json(
+            r#"{
+                "data": {
+                    "http": {"status_codes": [200, 503]},
+                    "db.tables": ["users", "orders"]
+                }
+            }"#,
+        )
+        .unwrap()
+        .into_value()
+        .unwrap();
+
+        let condition =
+            RuleCondition::any("span.data.http.status_codes", RuleCondition::gte("", 500));
+        assert!(condition.matches(&span));
+
+        let condition =
+            RuleCondition::every(r"span.data.db\.tables", RuleCondition::regex("", "[a-z]+"));
+        assert!(condition.matches(&span));
+
+        assert!(span.get_iter("span.data.http").is_none());
+        assert!(span.get_iter("span.data.x").is_none());
+    }
+
     #[test]
     fn span_from_event() {
         let event = Annotated::<Event>::from_json(
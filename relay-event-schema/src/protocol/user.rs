@@ -26,6 +26,21 @@ pub struct Geo {
     #[metastructure(pii = "true", max_chars = "summary")]
     pub region: Annotated<String>,
 
+    /// Number of the autonomous system that announces the IP address.
+    #[metastructure(pii = "maybe")]
+    pub asn: Annotated<u64>,
+
+    /// Name of the internet service provider or the organization that owns the autonomous system.
+    #[metastructure(pii = "maybe", max_chars = "summary")]
+    pub isp: Annotated<String>,
+
+    /// Whether the IP address belongs to an anonymizing service, such as a VPN, a public proxy, or
+    /// a Tor exit node.
+    pub is_anonymous: Annotated<bool>,
+
+    /// Whether the IP address belongs to a hosting provider or data center.
+    pub is_hosting_provider: Annotated<bool>,
+
     /// Additional arbitrary fields for forwards compatibility.
     #[metastructure(additional_properties)]
     pub other: Object<Value>,
@@ -107,6 +122,10 @@ mod tests {
             city: Annotated::new("San Francisco".to_string()),
             subdivision: Annotated::new("California".to_string()),
             region: Annotated::new("CA".to_string()),
+            asn: Annotated::empty(),
+            isp: Annotated::empty(),
+            is_anonymous: Annotated::empty(),
+            is_hosting_provider: Annotated::empty(),
             other: {
                 let mut map = Map::new();
                 map.insert(
@@ -129,6 +148,10 @@ mod tests {
             city: Annotated::empty(),
             subdivision: Annotated::empty(),
             region: Annotated::empty(),
+            asn: Annotated::empty(),
+            isp: Annotated::empty(),
+            is_anonymous: Annotated::empty(),
+            is_hosting_provider: Annotated::empty(),
             other: Object::default(),
         });
 
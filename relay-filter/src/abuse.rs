@@ -0,0 +1,321 @@
+//! Implements stateful filtering of abusive clients.
+//!
+//! Unlike all other filters, this filter keeps track of the events it has seen. Events are counted
+//! per project in windows of one minute by client IP address, user, and grouping hash. Once one of
+//! these exceeds its configured limit, all further events in the same window are filtered.
+//!
+//! Counts are kept in memory of a single Relay instance and are not shared between Relays. To
+//! bound memory, a limited number of sources is counted per window.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+use relay_base_schema::project::ProjectId;
+use relay_common::time::UnixTimestamp;
+use relay_event_schema::protocol::Event;
+
+use crate::{AbuseFilterConfig, FilterStatKey};
+
+/// The length of a counting window in seconds.
+const WINDOW_SECONDS: u64 = 60;
+
+/// The number of independently locked shards of the counts.
+const SHARDS: usize = 16;
+
+/// The maximum number of sources counted by each shard.
+const MAX_KEYS_PER_SHARD: usize = 10_000;
+
+/// Identifies a source of events within a project.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum AbuseKey {
+    /// The IP address of the client that sent the event.
+    ClientIp(ProjectId, IpAddr),
+    /// The identifier of the user in the event.
+    User(ProjectId, String),
+    /// The grouping hash of the event.
+    Fingerprint(ProjectId, String),
+}
+
+/// The number of events seen within a window.
+#[derive(Debug)]
+struct Window {
+    index: u64,
+    count: u32,
+}
+
+/// The state of the windows in a shard.
+#[derive(Debug, Default)]
+struct Windows {
+    /// The index of the window that was last used to evict outdated windows.
+    current: u64,
+    /// Counts by abuse key.
+    counts: HashMap<AbuseKey, Window>,
+}
+
+impl Windows {
+    /// Counts an event and returns `true` if the limit is exceeded.
+    ///
+    /// If the shard is full, new sources are not counted.
+    fn increment(&mut self, key: AbuseKey, index: u64, limit: u32) -> bool {
+        if self.counts.len() >= MAX_KEYS_PER_SHARD && !self.counts.contains_key(&key) {
+            return false;
+        }
+
+        let window = self.counts.entry(key).or_insert(Window { index, count: 0 });
+        if window.index != index {
+            window.index = index;
+            window.count = 0;
+        }
+
+        window.count = window.count.saturating_add(1);
+        window.count > limit
+    }
+
+    /// Removes all windows that ended before the given window.
+    fn evict(&mut self, index: u64) {
+        if self.current < index {
+            self.current = index;
+            self.counts.retain(|_, window| window.index >= index);
+        }
+    }
+}
+
+/// A filter that drops events from sources that exceed a rate limit.
+///
+/// The filter is shared between all projects and must be kept for the lifetime of the service.
+/// Counts are distributed across shards by source, so that concurrent events rarely wait for each
+/// other.
+#[derive(Debug, Default)]
+pub struct AbuseFilter {
+    shards: [Mutex<Windows>; SHARDS],
+}
+
+impl AbuseFilter {
+    /// Creates a new abuse filter without any state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shard that counts events of the given source.
+    fn shard(&self, key: &AbuseKey) -> &Mutex<Windows> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[(hasher.finish() % SHARDS as u64) as usize]
+    }
+
+    /// Counts an event and filters it if any of its sources exceeds the configured limits.
+    ///
+    /// Events are counted even if they are filtered, so that a source remains filtered for the
+    /// rest of the window. The fingerprint limit applies to the grouping hash of the event, so
+    /// events without a grouping hash are not counted by fingerprint.
+    pub fn should_filter(
+        &self,
+        project_id: ProjectId,
+        event: &Event,
+        client_ip: Option<IpAddr>,
+        config: &AbuseFilterConfig,
+        now: UnixTimestamp,
+    ) -> Result<(), FilterStatKey> {
+        if config.is_empty() {
+            return Ok(());
+        }
+
+        let index = now.as_secs() / WINDOW_SECONDS;
+        let mut keys = Vec::with_capacity(3);
+
+        if let (Some(limit), Some(client_ip)) = (config.client_ip_limit, client_ip) {
+            keys.push((AbuseKey::ClientIp(project_id, client_ip), limit));
+        }
+
+        let user_id = event.user.value().and_then(|user| user.id.value());
+        if let (Some(limit), Some(user_id)) = (config.user_limit, user_id) {
+            keys.push((
+                AbuseKey::User(project_id, user_id.as_str().to_owned()),
+                limit,
+            ));
+        }
+
+        let grouping_hash = event.grouping_hash.as_str();
+        if let (Some(limit), Some(grouping_hash)) = (config.fingerprint_limit, grouping_hash) {
+            keys.push((
+                AbuseKey::Fingerprint(project_id, grouping_hash.to_owned()),
+                limit,
+            ));
+        }
+
+        let mut exceeded = false;
+        for (key, limit) in keys {
+            let mut windows = self.shard(&key).lock().unwrap_or_else(|e| e.into_inner());
+
+            windows.evict(index);
+            exceeded |= windows.increment(key, index, limit);
+        }
+
+        if exceeded {
+            return Err(FilterStatKey::Abuse);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use relay_event_schema::protocol::User;
+    use relay_protocol::Annotated;
+
+    use super::*;
+
+    fn project_id() -> ProjectId {
+        ProjectId::new(42)
+    }
+
+    fn grouped_event(grouping_hash: &str) -> Event {
+        Event {
+            grouping_hash: Annotated::new(grouping_hash.to_owned()),
+            ..Default::default()
+        }
+    }
+
+    fn check(
+        filter: &AbuseFilter,
+        event: &Event,
+        client_ip: Option<IpAddr>,
+        config: &AbuseFilterConfig,
+        secs: u64,
+    ) -> Result<(), FilterStatKey> {
+        filter.should_filter(
+            project_id(),
+            event,
+            client_ip,
+            config,
+            UnixTimestamp::from_secs(secs),
+        )
+    }
+
+    #[test]
+    fn test_disabled() {
+        let filter = AbuseFilter::new();
+        let config = AbuseFilterConfig::default();
+        let client_ip = Some("127.0.0.1".parse().unwrap());
+
+        for _ in 0..100 {
+            assert_eq!(
+                check(&filter, &Event::default(), client_ip, &config, 0),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn test_client_ip_limit() {
+        let filter = AbuseFilter::new();
+        let config = AbuseFilterConfig {
+            client_ip_limit: Some(2),
+            ..Default::default()
+        };
+        let client_ip = Some("127.0.0.1".parse().unwrap());
+        let other_ip = Some("10.0.0.1".parse().unwrap());
+        let event = Event::default();
+
+        assert_eq!(check(&filter, &event, client_ip, &config, 0), Ok(()));
+        assert_eq!(check(&filter, &event, client_ip, &config, 10), Ok(()));
+        assert_eq!(
+            check(&filter, &event, client_ip, &config, 20),
+            Err(FilterStatKey::Abuse)
+        );
+
+        // Other clients are not affected.
+        assert_eq!(check(&filter, &event, other_ip, &config, 20), Ok(()));
+
+        // The next window starts over.
+        assert_eq!(check(&filter, &event, client_ip, &config, 60), Ok(()));
+    }
+
+    #[test]
+    fn test_user_limit() {
+        let filter = AbuseFilter::new();
+        let config = AbuseFilterConfig {
+            user_limit: Some(1),
+            ..Default::default()
+        };
+
+        let event = Event {
+            user: Annotated::new(User {
+                id: Annotated::new("user-1".to_string().into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(check(&filter, &event, None, &config, 0), Ok(()));
+        assert_eq!(
+            check(&filter, &event, None, &config, 0),
+            Err(FilterStatKey::Abuse)
+        );
+
+        // Events without a user are not counted.
+        assert_eq!(check(&filter, &Event::default(), None, &config, 0), Ok(()));
+    }
+
+    #[test]
+    fn test_fingerprint_limit() {
+        let filter = AbuseFilter::new();
+        let config = AbuseFilterConfig {
+            fingerprint_limit: Some(1),
+            ..Default::default()
+        };
+
+        let event = grouped_event("6cd9d1c3b8e0c7b5f8b1b6a5f0e3d2c1");
+        assert_eq!(check(&filter, &event, None, &config, 0), Ok(()));
+        assert_eq!(
+            check(&filter, &event, None, &config, 0),
+            Err(FilterStatKey::Abuse)
+        );
+
+        let other = grouped_event("0f4a8e1b2c3d4e5f60718293a4b5c6d7");
+        assert_eq!(check(&filter, &other, None, &config, 0), Ok(()));
+
+        // Events without a grouping hash are not counted.
+        assert_eq!(check(&filter, &Event::default(), None, &config, 0), Ok(()));
+        assert_eq!(check(&filter, &Event::default(), None, &config, 0), Ok(()));
+    }
+
+    #[test]
+    fn test_evict() {
+        let mut windows = Windows::default();
+
+        for i in 0..10 {
+            let key = AbuseKey::ClientIp(project_id(), IpAddr::from([10, 0, 0, i]));
+            assert!(!windows.increment(key, 0, 1));
+        }
+
+        windows.evict(2);
+        let key = AbuseKey::ClientIp(project_id(), "127.0.0.1".parse().unwrap());
+        assert!(!windows.increment(key, 2, 1));
+        assert_eq!(windows.counts.len(), 1);
+    }
+
+    #[test]
+    fn test_max_keys() {
+        let mut windows = Windows::default();
+
+        for i in 0..MAX_KEYS_PER_SHARD {
+            let key = AbuseKey::Fingerprint(project_id(), i.to_string());
+            assert!(!windows.increment(key, 0, 1));
+        }
+
+        // New sources are not counted once the shard is full.
+        let key = AbuseKey::Fingerprint(project_id(), "new".to_owned());
+        assert!(!windows.increment(key.clone(), 0, 1));
+        assert!(!windows.increment(key, 0, 1));
+        assert_eq!(windows.counts.len(), MAX_KEYS_PER_SHARD);
+
+        // Known sources are still counted.
+        let key = AbuseKey::Fingerprint(project_id(), "0".to_owned());
+        assert!(windows.increment(key, 0, 1));
+    }
+}
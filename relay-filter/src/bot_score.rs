@@ -0,0 +1,466 @@
+//! Scores events by the likelihood that they were sent by bots or synthetic traffic.
+//!
+//! Unlike the [web crawlers](crate::web_crawlers) filter, which only matches a list of known
+//! crawlers, the bot score combines several weak signals into a score between `0` and `100`:
+//!
+//!  - The user agent of the event or request, including crawlers detected by `relay-ua`.
+//!  - Markers of headless browsers, automation frameworks, and HTTP libraries in the user agent.
+//!  - Headers that real browsers always send but that are missing from the request.
+//!  - Whether the user's IP address belongs to a hosting provider or an anonymizing service.
+//!
+//! The score is written to the `bot_score` tag of the event, from where the filter reads it.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use relay_event_schema::protocol::{Event, Geo, TagEntry, Tags};
+use relay_protocol::Annotated;
+
+use crate::{web_crawlers, BotScoreFilterConfig, FilterStatKey};
+
+/// The name of the event tag that holds the bot score.
+pub const BOT_SCORE_TAG: &str = "bot_score";
+
+/// The highest possible bot score.
+pub const MAX_BOT_SCORE: u8 = 100;
+
+/// Score for user agents of headless browsers and browser automation frameworks.
+const HEADLESS_SCORE: u8 = 60;
+
+/// Score for user agents of command line tools and HTTP client libraries.
+const HTTP_LIBRARY_SCORE: u8 = 50;
+
+/// Score for events without any user agent.
+const MISSING_USER_AGENT_SCORE: u8 = 30;
+
+/// Score for user agents that claim to be a browser but cannot be attributed to any known one.
+const UNKNOWN_USER_AGENT_SCORE: u8 = 20;
+
+/// Score for Chromium-based browsers that do not send client hints.
+const MISSING_CLIENT_HINTS_SCORE: u8 = 20;
+
+/// Score for browsers that do not send an `Origin` header.
+const MISSING_ORIGIN_SCORE: u8 = 15;
+
+/// Score for IP addresses of hosting providers and data centers.
+const HOSTING_PROVIDER_SCORE: u8 = 30;
+
+/// Score for IP addresses of VPNs, public proxies, and Tor exit nodes.
+const ANONYMOUS_IP_SCORE: u8 = 15;
+
+/// The first version of Chromium that sends `Sec-CH-UA` client hints by default.
+const MIN_CLIENT_HINTS_CHROMIUM: u32 = 89;
+
+static HEADLESS_BROWSERS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?ix)
+        HeadlessChrome|
+        PhantomJS|
+        SlimerJS|
+        Puppeteer|
+        Playwright|
+        Selenium|
+        WebDriver|
+        Cypress|
+        Nightmare|
+        jsdom
+    ",
+    )
+    .expect("Invalid headless browsers Regex")
+});
+
+static HTTP_LIBRARIES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?ix)
+        ^(
+            curl|
+            Wget|
+            python-requests|
+            python-urllib|
+            python-httpx|
+            aiohttp|
+            Go-http-client|
+            Java/|
+            Apache-HttpClient|
+            libwww-perl|
+            node-fetch|
+            axios|
+            undici|
+            PostmanRuntime|
+            insomnia|
+            HTTPie|
+            Scrapy
+        )
+    ",
+    )
+    .expect("Invalid HTTP libraries Regex")
+});
+
+/// Signals about the request that submitted an event.
+///
+/// These are not part of the event payload and must be supplied by the caller.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestSignals<'a> {
+    /// The `User-Agent` header of the request.
+    pub user_agent: Option<&'a str>,
+    /// Whether the request contained `Sec-CH-UA` client hints.
+    pub has_client_hints: bool,
+    /// Whether the request contained an `Origin` or `Referer` header.
+    pub has_origin: bool,
+    /// Geographical information on the IP address of the user.
+    ///
+    /// If this is `None`, the geo information of the event's user is used instead.
+    pub geo: Option<&'a Geo>,
+}
+
+/// Returns the major version of Chromium for Chromium-based browsers.
+fn chromium_version(user_agent: &str) -> Option<u32> {
+    let parsed = relay_ua::parse_user_agent(user_agent);
+    match parsed.family.as_ref() {
+        "Chrome" | "Chrome Mobile" | "Edge" | "Opera" | "Samsung Internet" => {}
+        _ => return None,
+    }
+
+    // Some Chromium-based browsers report their own version, so read the engine's version.
+    let (_, rest) = user_agent.split_once("Chrome/")?;
+    let major = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+    major.parse().ok()
+}
+
+/// Returns `true` if the user agent string belongs to a regular web browser.
+fn is_browser(user_agent: &str) -> bool {
+    user_agent.starts_with("Mozilla/") && relay_ua::parse_user_agent(user_agent).family != "Other"
+}
+
+/// Returns `true` if the user agent string belongs to a Sentry SDK, such as `sentry.python/1.39.1`.
+fn is_sdk(user_agent: &str) -> bool {
+    user_agent.starts_with("sentry.") || user_agent.starts_with("sentry-")
+}
+
+/// Scores the user agent of the end user.
+///
+/// User agents of Sentry SDKs and other non-browser clients, such as native apps, do not describe
+/// the end user and are only scored if they match a known bot or automation tool.
+fn score_user_agent(user_agent: Option<&str>) -> u8 {
+    let Some(user_agent) = user_agent.filter(|ua| !ua.trim().is_empty()) else {
+        return MISSING_USER_AGENT_SCORE;
+    };
+
+    if is_sdk(user_agent) {
+        return 0;
+    }
+
+    if web_crawlers::matches_user_agent(user_agent) {
+        return MAX_BOT_SCORE;
+    }
+
+    if relay_ua::parse_device(user_agent).family == "Spider" {
+        return MAX_BOT_SCORE;
+    }
+
+    if HEADLESS_BROWSERS.is_match(user_agent) {
+        return HEADLESS_SCORE;
+    }
+
+    if HTTP_LIBRARIES.is_match(user_agent) {
+        return HTTP_LIBRARY_SCORE;
+    }
+
+    let claims_browser = user_agent.starts_with("Mozilla/");
+    if claims_browser && relay_ua::parse_user_agent(user_agent).family == "Other" {
+        return UNKNOWN_USER_AGENT_SCORE;
+    }
+
+    0
+}
+
+/// Scores the headers of requests sent directly by browsers.
+///
+/// Requests from server-side SDKs do not carry a browser user agent and are not scored.
+fn score_headers(request: &RequestSignals<'_>) -> u8 {
+    let Some(user_agent) = request.user_agent.filter(|ua| is_browser(ua)) else {
+        return 0;
+    };
+
+    let mut score = 0;
+
+    let needs_client_hints =
+        chromium_version(user_agent).map_or(false, |v| v >= MIN_CLIENT_HINTS_CHROMIUM);
+    if needs_client_hints && !request.has_client_hints {
+        score += MISSING_CLIENT_HINTS_SCORE;
+    }
+
+    if !request.has_origin {
+        score += MISSING_ORIGIN_SCORE;
+    }
+
+    score
+}
+
+/// Scores the network of the user's IP address.
+fn score_geo(geo: Option<&Geo>) -> u8 {
+    let Some(geo) = geo else {
+        return 0;
+    };
+
+    let mut score = 0;
+
+    if geo.is_hosting_provider.value() == Some(&true) {
+        score += HOSTING_PROVIDER_SCORE;
+    }
+
+    if geo.is_anonymous.value() == Some(&true) {
+        score += ANONYMOUS_IP_SCORE;
+    }
+
+    score
+}
+
+/// Computes the likelihood that the event was sent by a bot.
+///
+/// Returns a score between `0` (no signals of automated traffic) and [`MAX_BOT_SCORE`]. The user
+/// agent of the event's request takes precedence over the user agent of the request that
+/// submitted the event.
+pub fn score(event: &Event, request: &RequestSignals<'_>) -> u8 {
+    let user_agent = event.user_agent().or(request.user_agent);
+
+    let event_geo = event.user.value().and_then(|user| user.geo.value());
+    let geo = request.geo.or(event_geo);
+
+    let score = score_user_agent(user_agent)
+        .saturating_add(score_headers(request))
+        .saturating_add(score_geo(geo));
+
+    score.min(MAX_BOT_SCORE)
+}
+
+/// Returns the bot score stored in the tags of the event.
+pub fn get_score(event: &Event) -> Option<u8> {
+    event.tag_value(BOT_SCORE_TAG)?.parse().ok()
+}
+
+/// Writes the bot score to the tags of the event, replacing a previous score.
+pub fn set_score(event: &mut Event, score: u8) {
+    let tags = event.tags.value_mut().get_or_insert_with(Tags::default);
+    tags.retain(|entry| {
+        let key = entry.value().and_then(|entry| entry.0.as_str());
+        key != Some(BOT_SCORE_TAG)
+    });
+
+    tags.push(Annotated::new(TagEntry(
+        Annotated::new(BOT_SCORE_TAG.to_string()),
+        Annotated::new(score.to_string()),
+    )));
+}
+
+/// Filters events with a bot score at or above the configured threshold.
+///
+/// The score must have been computed and written to the event's tags before.
+pub fn should_filter(event: &Event, config: &BotScoreFilterConfig) -> Result<(), FilterStatKey> {
+    let Some(threshold) = config.threshold else {
+        return Ok(());
+    };
+
+    match get_score(event) {
+        Some(score) if score >= threshold => Err(FilterStatKey::BotScore),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use relay_event_schema::protocol::User;
+
+    use super::*;
+    use crate::testutils;
+
+    const CHROME: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 \
+        (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+    const HEADLESS_CHROME: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 \
+        (KHTML, like Gecko) HeadlessChrome/120.0.0.0 Safari/537.36";
+
+    fn browser_request(user_agent: &str) -> RequestSignals<'_> {
+        RequestSignals {
+            user_agent: Some(user_agent),
+            has_client_hints: true,
+            has_origin: true,
+            geo: None,
+        }
+    }
+
+    fn event_with_score(score: &str) -> Event {
+        Event {
+            tags: Annotated::new(Tags(
+                vec![Annotated::new(TagEntry(
+                    Annotated::new(BOT_SCORE_TAG.to_string()),
+                    Annotated::new(score.to_string()),
+                ))]
+                .into(),
+            )),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_score_browser() {
+        let event = testutils::get_event_with_user_agent(CHROME);
+        assert_eq!(score(&event, &browser_request(CHROME)), 0);
+    }
+
+    #[test]
+    fn test_score_crawler() {
+        let user_agent = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+        let event = testutils::get_event_with_user_agent(user_agent);
+        assert_eq!(score(&event, &RequestSignals::default()), MAX_BOT_SCORE);
+    }
+
+    #[test]
+    fn test_score_headless() {
+        let event = testutils::get_event_with_user_agent(HEADLESS_CHROME);
+        assert_eq!(score(&event, &RequestSignals::default()), HEADLESS_SCORE);
+    }
+
+    #[test]
+    fn test_score_http_library() {
+        let event = testutils::get_event_with_user_agent("python-requests/2.31.0");
+        assert_eq!(
+            score(&event, &RequestSignals::default()),
+            HTTP_LIBRARY_SCORE
+        );
+    }
+
+    #[test]
+    fn test_score_missing_user_agent() {
+        assert_eq!(
+            score(&Event::default(), &RequestSignals::default()),
+            MISSING_USER_AGENT_SCORE
+        );
+    }
+
+    #[test]
+    fn test_score_missing_headers() {
+        let request = RequestSignals {
+            has_client_hints: false,
+            has_origin: false,
+            ..browser_request(CHROME)
+        };
+
+        assert_eq!(
+            score(&Event::default(), &request),
+            MISSING_CLIENT_HINTS_SCORE + MISSING_ORIGIN_SCORE
+        );
+    }
+
+    #[test]
+    fn test_score_server_request() {
+        // Server-side SDKs do not send browser headers.
+        let request = RequestSignals {
+            user_agent: Some("sentry.python/1.39.1"),
+            ..Default::default()
+        };
+
+        let event = testutils::get_event_with_user_agent(CHROME);
+        assert_eq!(score(&event, &request), 0);
+    }
+
+    #[test]
+    fn test_score_sdk_request() {
+        // Events of server-side SDKs often do not contain a user agent.
+        let request = RequestSignals {
+            user_agent: Some("sentry.python/1.39.1"),
+            ..Default::default()
+        };
+
+        assert_eq!(score(&Event::default(), &request), 0);
+    }
+
+    #[test]
+    fn test_score_native_client() {
+        let user_agent = "MyApp/1.0 CFNetwork/1474 Darwin/23.0.0";
+        let event = testutils::get_event_with_user_agent(user_agent);
+        assert_eq!(score(&event, &RequestSignals::default()), 0);
+    }
+
+    #[test]
+    fn test_score_unknown_browser() {
+        let event = testutils::get_event_with_user_agent("Mozilla/5.0");
+        assert_eq!(
+            score(&event, &RequestSignals::default()),
+            UNKNOWN_USER_AGENT_SCORE
+        );
+    }
+
+    #[test]
+    fn test_score_geo() {
+        let mut event = testutils::get_event_with_user_agent(CHROME);
+        event.user = Annotated::new(User {
+            geo: Annotated::new(Geo {
+                is_hosting_provider: Annotated::new(true),
+                is_anonymous: Annotated::new(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            score(&event, &browser_request(CHROME)),
+            HOSTING_PROVIDER_SCORE + ANONYMOUS_IP_SCORE
+        );
+    }
+
+    #[test]
+    fn test_score_capped() {
+        let geo = Geo {
+            is_hosting_provider: Annotated::new(true),
+            ..Default::default()
+        };
+
+        let request = RequestSignals {
+            user_agent: Some(HEADLESS_CHROME),
+            has_client_hints: false,
+            has_origin: false,
+            geo: Some(&geo),
+        };
+
+        let event = testutils::get_event_with_user_agent(HEADLESS_CHROME);
+        assert_eq!(score(&event, &request), MAX_BOT_SCORE);
+    }
+
+    #[test]
+    fn test_set_score() {
+        let mut event = event_with_score("100");
+        set_score(&mut event, 35);
+
+        assert_eq!(get_score(&event), Some(35));
+        assert_eq!(event.tags.value().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_filter_threshold() {
+        let config = BotScoreFilterConfig {
+            is_enabled: true,
+            threshold: Some(50),
+        };
+
+        assert_eq!(
+            should_filter(&event_with_score("60"), &config),
+            Err(FilterStatKey::BotScore)
+        );
+        assert_eq!(
+            should_filter(&event_with_score("50"), &config),
+            Err(FilterStatKey::BotScore)
+        );
+        assert_eq!(should_filter(&event_with_score("49"), &config), Ok(()));
+        assert_eq!(should_filter(&Event::default(), &config), Ok(()));
+    }
+
+    #[test]
+    fn test_filter_without_threshold() {
+        let config = BotScoreFilterConfig {
+            is_enabled: true,
+            threshold: None,
+        };
+
+        assert_eq!(should_filter(&event_with_score("100"), &config), Ok(()));
+    }
+}
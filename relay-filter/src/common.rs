@@ -37,6 +37,12 @@ pub enum FilterStatKey {
     /// Filtered due to the fact that it was a call to a filtered transaction
     FilteredTransactions,
 
+    /// Filtered because the client, user, or fingerprint exceeded a rate limit.
+    Abuse,
+
+    /// Filtered because the bot score exceeded the configured threshold.
+    BotScore,
+
     /// Filtered due to a generic filter.
     GenericFilter(String),
 }
@@ -66,6 +72,8 @@ impl FilterStatKey {
             FilterStatKey::WebCrawlers => "web-crawlers",
             FilterStatKey::InvalidCsp => "invalid-csp",
             FilterStatKey::FilteredTransactions => "filtered-transaction",
+            FilterStatKey::Abuse => "abuse",
+            FilterStatKey::BotScore => "bot-score",
             FilterStatKey::GenericFilter(filter_identifier) => {
                 return Cow::Owned(filter_identifier);
             }
@@ -93,6 +101,8 @@ impl<'a> TryFrom<&'a str> for FilterStatKey {
             "web-crawlers" => FilterStatKey::WebCrawlers,
             "invalid-csp" => FilterStatKey::InvalidCsp,
             "filtered-transaction" => FilterStatKey::FilteredTransactions,
+            "abuse" => FilterStatKey::Abuse,
+            "bot-score" => FilterStatKey::BotScore,
             other => FilterStatKey::GenericFilter(other.to_string()),
         })
     }
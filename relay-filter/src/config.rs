@@ -229,6 +229,58 @@ impl LegacyBrowsersFilterConfig {
     }
 }
 
+/// Configuration for the abuse filter.
+///
+/// Limits are numbers of events per minute within a project. If a limit is not set, events are
+/// not counted by the respective source.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AbuseFilterConfig {
+    /// Maximum number of events per minute from a single client IP address.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ip_limit: Option<u32>,
+    /// Maximum number of events per minute with the same user ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_limit: Option<u32>,
+    /// Maximum number of events per minute with the same fingerprint.
+    ///
+    /// This is the grouping hash computed by Relay, which is only available for error events of
+    /// projects with a supported grouping config.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint_limit: Option<u32>,
+}
+
+impl AbuseFilterConfig {
+    /// Returns true if no configuration for this filter is given.
+    pub fn is_empty(&self) -> bool {
+        self.client_ip_limit.is_none()
+            && self.user_limit.is_none()
+            && self.fingerprint_limit.is_none()
+    }
+}
+
+/// Configuration for the bot score filter.
+///
+/// See [`bot_score`](crate::bot_score) for how the score is computed.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BotScoreFilterConfig {
+    /// Specifies whether events are scored and tagged with their bot score.
+    pub is_enabled: bool,
+    /// Filters events with a bot score at or above this value.
+    ///
+    /// Setting a threshold also enables scoring.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u8>,
+}
+
+impl BotScoreFilterConfig {
+    /// Returns true if no configuration for this filter is given.
+    pub fn is_empty(&self) -> bool {
+        !self.is_enabled && self.threshold.is_none()
+    }
+}
+
 /// Configuration for a generic filter.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -307,6 +359,14 @@ pub struct FiltersConfig {
     )]
     pub ignore_transactions: IgnoreTransactionsFilterConfig,
 
+    /// Configuration for the abuse filter.
+    #[serde(default, skip_serializing_if = "AbuseFilterConfig::is_empty")]
+    pub abuse: AbuseFilterConfig,
+
+    /// Configuration for the bot score filter.
+    #[serde(default, skip_serializing_if = "BotScoreFilterConfig::is_empty")]
+    pub bot_score: BotScoreFilterConfig,
+
     /// Configuration for generic filters.
     #[serde(default, skip_serializing_if = "GenericFiltersConfig::is_empty")]
     pub(crate) generic: GenericFiltersConfig,
@@ -324,8 +384,17 @@ impl FiltersConfig {
             && self.localhost.is_empty()
             && self.releases.is_empty()
             && self.ignore_transactions.is_empty()
+            && self.abuse.is_empty()
+            && self.bot_score.is_empty()
             && self.generic.is_empty()
     }
+
+    /// Returns true if there are generic filters that apply to items other than events.
+    ///
+    /// See [`should_filter_item`](crate::should_filter_item).
+    pub fn has_generic_filters(&self) -> bool {
+        !self.generic.is_empty()
+    }
 }
 
 #[cfg(test)]
@@ -366,6 +435,15 @@ mod tests {
                 patterns: [],
                 is_enabled: false,
             },
+            abuse: AbuseFilterConfig {
+                client_ip_limit: None,
+                user_limit: None,
+                fingerprint_limit: None,
+            },
+            bot_score: BotScoreFilterConfig {
+                is_enabled: false,
+                threshold: None,
+            },
             generic: GenericFiltersConfig {
                 version: 0,
                 filters: [],
@@ -410,6 +488,15 @@ mod tests {
                 patterns: GlobPatterns::new(vec!["*health*".to_string()]),
                 is_enabled: true,
             },
+            abuse: AbuseFilterConfig {
+                client_ip_limit: Some(100),
+                user_limit: None,
+                fingerprint_limit: Some(1000),
+            },
+            bot_score: BotScoreFilterConfig {
+                is_enabled: true,
+                threshold: Some(80),
+            },
             generic: GenericFiltersConfig {
                 version: 1,
                 filters: vec![GenericFilterConfig {
@@ -464,6 +551,14 @@ mod tests {
             ],
             "isEnabled": true
           },
+          "abuse": {
+            "clientIpLimit": 100,
+            "fingerprintLimit": 1000
+          },
+          "botScore": {
+            "isEnabled": true,
+            "threshold": 80
+          },
           "generic": {
             "version": 1,
             "filters": [
@@ -3,10 +3,13 @@
 //! Multiple generic filters can be defined and they are going to be checked in FIFO order. The
 //! first one that matches, will result in the event being discarded with a [`FilterStatKey`]
 //! identifying the matching filter.
+//!
+//! Besides events, generic filters also apply to standalone spans, replays, monitor check-ins, and
+//! sessions. Conditions select the type of item through the root component of their field paths,
+//! for example `span.op` or `replay.request.url`.
 
 use crate::{FilterStatKey, GenericFiltersConfig};
-use relay_event_schema::protocol::Event;
-use relay_protocol::RuleCondition;
+use relay_protocol::{Getter, RuleCondition};
 
 /// Maximum supported version of the generic filters schema.
 ///
@@ -17,18 +20,34 @@ fn is_enabled(config: &GenericFiltersConfig) -> bool {
     config.version > 0 && config.version <= VERSION
 }
 
-/// Checks events by patterns in their error messages.
-fn matches(event: &Event, condition: Option<&RuleCondition>) -> bool {
-    // TODO: the condition DSL needs to be extended to support more complex semantics, such as
-    //  collections operations.
-    condition.map_or(false, |condition| condition.matches(event))
+/// Checks items by the condition of a generic filter.
+fn matches<T>(item: &T, condition: Option<&RuleCondition>) -> bool
+where
+    T: Getter + ?Sized,
+{
+    condition.map_or(false, |condition| condition.matches(item))
+}
+
+/// Filters events and other items by the conditions of generic filters.
+pub(crate) fn should_filter<T>(item: &T, config: &GenericFiltersConfig) -> Result<(), FilterStatKey>
+where
+    T: Getter + ?Sized,
+{
+    should_filter_where(item, config, |_| true)
 }
 
-/// Filters events by patterns in their error messages.
-pub(crate) fn should_filter(
-    event: &Event,
+/// Filters events and other items by the conditions of selected generic filters.
+///
+/// Only filters with a condition for which `select` returns `true` are applied.
+pub(crate) fn should_filter_where<T, F>(
+    item: &T,
     config: &GenericFiltersConfig,
-) -> Result<(), FilterStatKey> {
+    select: F,
+) -> Result<(), FilterStatKey>
+where
+    T: Getter + ?Sized,
+    F: Fn(&RuleCondition) -> bool,
+{
     // We check if the configuration is enabled, since we support only configuration with a version
     // <= than the maximum one in this Relay instance.
     if !is_enabled(config) {
@@ -36,7 +55,11 @@ pub(crate) fn should_filter(
     }
 
     for filter_config in config.filters.iter() {
-        if !filter_config.is_empty() && matches(event, filter_config.condition.as_ref()) {
+        let Some(condition) = filter_config.condition.as_ref().filter(|c| select(c)) else {
+            continue;
+        };
+
+        if !filter_config.is_empty() && matches(item, Some(condition)) {
             return Err(FilterStatKey::GenericFilter(filter_config.id.clone()));
         }
     }
@@ -48,7 +71,10 @@ pub(crate) fn should_filter(
 mod tests {
     use crate::generic::{should_filter, VERSION};
     use crate::{FilterStatKey, GenericFilterConfig, GenericFiltersConfig};
-    use relay_event_schema::protocol::{Event, LenientString};
+    use relay_event_schema::protocol::{
+        Event, Exception, Geo, LenientString, Replay, SessionAggregates, SessionAttributes, Span,
+        User, Values,
+    };
     use relay_protocol::Annotated;
     use relay_protocol::RuleCondition;
 
@@ -146,4 +172,190 @@ mod tests {
         };
         assert_eq!(should_filter(&event, &config), Ok(()));
     }
+
+    #[test]
+    fn test_should_filter_hosting_providers() {
+        let config = GenericFiltersConfig {
+            version: 1,
+            filters: vec![GenericFilterConfig {
+                id: "dataCenters".to_string(),
+                is_enabled: true,
+                condition: Some(
+                    RuleCondition::eq("event.user.geo.is_hosting_provider", true)
+                        | RuleCondition::eq("event.user.geo.is_anonymous", true),
+                ),
+            }],
+        };
+
+        let event_with_geo = |is_anonymous, is_hosting_provider| Event {
+            user: Annotated::new(User {
+                geo: Annotated::new(Geo {
+                    asn: Annotated::new(16509),
+                    is_anonymous: Annotated::new(is_anonymous),
+                    is_hosting_provider: Annotated::new(is_hosting_provider),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            should_filter(&event_with_geo(false, true), &config),
+            Err(FilterStatKey::GenericFilter("dataCenters".to_string()))
+        );
+        assert_eq!(
+            should_filter(&event_with_geo(true, false), &config),
+            Err(FilterStatKey::GenericFilter("dataCenters".to_string()))
+        );
+        assert_eq!(
+            should_filter(&event_with_geo(false, false), &config),
+            Ok(())
+        );
+        assert_eq!(should_filter(&Event::default(), &config), Ok(()));
+    }
+
+    #[test]
+    fn test_should_filter_exceptions() {
+        let config = GenericFiltersConfig {
+            version: 1,
+            filters: vec![GenericFilterConfig {
+                id: "chunkLoadErrors".to_string(),
+                is_enabled: true,
+                condition: Some(RuleCondition::any(
+                    "event.exception.values",
+                    RuleCondition::glob("type", "ChunkLoadError")
+                        & RuleCondition::regex("value", r"Loading chunk \d+ failed.*"),
+                )),
+            }],
+        };
+
+        let event_with_exceptions = |exceptions: &[(&str, &str)]| Event {
+            exceptions: Annotated::new(Values::new(
+                exceptions
+                    .iter()
+                    .map(|(ty, value)| {
+                        Annotated::new(Exception {
+                            ty: Annotated::new(ty.to_string()),
+                            value: Annotated::new(value.to_string().into()),
+                            ..Default::default()
+                        })
+                    })
+                    .collect(),
+            )),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            should_filter(
+                &event_with_exceptions(&[
+                    ("TypeError", "x is undefined"),
+                    ("ChunkLoadError", "Loading chunk 42 failed. (timeout)"),
+                ]),
+                &config
+            ),
+            Err(FilterStatKey::GenericFilter("chunkLoadErrors".to_string()))
+        );
+        assert_eq!(
+            should_filter(
+                &event_with_exceptions(&[("ChunkLoadError", "Loading CSS chunk failed")]),
+                &config
+            ),
+            Ok(())
+        );
+        assert_eq!(should_filter(&Event::default(), &config), Ok(()));
+    }
+
+    #[test]
+    fn test_should_filter_items() {
+        let config = GenericFiltersConfig {
+            version: 1,
+            filters: vec![
+                GenericFilterConfig {
+                    id: "healthChecks".to_string(),
+                    is_enabled: true,
+                    condition: Some(RuleCondition::glob("span.description", "GET /health*")),
+                },
+                GenericFilterConfig {
+                    id: "botReplays".to_string(),
+                    is_enabled: true,
+                    condition: Some(RuleCondition::any(
+                        "replay.urls",
+                        RuleCondition::glob("", "*/robots.txt"),
+                    )),
+                },
+                GenericFilterConfig {
+                    id: "oldReleases".to_string(),
+                    is_enabled: true,
+                    condition: Some(RuleCondition::eq("session.release", "0.1")),
+                },
+            ],
+        };
+
+        let span = Span {
+            description: Annotated::new("GET /healthz".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            should_filter(&span, &config),
+            Err(FilterStatKey::GenericFilter("healthChecks".to_string()))
+        );
+
+        let replay = Replay {
+            urls: Annotated::new(vec![
+                Annotated::new("https://example.com/".to_string()),
+                Annotated::new("https://example.com/robots.txt".to_string()),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            should_filter(&replay, &config),
+            Err(FilterStatKey::GenericFilter("botReplays".to_string()))
+        );
+
+        let session = SessionAggregates {
+            aggregates: vec![],
+            attributes: SessionAttributes {
+                release: "0.1".to_string(),
+                environment: None,
+                ip_address: None,
+                user_agent: None,
+            },
+        };
+        assert_eq!(
+            should_filter(&session, &config),
+            Err(FilterStatKey::GenericFilter("oldReleases".to_string()))
+        );
+
+        // Conditions on other items do not apply.
+        assert_eq!(should_filter(&Span::default(), &config), Ok(()));
+        assert_eq!(should_filter(&Event::default(), &config), Ok(()));
+    }
+
+    #[test]
+    fn test_should_filter_grouping_hash() {
+        let config = crate::FiltersConfig {
+            generic: GenericFiltersConfig {
+                version: 1,
+                filters: vec![GenericFilterConfig {
+                    id: "noisyGroup".to_string(),
+                    is_enabled: true,
+                    condition: Some(RuleCondition::eq("event.grouping_hash", "abc")),
+                }],
+            },
+            ..Default::default()
+        };
+
+        let event = Event {
+            grouping_hash: Annotated::new("abc".to_string()),
+            ..Default::default()
+        };
+
+        // Conditions on the grouping hash only apply once it has been computed.
+        assert_eq!(crate::should_filter(&event, None, &config), Ok(()));
+        assert_eq!(
+            crate::should_filter_grouped(&event, &config),
+            Err(FilterStatKey::GenericFilter("noisyGroup".to_string()))
+        );
+    }
 }
@@ -6,6 +6,8 @@
 //! * browser extensions (filter events caused by known problematic browser extensions)
 //! * web crawlers (filter events sent by user agents known to be web crawlers)
 //! * legacy browsers (filter events originating from legacy browsers, can be configured)
+//! * abuse (filter events from clients, users, or fingerprints that exceed a rate limit)
+//! * bot score (filter events that are likely sent by bots or synthetic traffic)
 #![warn(missing_docs)]
 #![doc(
     html_logo_url = "https://raw.githubusercontent.com/getsentry/relay/master/artwork/relay-icon.png",
@@ -15,7 +17,10 @@
 use std::net::IpAddr;
 
 use relay_event_schema::protocol::Event;
+use relay_protocol::Getter;
 
+pub mod abuse;
+pub mod bot_score;
 pub mod browser_extensions;
 pub mod client_ips;
 pub mod csp;
@@ -37,10 +42,19 @@ pub use crate::common::*;
 pub use crate::config::*;
 pub use crate::csp::matches_any_origin;
 
+/// The field path of the grouping hash that Relay computes for events.
+///
+/// Generic filters with conditions on this field are applied by [`should_filter_grouped`].
+pub const GROUPING_HASH_FIELD: &str = "event.grouping_hash";
+
 /// Checks whether an event should be filtered for a particular configuration.
 ///
 /// If the event should be filtered, the `Err` returned contains a filter reason.
 /// The reason is the message returned by the first filter that didn't pass.
+///
+/// Generic filters with conditions on the [grouping hash](GROUPING_HASH_FIELD) are skipped, since
+/// the hash is computed only after stack traces have been resolved. Use [`should_filter_grouped`]
+/// to apply them.
 pub fn should_filter(
     event: &Event,
     client_ip: Option<IpAddr>,
@@ -49,7 +63,9 @@ pub fn should_filter(
     // In order to maintain backwards compatibility, we still want to run the old matching logic,
     // but we will try to match generic filters first, since the goal is to eventually fade out the
     // the normal filters except for the ones that have complex conditions.
-    generic::should_filter(event, &config.generic)?;
+    generic::should_filter_where(event, &config.generic, |condition| {
+        !condition.references(GROUPING_HASH_FIELD)
+    })?;
 
     // The order of applying filters should not matter as they are additive. Still, be careful
     // when making changes to this order.
@@ -61,7 +77,31 @@ pub fn should_filter(
     browser_extensions::should_filter(event, &config.browser_extensions)?;
     legacy_browsers::should_filter(event, &config.legacy_browsers)?;
     web_crawlers::should_filter(event, &config.web_crawlers)?;
+    bot_score::should_filter(event, &config.bot_score)?;
     transaction_name::should_filter(event, &config.ignore_transactions)?;
 
     Ok(())
 }
+
+/// Checks whether an event should be filtered by generic filters on its grouping hash.
+///
+/// This complements [`should_filter`] and must run after the grouping hash has been computed.
+pub fn should_filter_grouped(event: &Event, config: &FiltersConfig) -> Result<(), FilterStatKey> {
+    generic::should_filter_where(event, &config.generic, |condition| {
+        condition.references(GROUPING_HASH_FIELD)
+    })
+}
+
+/// Checks whether an item other than an event should be filtered for a particular configuration.
+///
+/// Only generic filters apply to items such as standalone spans, replays, monitor check-ins, and
+/// sessions. Their conditions must use paths starting with the root component of the item, which
+/// is `span`, `replay`, `check_in`, or `session`, respectively.
+///
+/// If the item should be filtered, the `Err` returned contains the filter reason.
+pub fn should_filter_item<T>(item: &T, config: &FiltersConfig) -> Result<(), FilterStatKey>
+where
+    T: Getter + ?Sized,
+{
+    generic::should_filter(item, &config.generic)
+}
@@ -47,11 +47,12 @@ static ALLOWED_WEB_CRAWLERS: Lazy<Regex> = Lazy::new(|| {
 
 /// Checks if the event originates from a known web crawler.
 pub fn matches(event: &Event) -> bool {
-    if let Some(user_agent) = event.user_agent() {
-        WEB_CRAWLERS.is_match(user_agent) && !ALLOWED_WEB_CRAWLERS.is_match(user_agent)
-    } else {
-        false
-    }
+    event.user_agent().map_or(false, matches_user_agent)
+}
+
+/// Checks if the user agent string belongs to a known web crawler.
+pub fn matches_user_agent(user_agent: &str) -> bool {
+    WEB_CRAWLERS.is_match(user_agent) && !ALLOWED_WEB_CRAWLERS.is_match(user_agent)
 }
 
 /// Filters events originating from a known web crawler.
@@ -51,6 +51,8 @@ pub enum KafkaTopic {
     Monitors,
     /// Standalone spans without a transaction.
     Spans,
+    /// Reports of passive cardinality limits.
+    CardinalityReports,
 }
 
 impl KafkaTopic {
@@ -58,7 +60,7 @@ impl KafkaTopic {
     /// It will have to be adjusted if the new variants are added.
     pub fn iter() -> std::slice::Iter<'static, Self> {
         use KafkaTopic::*;
-        static TOPICS: [KafkaTopic; 13] = [
+        static TOPICS: [KafkaTopic; 14] = [
             Events,
             Attachments,
             Transactions,
@@ -72,6 +74,7 @@ impl KafkaTopic {
             ReplayRecordings,
             Monitors,
             Spans,
+            CardinalityReports,
         ];
         TOPICS.iter()
     }
@@ -111,6 +114,8 @@ pub struct TopicAssignments {
     pub monitors: TopicAssignment,
     /// Standalone spans without a transaction.
     pub spans: TopicAssignment,
+    /// Reports of passive cardinality limits.
+    pub cardinality_reports: TopicAssignment,
 }
 
 impl TopicAssignments {
@@ -131,6 +136,7 @@ impl TopicAssignments {
             KafkaTopic::ReplayRecordings => &self.replay_recordings,
             KafkaTopic::Monitors => &self.monitors,
             KafkaTopic::Spans => &self.spans,
+            KafkaTopic::CardinalityReports => &self.cardinality_reports,
         }
     }
 }
@@ -152,6 +158,7 @@ impl Default for TopicAssignments {
             replay_recordings: "ingest-replay-recordings".to_owned().into(),
             monitors: "ingest-monitors".to_owned().into(),
             spans: "ingest-spans".to_owned().into(),
+            cardinality_reports: "cardinality-reports".to_owned().into(),
         }
     }
 }
@@ -722,6 +722,14 @@ impl CardinalityItem for Bucket {
         Some(mri.namespace)
     }
 
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn tag_keys(&self) -> Vec<&str> {
+        self.tags.keys().map(String::as_str).collect()
+    }
+
     fn to_hash(&self) -> u32 {
         let mut hasher = FnvHasher::default();
         self.name.hash(&mut hasher);
@@ -18,6 +18,7 @@
 
 use once_cell::sync::OnceCell;
 use relay_base_schema::project::ProjectId;
+use relay_protocol::{Getter, Val};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -163,6 +164,31 @@ pub struct CheckIn {
     pub contexts: Option<CheckInContexts>,
 }
 
+impl CheckInStatus {
+    /// Returns the string representation of this status.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Error => "error",
+            Self::InProgress => "in_progress",
+            Self::Missed => "missed",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl Getter for CheckIn {
+    fn get_value(&self, path: &str) -> Option<Val<'_>> {
+        Some(match path.strip_prefix("check_in.")? {
+            "monitor_slug" => self.monitor_slug.as_str().into(),
+            "status" => self.status.as_str().into(),
+            "environment" => self.environment.as_deref()?.into(),
+            "duration" => self.duration?.into(),
+            _ => return None,
+        })
+    }
+}
+
 /// The result from calling process_check_in
 pub struct ProcessedCheckInResult {
     /// The routing key to be used for the check-in payload.
@@ -180,8 +206,17 @@ pub fn process_check_in(
     payload: &[u8],
     project_id: ProjectId,
 ) -> Result<ProcessedCheckInResult, ProcessCheckInError> {
-    let mut check_in = serde_json::from_slice::<CheckIn>(payload)?;
+    let check_in = serde_json::from_slice::<CheckIn>(payload)?;
+    normalize_check_in(check_in, project_id)
+}
 
+/// Normalizes a parsed monitor check-in.
+///
+/// This is equivalent to [`process_check_in`] for callers that have already parsed the payload.
+pub fn normalize_check_in(
+    mut check_in: CheckIn,
+    project_id: ProjectId,
+) -> Result<ProcessedCheckInResult, ProcessCheckInError> {
     // Missed status cannot be ingested, this is computed on the server.
     if check_in.status == CheckInStatus::Missed {
         check_in.status = CheckInStatus::Unknown;
@@ -378,4 +413,29 @@ mod tests {
             Err(ProcessCheckInError::InvalidEnvironment)
         ));
     }
+
+    #[test]
+    fn getter() {
+        let json = r#"{
+          "check_in_id": "a460c25ff2554577b920fcfacae4e5eb",
+          "monitor_slug": "healthcheck",
+          "status": "in_progress",
+          "duration": 21.0
+        }"#;
+
+        let check_in = serde_json::from_str::<CheckIn>(json).unwrap();
+        assert_eq!(
+            check_in.get_value("check_in.monitor_slug"),
+            Some(Val::String("healthcheck"))
+        );
+        assert_eq!(
+            check_in.get_value("check_in.status"),
+            Some(Val::String("in_progress"))
+        );
+        assert_eq!(
+            check_in.get_value("check_in.duration"),
+            Some(Val::F64(21.0))
+        );
+        assert_eq!(check_in.get_value("check_in.environment"), None);
+    }
 }
@@ -1,8 +1,13 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::BTreeSet;
 
+use relay_common::glob3::GlobPatterns;
+use relay_protocol::{IntoValue, Value};
+
 use crate::builtin::BUILTIN_RULES_MAP;
-use crate::{PiiConfig, PiiConfigError, Redaction, RuleSpec, RuleType, SelectorSpec};
+use crate::selector::{PredicateOperator, SelectorPathItem, SelectorPredicate};
+use crate::{PiiConfig, PiiConfigError, Redaction, RuleSpec, RuleType, SelectorSpec, Vault};
 
 /// A representation of `PiiConfig` that is more (CPU-)efficient for use in `PiiProcessor`.
 ///
@@ -11,6 +16,8 @@ use crate::{PiiConfig, PiiConfigError, Redaction, RuleSpec, RuleType, SelectorSp
 #[derive(Debug, Clone)]
 pub struct CompiledPiiConfig {
     pub(super) applications: Vec<(SelectorSpec, BTreeSet<RuleRef>)>,
+    /// All predicates used in selectors of the applications.
+    pub(super) predicates: Vec<CompiledPredicate>,
 }
 
 impl CompiledPiiConfig {
@@ -26,7 +33,15 @@ impl CompiledPiiConfig {
             applications.push((selector.clone(), rule_set));
         }
 
-        CompiledPiiConfig { applications }
+        let mut predicates = Vec::new();
+        for (selector, _) in &applications {
+            collect_predicates(selector, &mut predicates);
+        }
+
+        CompiledPiiConfig {
+            applications,
+            predicates,
+        }
     }
 
     /// Force compilation of all regex patterns in this config.
@@ -53,6 +68,23 @@ impl CompiledPiiConfig {
                 | RuleType::Pemkey
                 | RuleType::UrlAuth
                 | RuleType::UsSsn
+                | RuleType::Phone
+                | RuleType::Passport
+                | RuleType::UkNino
+                | RuleType::DeTaxId
+                | RuleType::InAadhaar
+                | RuleType::InPan
+                | RuleType::BrCpf
+                | RuleType::BrCnpj
+                | RuleType::Jwt
+                | RuleType::Bearer
+                | RuleType::AwsKey
+                | RuleType::GcpKey
+                | RuleType::GithubToken
+                | RuleType::GitlabToken
+                | RuleType::SlackToken
+                | RuleType::StripeKey
+                | RuleType::HighEntropy
                 | RuleType::Password
                 | RuleType::Multiple(_)
                 | RuleType::Alias(_)
@@ -63,14 +95,112 @@ impl CompiledPiiConfig {
     }
 }
 
+/// A [`SelectorPredicate`] with its pattern compiled.
+#[derive(Debug, Clone)]
+pub(super) struct CompiledPredicate {
+    /// The path item that the predicate applies to, without any predicates.
+    pub item: SelectorPathItem,
+    /// The predicate as it occurs in the selector.
+    pub predicate: SelectorPredicate,
+    /// The patterns of glob predicates.
+    pattern: Option<GlobPatterns>,
+}
+
+impl CompiledPredicate {
+    fn new(item: SelectorPathItem, predicate: SelectorPredicate) -> Self {
+        let pattern = match predicate.operator {
+            PredicateOperator::Glob => Some(GlobPatterns::new(vec![predicate.literal.clone()])),
+            PredicateOperator::Equal | PredicateOperator::NotEqual => None,
+        };
+
+        CompiledPredicate {
+            item,
+            predicate,
+            pattern,
+        }
+    }
+
+    /// Evaluates the predicate on the value of the path item.
+    ///
+    /// Strings, numbers, and booleans are compared by their string representation. Predicates on
+    /// other values and missing paths only hold for `!=`.
+    pub fn evaluate<T: IntoValue + Clone>(&self, value: &T) -> bool {
+        let value = value.clone().into_value();
+        let text = match lookup(&value, &self.predicate.path) {
+            Some(Value::String(string)) => Cow::Borrowed(string.as_str()),
+            Some(Value::Bool(boolean)) => Cow::Owned(boolean.to_string()),
+            Some(Value::I64(number)) => Cow::Owned(number.to_string()),
+            Some(Value::U64(number)) => Cow::Owned(number.to_string()),
+            Some(Value::F64(number)) => Cow::Owned(number.to_string()),
+            Some(Value::Array(_) | Value::Object(_)) | None => {
+                return self.predicate.operator == PredicateOperator::NotEqual;
+            }
+        };
+
+        match self.predicate.operator {
+            PredicateOperator::Equal => text == self.predicate.literal,
+            PredicateOperator::NotEqual => text != self.predicate.literal,
+            PredicateOperator::Glob => self
+                .pattern
+                .as_ref()
+                .map_or(false, |pattern| pattern.is_match(text.as_bytes())),
+        }
+    }
+}
+
+/// Returns the value at the path of a predicate, or `None` if the path does not exist.
+///
+/// Keys are compared case-insensitively.
+fn lookup<'v>(value: &'v Value, path: &[SelectorPathItem]) -> Option<&'v Value> {
+    let Some((item, rest)) = path.split_first() else {
+        return Some(value);
+    };
+
+    let child = match (item, value) {
+        (SelectorPathItem::Key(key), Value::Object(object)) => object
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, child)| child)?,
+        (SelectorPathItem::Index(index), Value::Array(array)) => array.get(*index)?,
+        _ => return None,
+    };
+
+    lookup(child.value()?, rest)
+}
+
+fn collect_predicates(selector: &SelectorSpec, predicates: &mut Vec<CompiledPredicate>) {
+    match selector {
+        SelectorSpec::And(selectors) | SelectorSpec::Or(selectors) => {
+            for selector in selectors {
+                collect_predicates(selector, predicates);
+            }
+        }
+        SelectorSpec::Not(selector) => collect_predicates(selector, predicates),
+        SelectorSpec::Path(items) => {
+            for mut item in items {
+                while let SelectorPathItem::Predicate(inner, predicate) = item {
+                    let base = inner.base();
+                    if !predicates
+                        .iter()
+                        .any(|p| &p.item == base && &p.predicate == predicate)
+                    {
+                        predicates.push(CompiledPredicate::new(base.clone(), predicate.clone()));
+                    }
+                    item = &**inner;
+                }
+            }
+        }
+    }
+}
+
 fn get_rule(config: &PiiConfig, id: &str) -> Option<RuleRef> {
-    if let Some(spec) = config.rules.get(id) {
-        Some(RuleRef::new(id.to_owned(), spec))
-    } else {
-        BUILTIN_RULES_MAP
-            .get(id)
-            .map(|spec| RuleRef::new(id.to_owned(), spec))
+    let spec = config.rules.get(id).or_else(|| BUILTIN_RULES_MAP.get(id))?;
+
+    let mut rule = RuleRef::new(id.to_owned(), spec, config.vars.hash_key.as_deref());
+    if rule.redaction == Redaction::Encrypt {
+        rule.vault = config.vars.vault_key.as_deref().map(Vault::new);
     }
+    Some(rule)
 }
 
 #[allow(clippy::mutable_key_type)]
@@ -127,27 +257,37 @@ pub(super) struct RuleRef {
     pub origin: String,
     pub ty: RuleType,
     pub redaction: Redaction,
+    /// The secret key for keyed redactions, taken from the config's `hashKey` variable.
+    pub hash_key: Option<String>,
+    /// The vault for encrypting values, created from the config's `vaultKey` variable.
+    pub vault: Option<Vault>,
 }
 
 impl RuleRef {
-    fn new(id: String, spec: &RuleSpec) -> Self {
+    fn new(id: String, spec: &RuleSpec, hash_key: Option<&str>) -> Self {
         RuleRef {
             origin: id.clone(),
             id,
             ty: spec.ty.clone(),
             redaction: spec.redaction.clone(),
+            hash_key: hash_key.map(str::to_owned),
+            vault: None,
         }
     }
 
     pub fn for_parent(self, parent: Self) -> Self {
+        let (redaction, vault) = match parent.redaction {
+            Redaction::Default => (self.redaction, self.vault),
+            _ => (parent.redaction, parent.vault),
+        };
+
         RuleRef {
             id: self.id,
             origin: parent.origin,
             ty: self.ty,
-            redaction: match parent.redaction {
-                Redaction::Default => self.redaction,
-                _ => parent.redaction,
-            },
+            redaction,
+            hash_key: self.hash_key,
+            vault,
         }
     }
 }
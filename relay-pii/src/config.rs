@@ -182,6 +182,40 @@ pub enum RuleType {
     UrlAuth,
     /// US SSN.
     UsSsn,
+    /// Phone numbers in E.164 and common national formats.
+    Phone,
+    /// Passport numbers in fields named like passports, and passport machine readable zones.
+    Passport,
+    /// UK National Insurance number.
+    UkNino,
+    /// German tax identification number (Steuer-ID).
+    DeTaxId,
+    /// Indian Aadhaar number.
+    InAadhaar,
+    /// Indian Permanent Account Number (PAN).
+    InPan,
+    /// Brazilian individual taxpayer number (CPF).
+    BrCpf,
+    /// Brazilian company registration number (CNPJ).
+    BrCnpj,
+    /// JSON Web Tokens.
+    Jwt,
+    /// OAuth bearer tokens.
+    Bearer,
+    /// AWS access key IDs and secret access keys.
+    AwsKey,
+    /// GCP API keys and private keys of service accounts.
+    GcpKey,
+    /// GitHub access tokens.
+    GithubToken,
+    /// GitLab access tokens.
+    GitlabToken,
+    /// Slack tokens and incoming webhook URLs.
+    SlackToken,
+    /// Stripe secret keys and webhook secrets.
+    StripeKey,
+    /// Random-looking strings, such as generated keys and tokens.
+    HighEntropy,
     /// Keys that look like passwords
     Password,
     /// When a regex matches a key, a value is removed
@@ -214,11 +248,14 @@ pub struct Vars {
     /// The default secret key for hashing operations.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub hash_key: Option<String>,
+    /// The secret key for reversible encryption of values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault_key: Option<String>,
 }
 
 impl Vars {
     fn is_empty(&self) -> bool {
-        self.hash_key.is_none()
+        self.hash_key.is_none() && self.vault_key.is_none()
     }
 }
 
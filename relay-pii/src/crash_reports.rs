@@ -0,0 +1,321 @@
+//! Scrubbing of Apple crash reports and Unreal crash contexts.
+
+use std::borrow::Cow;
+use std::str::Utf8Error;
+
+use elementtree::Element;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use relay_event_schema::processor::{FieldAttrs, Pii, ProcessingState, ValueType};
+use relay_protocol::Annotated;
+
+use crate::{utils, PiiAttachmentsProcessor};
+
+/// Matches a `Key: value` field of an Apple crash report.
+static FIELD_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([A-Za-z][A-Za-z0-9 ()]*?):\s+(\S.*)$").unwrap());
+
+/// Matches an image in the `Binary Images` section of an Apple crash report.
+///
+/// The path of the image is the last component of the line, after the address range, the name,
+/// the architecture, and the UUID of the image.
+static IMAGE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^\s*0x[0-9a-f]+\s*-\s*.*?\s(/.*)$").unwrap());
+
+/// An error returned from [`PiiAttachmentsProcessor::scrub_apple_crash_report`] and
+/// [`PiiAttachmentsProcessor::scrub_unreal_context`].
+#[derive(Debug, thiserror::Error)]
+pub enum ScrubCrashReportError {
+    /// The Unreal crash context is not a valid XML document.
+    #[error("invalid XML document")]
+    InvalidXml(#[from] elementtree::Error),
+
+    /// The crash report is not valid UTF-8.
+    #[error("string decoding error")]
+    Decoding(#[from] Utf8Error),
+}
+
+fn pii_attrs() -> Option<Cow<'static, FieldAttrs>> {
+    Some(Cow::Owned(FieldAttrs::new().pii(Pii::True)))
+}
+
+/// Converts the key of an Apple crash report field into a selector key.
+///
+/// For example, `Hardware Model` becomes `hardware_model`.
+fn field_key(key: &str) -> String {
+    let mut normalized = String::with_capacity(key.len());
+
+    for c in key.chars() {
+        if c.is_ascii_alphanumeric() {
+            normalized.push(c.to_ascii_lowercase());
+        } else if !normalized.is_empty() && !normalized.ends_with('_') {
+            normalized.push('_');
+        }
+    }
+
+    normalized.truncate(normalized.trim_end_matches('_').len());
+    normalized
+}
+
+impl PiiAttachmentsProcessor<'_> {
+    /// Applies PII rules to a single line of text.
+    ///
+    /// Returns the scrubbed text, or `None` if it was not modified.
+    fn scrub_text(&self, text: &str, state: &ProcessingState<'_>) -> Option<String> {
+        let mut value = Annotated::new(text.to_owned());
+        utils::scrub_value(self.compiled_config, &mut value, state);
+
+        if value.meta().is_empty() {
+            return None;
+        }
+
+        Some(value.into_value().unwrap_or_default())
+    }
+
+    /// Applies PII rules to an Apple crash report.
+    ///
+    /// Header fields such as `Process` or `Path` are scrubbed as strings under their key in
+    /// lower snake case. For example, `$attachments.$apple_crash_report.hardware_model` selects
+    /// the `Hardware Model` field. Paths of loaded images are scrubbed as items of the
+    /// `binary_images` array. All other lines, including the backtraces, are left unchanged.
+    ///
+    /// Returns the scrubbed crash report, or `None` if it was not modified.
+    pub fn scrub_apple_crash_report(
+        &self,
+        filename: &str,
+        data: &[u8],
+    ) -> Result<Option<Vec<u8>>, ScrubCrashReportError> {
+        let text = std::str::from_utf8(data)?;
+
+        let file_state = self.state(filename, ValueType::AppleCrashReport);
+        let images_state =
+            file_state.enter_static("binary_images", pii_attrs(), Some(ValueType::Array));
+
+        let mut output = String::with_capacity(text.len());
+        let mut changed = false;
+        let mut in_images = false;
+        let mut image_index = 0;
+
+        for line in text.split_inclusive('\n') {
+            let content = line.trim_end_matches(|c| c == '\r' || c == '\n');
+            let line_ending = &line[content.len()..];
+
+            let scrubbed = if in_images {
+                IMAGE_REGEX.captures(content).and_then(|captures| {
+                    let path = captures.get(1)?;
+                    let state =
+                        images_state.enter_index(image_index, pii_attrs(), Some(ValueType::String));
+                    image_index += 1;
+
+                    let scrubbed = self.scrub_text(path.as_str(), &state)?;
+                    Some(format!("{}{}", &content[..path.start()], scrubbed))
+                })
+            } else if content.trim_end() == "Binary Images:" {
+                in_images = true;
+                None
+            } else {
+                FIELD_REGEX.captures(content).and_then(|captures| {
+                    let key = field_key(captures.get(1)?.as_str());
+                    let value = captures.get(2)?;
+                    let state =
+                        file_state.enter_borrowed(&key, pii_attrs(), Some(ValueType::String));
+
+                    let scrubbed = self.scrub_text(value.as_str(), &state)?;
+                    Some(format!("{}{}", &content[..value.start()], scrubbed))
+                })
+            };
+
+            match scrubbed {
+                Some(scrubbed) => {
+                    output.push_str(&scrubbed);
+                    changed = true;
+                }
+                None => output.push_str(content),
+            }
+
+            output.push_str(line_ending);
+        }
+
+        Ok(changed.then(|| output.into_bytes()))
+    }
+
+    /// Applies PII rules to the text of an XML element and all of its children.
+    ///
+    /// Returns `true`, if the element was modified.
+    fn scrub_element(&self, element: &mut Element, state: &ProcessingState<'_>) -> bool {
+        if element.children().next().is_none() {
+            if element.text().is_empty() {
+                return false;
+            }
+
+            return match self.scrub_text(element.text(), state) {
+                Some(scrubbed) => {
+                    element.set_text(scrubbed);
+                    true
+                }
+                None => false,
+            };
+        }
+
+        let mut changed = false;
+        for child in element.children_mut() {
+            let value_type = match child.children().next() {
+                Some(_) => ValueType::Object,
+                None => ValueType::String,
+            };
+
+            let name = child.tag().name().to_owned();
+            let child_state = state.enter_borrowed(&name, pii_attrs(), Some(value_type));
+            changed |= self.scrub_element(child, &child_state);
+        }
+
+        changed
+    }
+
+    /// Applies PII rules to the XML crash context of an Unreal crash report.
+    ///
+    /// The text of every element is scrubbed as a string under the path of its tag names below the
+    /// root element. For example, `$attachments.$unreal_context.RuntimeProperties.UserName`
+    /// selects the name of the user.
+    ///
+    /// Returns the scrubbed crash context, or `None` if it was not modified.
+    pub fn scrub_unreal_context(
+        &self,
+        filename: &str,
+        data: &[u8],
+    ) -> Result<Option<Vec<u8>>, ScrubCrashReportError> {
+        let mut root = Element::from_reader(data)?;
+
+        let state = self.state(filename, ValueType::UnrealContext);
+        if !self.scrub_element(&mut root, &state) {
+            return Ok(None);
+        }
+
+        let mut output = Vec::with_capacity(data.len());
+        root.to_writer(&mut output)?;
+        Ok(Some(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::applications_config;
+
+    const APPLE_CRASH_REPORT: &str = "\
+Incident Identifier: 5C32DF84-31A0-43E7-87D0-239F7F594940
+Hardware Model:      MacBookPro14,3
+Process:             YetAnotherMac [49028]
+Path:                /Users/jane/Library/Developer/YetAnotherMac.app/Contents/MacOS/YetAnotherMac
+Identifier:          com.YourCompany.GenericShooter
+
+Thread 0 Crashed:
+0   libsystem_kernel.dylib        \t0x00007fff61bc6c2a 0x7fff61bc6000 + 3114
+
+Binary Images:
+0x10fa4a000 - 0x10fa4bfff +YetAnotherMac x86_64  <f5c2dbc0e8723bf28e4e48bdd1b8f0d8> /Users/jane/Library/Developer/YetAnotherMac.app/Contents/MacOS/YetAnotherMac
+0x7fff61bc6000 - 0x7fff61bf3ff7  libsystem_kernel.dylib (4903.241.1) <ed0dd95d-c9b3-3a9d-b39b-04b9d2b7a96e> /usr/lib/system/libsystem_kernel.dylib
+";
+
+    const UNREAL_CONTEXT: &str = "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<FGenericCrashContext>
+<RuntimeProperties>
+<UserName>jane</UserName>
+<BaseDir>C:/Users/jane/YetAnotherMac/Binaries/Win64/</BaseDir>
+<CrashVersion>3</CrashVersion>
+</RuntimeProperties>
+</FGenericCrashContext>";
+
+    #[test]
+    fn test_field_key() {
+        assert_eq!(field_key("Hardware Model"), "hardware_model");
+        assert_eq!(field_key("Report Version"), "report_version");
+        assert_eq!(field_key("Crashed Thread (0)"), "crashed_thread_0");
+    }
+
+    #[test]
+    fn test_apple_crash_report_userpath() {
+        let config = applications_config(serde_json::json!({
+            "$attachments.$apple_crash_report.**": ["@userpath:replace"]
+        }));
+        let compiled = config.compiled();
+        let processor = PiiAttachmentsProcessor::new(compiled);
+
+        let scrubbed = processor
+            .scrub_apple_crash_report("crash.txt", APPLE_CRASH_REPORT.as_bytes())
+            .unwrap()
+            .unwrap();
+
+        let expected = APPLE_CRASH_REPORT.replace("/Users/jane/", "/Users/[user]/");
+        assert_eq!(std::str::from_utf8(&scrubbed).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_apple_crash_report_field() {
+        let config = applications_config(serde_json::json!({
+            "$attachments.$apple_crash_report.hardware_model": ["@anything:replace"]
+        }));
+        let compiled = config.compiled();
+        let processor = PiiAttachmentsProcessor::new(compiled);
+
+        let scrubbed = processor
+            .scrub_apple_crash_report("crash.txt", APPLE_CRASH_REPORT.as_bytes())
+            .unwrap()
+            .unwrap();
+
+        let expected = APPLE_CRASH_REPORT.replace("MacBookPro14,3", "[Filtered]");
+        assert_eq!(std::str::from_utf8(&scrubbed).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_apple_crash_report_unchanged() {
+        let config = applications_config(serde_json::json!({
+            "$attachments.$apple_crash_report.**": ["@email:replace"]
+        }));
+        let compiled = config.compiled();
+        let processor = PiiAttachmentsProcessor::new(compiled);
+
+        let scrubbed = processor
+            .scrub_apple_crash_report("crash.txt", APPLE_CRASH_REPORT.as_bytes())
+            .unwrap();
+        assert_eq!(scrubbed, None);
+    }
+
+    #[test]
+    fn test_unreal_context() {
+        let config = applications_config(serde_json::json!({
+            "$attachments.$unreal_context.RuntimeProperties.UserName": ["@anything:replace"],
+            "$attachments.$unreal_context.**": ["@userpath:replace"]
+        }));
+        let compiled = config.compiled();
+        let processor = PiiAttachmentsProcessor::new(compiled);
+
+        let scrubbed = processor
+            .scrub_unreal_context("CrashContext.runtime-xml", UNREAL_CONTEXT.as_bytes())
+            .unwrap()
+            .unwrap();
+
+        let root = Element::from_reader(scrubbed.as_slice()).unwrap();
+        let properties = root.find("RuntimeProperties").unwrap();
+        assert_eq!(properties.find("UserName").unwrap().text(), "[Filtered]");
+        assert_eq!(
+            properties.find("BaseDir").unwrap().text(),
+            "C:/Users/[user]/YetAnotherMac/Binaries/Win64/"
+        );
+        assert_eq!(properties.find("CrashVersion").unwrap().text(), "3");
+    }
+
+    #[test]
+    fn test_unreal_context_invalid() {
+        let config = applications_config(serde_json::json!({
+            "$attachments.$unreal_context.**": ["@userpath:replace"]
+        }));
+        let compiled = config.compiled();
+        let processor = PiiAttachmentsProcessor::new(compiled);
+
+        assert!(processor
+            .scrub_unreal_context("CrashContext.runtime-xml", b"<FGenericCrashContext>")
+            .is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Comparison of the effects of two data privacy settings on an event.
+//!
+//! Changes to PII rules or datascrubbing settings are hard to review by looking at the config
+//! alone. [`diff_privacy_settings`] scrubs a sample event with the old and the new settings and
+//! reports every field that is scrubbed differently, based on the remarks that rules leave in the
+//! event's metadata.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use relay_event_schema::processor::{
+    self, ProcessValue, ProcessingResult, ProcessingState, Processor,
+};
+use relay_event_schema::protocol::Event;
+use relay_protocol::{Annotated, IntoValue, Meta, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::{scrub_event, DataScrubbingConfig, PiiConfig, ScrubEventError};
+
+/// The data privacy settings of a project.
+///
+/// These are the fields of the project config that control scrubbing of events.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PrivacySettings {
+    /// Advanced PII rules.
+    pub pii_config: Option<PiiConfig>,
+    /// Legacy datascrubbing settings.
+    pub datascrubbing_settings: DataScrubbingConfig,
+}
+
+impl PrivacySettings {
+    /// Scrubs the event in the same way as event processing does.
+    fn scrub(&self, event: &mut Annotated<Event>) -> Result<(), ScrubEventError> {
+        scrub_event(
+            event,
+            self.pii_config.as_ref(),
+            &self.datascrubbing_settings,
+        )
+    }
+}
+
+/// The result of scrubbing a single field.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PiiFieldResult {
+    /// The value of the field after scrubbing, or `None` if it was removed.
+    pub value: Option<Value>,
+    /// The IDs of the rules that modified the field.
+    pub rule_ids: Vec<String>,
+}
+
+/// A field that is scrubbed differently by two data privacy settings.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PiiFieldChange {
+    /// The path of the field in the event, which can be used as selector.
+    pub path: String,
+    /// The value of the field before scrubbing.
+    pub original: Option<Value>,
+    /// The result of scrubbing with the old settings.
+    pub old: PiiFieldResult,
+    /// The result of scrubbing with the new settings.
+    pub new: PiiFieldResult,
+}
+
+/// Collects values and rule IDs of fields in an event.
+struct FieldCollector<'a> {
+    /// Paths of fields to collect even if they have no remarks.
+    paths: &'a BTreeSet<String>,
+    /// The collected fields by path.
+    fields: BTreeMap<String, PiiFieldResult>,
+}
+
+impl<'a> FieldCollector<'a> {
+    fn collect(event: &mut Annotated<Event>, paths: &'a BTreeSet<String>) -> Self {
+        let mut collector = Self {
+            paths,
+            fields: BTreeMap::new(),
+        };
+
+        // The collector never returns errors.
+        processor::process_value(event, &mut collector, ProcessingState::root()).ok();
+        collector
+    }
+}
+
+impl Processor for FieldCollector<'_> {
+    fn before_process<T: ProcessValue>(
+        &mut self,
+        value: Option<&T>,
+        meta: &mut Meta,
+        state: &ProcessingState<'_>,
+    ) -> ProcessingResult {
+        let path = state.path().to_string();
+        if path.is_empty() {
+            return Ok(());
+        }
+
+        let mut rule_ids = Vec::new();
+        for remark in meta.iter_remarks() {
+            if !rule_ids.iter().any(|id| id == remark.rule_id()) {
+                rule_ids.push(remark.rule_id().to_owned());
+            }
+        }
+
+        if !rule_ids.is_empty() || self.paths.contains(&path) {
+            let value = value.map(|value| value.clone().into_value());
+            self.fields.insert(path, PiiFieldResult { value, rule_ids });
+        }
+
+        Ok(())
+    }
+}
+
+/// Scrubs the event with the old and the new settings and returns all fields that differ.
+///
+/// Fields are compared by their scrubbed values and the IDs of the rules that modified them. The
+/// changes are sorted by path.
+pub fn diff_privacy_settings(
+    event: &Annotated<Event>,
+    old: &PrivacySettings,
+    new: &PrivacySettings,
+) -> Result<Vec<PiiFieldChange>, ScrubEventError> {
+    let mut old_event = event.clone();
+    old.scrub(&mut old_event)?;
+
+    let mut new_event = event.clone();
+    new.scrub(&mut new_event)?;
+
+    let no_paths = BTreeSet::new();
+    let mut old_fields = FieldCollector::collect(&mut old_event, &no_paths).fields;
+    let mut new_fields = FieldCollector::collect(&mut new_event, &no_paths).fields;
+
+    let paths = old_fields
+        .keys()
+        .chain(new_fields.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    // Fields that are not modified by one of the settings keep their original value.
+    let mut original_fields = FieldCollector::collect(&mut event.clone(), &paths).fields;
+
+    let mut changes = Vec::new();
+    for path in paths {
+        let original = original_fields.remove(&path).and_then(|field| field.value);
+        let unchanged = || PiiFieldResult {
+            value: original.clone(),
+            rule_ids: Vec::new(),
+        };
+
+        let old = old_fields.remove(&path).unwrap_or_else(unchanged);
+        let new = new_fields.remove(&path).unwrap_or_else(unchanged);
+
+        if old != new {
+            changes.push(PiiFieldChange {
+                path,
+                original,
+                old,
+                new,
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(json: serde_json::Value) -> PrivacySettings {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn event() -> Annotated<Event> {
+        Annotated::from_json(
+            r#"{
+                "user": {"email": "jane@example.com", "ip_address": "127.0.0.1"},
+                "extra": {"password": "hunter2", "note": "nothing to see"}
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_diff_added_rule() {
+        let old = settings(serde_json::json!({}));
+        let new = settings(serde_json::json!({
+            "piiConfig": {
+                "applications": {
+                    "$user.email": ["@email:replace"]
+                }
+            }
+        }));
+
+        let changes = diff_privacy_settings(&event(), &old, &new).unwrap();
+        assert_eq!(
+            changes,
+            vec![PiiFieldChange {
+                path: "user.email".to_owned(),
+                original: Some(Value::String("jane@example.com".to_owned())),
+                old: PiiFieldResult {
+                    value: Some(Value::String("jane@example.com".to_owned())),
+                    rule_ids: vec![],
+                },
+                new: PiiFieldResult {
+                    value: Some(Value::String("[email]".to_owned())),
+                    rule_ids: vec!["@email:replace".to_owned()],
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_datascrubbing_settings() {
+        let old = settings(serde_json::json!({
+            "datascrubbingSettings": {
+                "scrubData": true,
+                "scrubDefaults": true
+            }
+        }));
+        let new = settings(serde_json::json!({
+            "datascrubbingSettings": {
+                "scrubData": true,
+                "scrubDefaults": true,
+                "excludeFields": ["password"]
+            }
+        }));
+
+        let changes = diff_privacy_settings(&event(), &old, &new).unwrap();
+        assert_eq!(changes.len(), 1);
+
+        let change = &changes[0];
+        assert_eq!(change.path, "extra.password");
+        assert_eq!(
+            change.old.value,
+            Some(Value::String("[Filtered]".to_owned()))
+        );
+        assert_eq!(change.old.rule_ids, vec!["@password:filter"]);
+        assert_eq!(change.new.value, Some(Value::String("hunter2".to_owned())));
+        assert!(change.new.rule_ids.is_empty());
+    }
+
+    #[test]
+    fn test_diff_same_settings() {
+        let settings = settings(serde_json::json!({
+            "datascrubbingSettings": {
+                "scrubData": true,
+                "scrubDefaults": true
+            }
+        }));
+
+        let changes = diff_privacy_settings(&event(), &settings, &settings).unwrap();
+        assert!(changes.is_empty());
+    }
+}
@@ -11,21 +11,32 @@ mod builtin;
 mod compiledconfig;
 mod config;
 mod convert;
+mod crash_reports;
+mod dry_run;
 mod generate_selectors;
 mod legacy;
+mod metric_tags;
 mod minidumps;
 mod processor;
 mod redactions;
 mod regexes;
 mod selector;
+mod structured;
 mod utils;
+mod validators;
+mod vault;
 
 pub use self::attachments::*;
 pub use self::compiledconfig::*;
 pub use self::config::*;
+pub use self::crash_reports::*;
+pub use self::dry_run::*;
 pub use self::generate_selectors::selector_suggestions_from_value;
 pub use self::legacy::*;
+pub use self::metric_tags::*;
 pub use self::minidumps::*;
 pub use self::processor::*;
 pub use self::redactions::*;
 pub use self::selector::*;
+pub use self::structured::*;
+pub use self::vault::*;
@@ -0,0 +1,97 @@
+//! Scrubbing of metric tag values.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use relay_event_schema::processor::{FieldAttrs, Pii, ProcessingState, ValueType};
+use relay_protocol::Annotated;
+
+use crate::{utils, PiiConfig};
+
+/// Applies PII rules to the values of metric tags.
+///
+/// Every tag value is scrubbed as a string under its tag key, so that selectors such as `$string`
+/// or `transaction` apply to the tag of the same name. Tags whose values are removed by a rule
+/// are dropped from the map.
+///
+/// Returns the number of tags that were modified.
+pub fn scrub_metric_tags(configs: &[&PiiConfig], tags: &mut BTreeMap<String, String>) -> usize {
+    let attrs = Cow::Owned(FieldAttrs::new().pii(Pii::True));
+    let root_state = ProcessingState::root().enter_nothing(Some(attrs.clone()));
+
+    let mut modified = 0;
+    tags.retain(|key, value| {
+        let state = root_state.enter_borrowed(key, Some(attrs.clone()), Some(ValueType::String));
+
+        let mut annotated = Annotated::new(std::mem::take(value));
+        for config in configs {
+            utils::scrub_value(config.compiled(), &mut annotated, &state);
+        }
+
+        if !annotated.meta().is_empty() {
+            modified += 1;
+        }
+
+        match annotated.into_value() {
+            Some(scrubbed) => {
+                *value = scrubbed;
+                true
+            }
+            None => false,
+        }
+    });
+
+    modified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataScrubbingConfig;
+
+    fn tags(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_scrub_default_rules() {
+        let datascrubbing = DataScrubbingConfig {
+            scrub_data: true,
+            scrub_defaults: true,
+            ..Default::default()
+        };
+        let config = datascrubbing.pii_config().unwrap().as_ref().unwrap();
+
+        let mut tags = tags(&[
+            ("user", "jane@example.com"),
+            ("password", "hunter2"),
+            ("transaction", "/api/orders/"),
+        ]);
+
+        let modified = scrub_metric_tags(&[config], &mut tags);
+        assert_eq!(modified, 2);
+        assert_eq!(tags["user"], "[Filtered]");
+        assert_eq!(tags["password"], "[Filtered]");
+        assert_eq!(tags["transaction"], "/api/orders/");
+    }
+
+    #[test]
+    fn test_scrub_remove() {
+        let config: PiiConfig = serde_json::from_value(serde_json::json!({
+            "applications": {
+                "token": ["@anything:remove"],
+                "$string": ["@email:replace"]
+            }
+        }))
+        .unwrap();
+
+        let mut tags = tags(&[("token", "abc"), ("environment", "production")]);
+
+        let modified = scrub_metric_tags(&[&config], &mut tags);
+        assert_eq!(modified, 1);
+        assert_eq!(tags, self::tags(&[("environment", "production")]));
+    }
+}
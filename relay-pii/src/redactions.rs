@@ -47,6 +47,26 @@ pub enum Redaction {
     Mask,
     /// Replaces the value with a hash
     Hash,
+    /// Replaces the value with a keyed hash.
+    ///
+    /// The key is configured in the `hashKey` variable of the PII config. Unlike
+    /// [`Hash`](Self::Hash), the result cannot be reversed with a dictionary attack without knowing
+    /// the key. If no key is configured, the value is removed instead.
+    Hmac,
+    /// Replaces the value with a pseudonym of the same shape.
+    ///
+    /// ASCII digits are replaced with digits, ASCII letters with letters of the same case, and all
+    /// other characters are kept. IP addresses are replaced with valid IP addresses of the same
+    /// version. Pseudonyms are derived from the `hashKey` variable of the PII config, so equal values
+    /// receive equal pseudonyms. If no key is configured, the value is removed instead.
+    Tokenize,
+    /// Replaces the value with its ciphertext.
+    ///
+    /// The value is encrypted with the `vaultKey` variable of the PII config and can be restored
+    /// by anyone holding the key, see [`Vault`](crate::Vault). Equal values receive equal
+    /// ciphertexts. If no key is configured, or the value is in an attachment, the value is removed
+    /// instead.
+    Encrypt,
     /// Added for forward compatibility as catch-all variant.
     #[serde(other, skip_serializing)]
     Other,
@@ -68,6 +88,18 @@ mod tests {
         assert!(deser == redaction);
     }
 
+    #[test]
+    fn test_redaction_deser_keyed() {
+        let deser: Redaction = serde_json::from_str(r#"{"method": "hmac"}"#).unwrap();
+        assert_eq!(deser, Redaction::Hmac);
+
+        let deser: Redaction = serde_json::from_str(r#"{"method": "tokenize"}"#).unwrap();
+        assert_eq!(deser, Redaction::Tokenize);
+
+        let deser: Redaction = serde_json::from_str(r#"{"method": "encrypt"}"#).unwrap();
+        assert_eq!(deser, Redaction::Encrypt);
+    }
+
     #[test]
     fn test_redaction_deser_other() {
         let json = r#"{"method": "foo", "text": "[filter]"}"#;
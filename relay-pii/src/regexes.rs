@@ -3,6 +3,7 @@ use regex::Regex;
 use smallvec::{smallvec, SmallVec};
 
 use crate::config::RuleType;
+use crate::validators;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum PatternType {
@@ -93,12 +94,71 @@ pub fn get_regex_for_rule_type(
         RuleType::UrlAuth => smallvec![(v, &*URL_AUTH_REGEX, ReplaceBehavior::replace_group(1))],
         RuleType::UsSsn => smallvec![(v, &*US_SSN_REGEX, ReplaceBehavior::replace_match())],
         RuleType::Userpath => smallvec![(v, &*PATH_REGEX, ReplaceBehavior::replace_group(1))],
+        RuleType::Phone => smallvec![(v, &*PHONE_REGEX, ReplaceBehavior::replace_group(1))],
+        RuleType::Passport => smallvec![
+            (kv, &*PASSPORT_KEY_REGEX, ReplaceBehavior::replace_value()),
+            (v, &*PASSPORT_MRZ_REGEX, ReplaceBehavior::replace_match()),
+        ],
+        RuleType::UkNino => smallvec![(v, &*UK_NINO_REGEX, ReplaceBehavior::replace_match())],
+        RuleType::DeTaxId => smallvec![(v, &*DE_TAX_ID_REGEX, ReplaceBehavior::replace_match())],
+        RuleType::InAadhaar => smallvec![(v, &*AADHAAR_REGEX, ReplaceBehavior::replace_match())],
+        RuleType::InPan => smallvec![(v, &*PAN_REGEX, ReplaceBehavior::replace_match())],
+        RuleType::BrCpf => smallvec![(v, &*CPF_REGEX, ReplaceBehavior::replace_match())],
+        RuleType::BrCnpj => smallvec![(v, &*CNPJ_REGEX, ReplaceBehavior::replace_match())],
+        RuleType::Jwt => smallvec![(v, &*JWT_REGEX, ReplaceBehavior::replace_match())],
+        RuleType::Bearer => smallvec![(v, &*BEARER_REGEX, ReplaceBehavior::replace_group(1))],
+        RuleType::AwsKey => smallvec![
+            (v, &*AWS_ACCESS_KEY_REGEX, ReplaceBehavior::replace_match()),
+            (v, &*AWS_SECRET_KEY_REGEX, ReplaceBehavior::replace_group(1)),
+        ],
+        RuleType::GcpKey => smallvec![
+            (v, &*GCP_API_KEY_REGEX, ReplaceBehavior::replace_match()),
+            (
+                v,
+                &*GCP_PRIVATE_KEY_REGEX,
+                ReplaceBehavior::replace_group(1)
+            ),
+        ],
+        RuleType::GithubToken => {
+            smallvec![(v, &*GITHUB_TOKEN_REGEX, ReplaceBehavior::replace_match())]
+        }
+        RuleType::GitlabToken => {
+            smallvec![(v, &*GITLAB_TOKEN_REGEX, ReplaceBehavior::replace_match())]
+        }
+        RuleType::SlackToken => {
+            smallvec![(v, &*SLACK_TOKEN_REGEX, ReplaceBehavior::replace_match())]
+        }
+        RuleType::StripeKey => smallvec![(v, &*STRIPE_KEY_REGEX, ReplaceBehavior::replace_match())],
+        RuleType::HighEntropy => {
+            smallvec![(v, &*HIGH_ENTROPY_REGEX, ReplaceBehavior::replace_match())]
+        }
 
         // These ought to have been resolved in CompiledConfig
         RuleType::Alias(_) | RuleType::Multiple(_) | RuleType::Unknown(_) => smallvec![],
     }
 }
 
+/// Validates a match of a value pattern, for instance by verifying its check digits.
+pub type Validator = fn(&str) -> bool;
+
+/// Return the validator for matches of the value patterns of the given rule type.
+///
+/// Matches that fail validation are not redacted. Key patterns are never validated.
+pub fn get_validator_for_rule_type(ty: &RuleType) -> Option<Validator> {
+    match ty {
+        RuleType::Phone => Some(validators::is_valid_phone),
+        RuleType::Passport => Some(validators::is_valid_passport_mrz),
+        RuleType::UkNino => Some(validators::is_valid_uk_nino),
+        RuleType::DeTaxId => Some(validators::is_valid_de_tax_id),
+        RuleType::InAadhaar => Some(validators::is_valid_aadhaar),
+        RuleType::BrCpf => Some(validators::is_valid_cpf),
+        RuleType::BrCnpj => Some(validators::is_valid_cnpj),
+        RuleType::Jwt => Some(validators::is_valid_jwt),
+        RuleType::HighEntropy => Some(validators::is_high_entropy),
+        _ => None,
+    }
+}
+
 #[rustfmt::skip]
 macro_rules! ip {
     (v4s) => { "(25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)" };
@@ -289,3 +349,215 @@ static PASSWORD_KEY_REGEX: Lazy<Regex> = Lazy::new(|| {
         r"(?i)(password|secret|passwd|api_key|apikey|auth|credentials|mysql_pwd|privatekey|private_key|token)"
     ).unwrap()
 });
+
+static PHONE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            # not part of an identifier, a version, or build metadata
+            (?:^|[^\w.+])
+            (
+                # international format, such as +44 20 7946 0958
+                \+[1-9](?:[\ .-]?\(?\d\)?){6,14}
+            |
+                # international format with 00 and separated groups, such as 0049 30 901820
+                00[1-9]\d{0,2}(?:[\ .-]\(?\d{2,8}\)?){1,5}
+            |
+                # area code in parentheses, such as (555) 123-4567 or (030) 1234567
+                \(\d{2,5}\)[\ -]?\d{3,4}[\ -]?\d{2,5}
+            |
+                # national format with trunk prefix, such as 030 1234567 or 020 7946 0958
+                0\d{1,4}[\ /-]\d{3,4}[\ -]?\d{2,5}
+            |
+                # North American format, such as 555-123-4567
+                [2-9]\d{2}[\ .-]\d{3}[\ .-]\d{4}
+            )
+            \b
+        ",
+    )
+    .unwrap()
+});
+
+static PASSPORT_KEY_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)passport").unwrap());
+
+static PASSPORT_MRZ_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            # optional first line with the document type, issuing state, and name
+            (?:\bP[A-Z<][A-Z<]{3}[A-Z<]{39}\s*)?
+            # second line with document number, nationality, dates, and check digits
+            \b[A-Z0-9<]{9}[0-9][A-Z<]{3}[0-9]{6}[0-9][MFX<][0-9]{6}[0-9][A-Z0-9<]{14}[0-9<][0-9]
+        ",
+    )
+    .unwrap()
+});
+
+static UK_NINO_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \b
+            [A-CEGHJ-PR-TW-Z][A-CEGHJ-NPR-TW-Z]
+            \ ?[0-9]{2}\ ?[0-9]{2}\ ?[0-9]{2}\ ?
+            [A-D]
+            \b
+        ",
+    )
+    .unwrap()
+});
+
+static DE_TAX_ID_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \b[1-9][0-9](?:\ ?[0-9]{3}){3}\b
+        ",
+    )
+    .unwrap()
+});
+
+static AADHAAR_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \b[2-9][0-9]{3}[\ -]?[0-9]{4}[\ -]?[0-9]{4}\b
+        ",
+    )
+    .unwrap()
+});
+
+static PAN_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            # the fourth letter denotes the type of holder, such as P for individuals
+            \b[A-Z]{3}[ABCFGHJLPT][A-Z][0-9]{4}[A-Z]\b
+        ",
+    )
+    .unwrap()
+});
+
+static CPF_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \b[0-9]{3}\.?[0-9]{3}\.?[0-9]{3}-?[0-9]{2}\b
+        ",
+    )
+    .unwrap()
+});
+
+static CNPJ_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \b[0-9]{2}\.?[0-9]{3}\.?[0-9]{3}/?[0-9]{4}-?[0-9]{2}\b
+        ",
+    )
+    .unwrap()
+});
+
+static JWT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \beyJ[A-Za-z0-9_-]+\.eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*
+        ",
+    )
+    .unwrap()
+});
+
+static BEARER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?ix)
+            \bbearer\s+
+            ([a-z0-9._~+/-]+=*)
+        ",
+    )
+    .unwrap()
+});
+
+static AWS_ACCESS_KEY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            # access key IDs of users, roles, and other resources
+            \b(?:AKIA|ASIA|ABIA|ACCA|AGPA|AIDA|AIPA|ANPA|ANVA|AROA)[A-Z0-9]{16}\b
+        ",
+    )
+    .unwrap()
+});
+
+static AWS_SECRET_KEY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?ix)
+            # secret access keys have no prefix, so they are only detected next to their name
+            aws.{0,20}?secret.{0,20}?[\s'":=]+
+            ([a-z0-9/+]{40})\b
+        "#,
+    )
+    .unwrap()
+});
+
+static GCP_API_KEY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \bAIza[0-9A-Za-z_-]{35}
+        ",
+    )
+    .unwrap()
+});
+
+static GCP_PRIVATE_KEY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?x)
+            # private key and its ID in the JSON credentials of a service account
+            "private_key(?:_id)?"\s*:\s*"([^"]+)"
+        "#,
+    )
+    .unwrap()
+});
+
+static GITHUB_TOKEN_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \b(?:
+                (?:ghp|gho|ghu|ghs|ghr)_[A-Za-z0-9]{36}
+                | github_pat_[A-Za-z0-9_]{82}
+            )\b
+        ",
+    )
+    .unwrap()
+});
+
+static GITLAB_TOKEN_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \b(?:glpat|glptt|glrt|gldt|glft|gloas|glsoat)-[A-Za-z0-9_-]{20,}
+        ",
+    )
+    .unwrap()
+});
+
+static SLACK_TOKEN_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \bxox[abposr]-[A-Za-z0-9-]{10,}
+            | https://hooks\.slack\.com/(?:services|workflows|triggers)/[A-Za-z0-9/_-]+
+        ",
+    )
+    .unwrap()
+});
+
+static STRIPE_KEY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            \b(?:
+                [sr]k_(?:live|test)_[A-Za-z0-9]{20,}
+                | whsec_[A-Za-z0-9]{32,}
+            )\b
+        ",
+    )
+    .unwrap()
+});
+
+static HIGH_ENTROPY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+            # candidates for base64 or base62 encoded secrets, filtered by their entropy
+            [A-Za-z0-9+/_-]{32,}={0,2}
+        ",
+    )
+    .unwrap()
+});
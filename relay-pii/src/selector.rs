@@ -32,6 +32,10 @@ pub enum InvalidSelectorError {
     #[error("unknown value")]
     UnknownType,
 
+    /// Predicates cannot be applied to deep wildcards.
+    #[error("predicates cannot be applied to deep wildcards")]
+    InvalidPredicate,
+
     /// Internal parser bug: An unexpected item was consumed.
     #[error("parser bug: consumed {0} (expected {1})")]
     UnexpectedToken(String, &'static str),
@@ -65,6 +69,8 @@ pub enum SelectorPathItem {
     Wildcard,
     /// The component is a deep wildcard (`**`).
     DeepWildcard,
+    /// The component matches the inner component if the predicate holds for its value.
+    Predicate(Box<SelectorPathItem>, SelectorPredicate),
 }
 
 impl fmt::Display for SelectorPathItem {
@@ -81,6 +87,7 @@ impl fmt::Display for SelectorPathItem {
             }
             SelectorPathItem::Wildcard => write!(f, "*"),
             SelectorPathItem::DeepWildcard => write!(f, "**"),
+            SelectorPathItem::Predicate(ref item, ref predicate) => write!(f, "{item}{predicate}"),
         }
     }
 }
@@ -90,10 +97,23 @@ impl SelectorPathItem {
     ///
     /// `pii` is not the same as `state.attrs().pii`, but rather the PII flag of the state we're
     /// actually trying to match against. `i` is the position of the path item within the path.
-    pub(super) fn matches_state(&self, pii: Pii, i: usize, state: &ProcessingState<'_>) -> bool {
+    ///
+    /// Predicates are checked with `predicates`, which evaluates a predicate on the value of the
+    /// given state.
+    pub(super) fn matches_state(
+        &self,
+        pii: Pii,
+        i: usize,
+        state: &ProcessingState<'_>,
+        predicates: PredicateFn<'_>,
+    ) -> bool {
         match (self, pii) {
             (_, Pii::False) => false,
 
+            (SelectorPathItem::Predicate(ref item, ref predicate), _) => {
+                item.matches_state(pii, i, state, predicates) && predicates(state, predicate)
+            }
+
             // necessary because of array indices
             (SelectorPathItem::Wildcard, _) => true,
 
@@ -137,6 +157,8 @@ impl SelectorPathItem {
                         | ValueType::Minidump
                         | ValueType::HeapMemory
                         | ValueType::StackMemory
+                        | ValueType::AppleCrashReport
+                        | ValueType::UnrealContext
                         | ValueType::ClientSdkInfo => i == 0,
                     }
             }
@@ -148,6 +170,119 @@ impl SelectorPathItem {
                 .unwrap_or(false),
         }
     }
+
+    /// Determine whether a path item with predicates matches an element of its value.
+    ///
+    /// Predicates on arrays and pair lists apply to their elements, so that
+    /// `headers[0 == 'Authorization']` matches the elements of `headers` whose first item is
+    /// `Authorization`. `element` is the state of the element and `parent` the state of the
+    /// array or pair list containing it.
+    pub(super) fn matches_element(
+        &self,
+        pii: Pii,
+        i: usize,
+        element: &ProcessingState<'_>,
+        parent: &ProcessingState<'_>,
+        predicates: PredicateFn<'_>,
+    ) -> bool {
+        let SelectorPathItem::Predicate(ref item, ref predicate) = *self else {
+            return false;
+        };
+
+        predicates(element, predicate)
+            && match **item {
+                SelectorPathItem::Predicate(..) => {
+                    item.matches_element(pii, i, element, parent, predicates)
+                }
+                ref item => item.matches_state(pii, i, parent, predicates),
+            }
+    }
+
+    /// Returns `true` if the component selects the value of a pair, such as `[1]` in
+    /// `$http.headers[0 == 'Authorization'][1]`.
+    ///
+    /// See [`is_pair_value_state`] for how pair values are matched.
+    fn is_pair_value(&self) -> bool {
+        *self == SelectorPathItem::Index(PAIR_VALUE_INDEX)
+    }
+
+    /// Returns the component without predicates.
+    pub(super) fn base(&self) -> &SelectorPathItem {
+        match self {
+            SelectorPathItem::Predicate(item, _) => item.base(),
+            item => item,
+        }
+    }
+}
+
+/// The index of the value in a pair of a pair list, such as `[key, value]`.
+const PAIR_VALUE_INDEX: usize = 1;
+
+/// Returns `true` if the state is the element of a pair list, which is processed as the value of
+/// the pair.
+///
+/// Pair lists are processed like objects: the value of each pair is entered with the key of the
+/// pair instead of the pair's index in the list and then [`PAIR_VALUE_INDEX`]. A pair value thus
+/// has a single state that stands for both the pair and its value. Selectors addressing the pair
+/// with a predicate and then its value, like `headers[0 == 'Authorization'][1]`, match this state
+/// with both path items.
+fn is_pair_value_state(state: &ProcessingState<'_>) -> bool {
+    state.path().key().is_some()
+}
+
+/// Evaluates a [`SelectorPredicate`] on the value of a processing state.
+pub type PredicateFn<'a> = &'a dyn Fn(&ProcessingState<'_>, &SelectorPredicate) -> bool;
+
+/// The comparison operator of a [`SelectorPredicate`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum PredicateOperator {
+    /// The value equals the literal (`==`).
+    Equal,
+    /// The value does not equal the literal (`!=`).
+    NotEqual,
+    /// The value matches the literal as a case-insensitive glob pattern (`~`).
+    Glob,
+}
+
+impl fmt::Display for PredicateOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PredicateOperator::Equal => write!(f, "=="),
+            PredicateOperator::NotEqual => write!(f, "!="),
+            PredicateOperator::Glob => write!(f, "~"),
+        }
+    }
+}
+
+/// A condition on the value of a path item, such as `$frame[abs_path ~ '*/vendor/*']`.
+///
+/// The predicate compares a value below the matched item with a string literal. Numbers and
+/// booleans are compared by their string representation.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct SelectorPredicate {
+    /// The path of the compared value relative to the matched item.
+    ///
+    /// Consists of keys and indexes only.
+    pub path: Vec<SelectorPathItem>,
+    /// The comparison operator.
+    pub operator: PredicateOperator,
+    /// The string literal to compare with.
+    pub literal: String,
+}
+
+impl fmt::Display for SelectorPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (idx, item) in self.path.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{item}")?;
+        }
+
+        let literal = self.literal.replace('\'', "''");
+        write!(f, " {} '{}']", self.operator, literal)
+    }
 }
 
 /// A selector that can match paths of processing states.
@@ -192,8 +327,15 @@ impl SelectorSpec {
     /// Checks if a path matches given selector.
     ///
     /// This walks both the selector and the path starting at the end and towards the root
-    /// to determine if the selector matches the current path.
+    /// to determine if the selector matches the current path. Path items with predicates never
+    /// match, since values are not available. Use [`matches_path_with`](Self::matches_path_with)
+    /// to evaluate predicates.
     pub fn matches_path(&self, path: &Path) -> bool {
+        self.matches_path_with(path, &|_, _| false)
+    }
+
+    /// Checks if a path matches given selector, evaluating predicates with the given function.
+    pub fn matches_path_with(&self, path: &Path, predicates: PredicateFn<'_>) -> bool {
         let pii = path.attrs().pii;
         if pii == Pii::False {
             return false;
@@ -210,11 +352,27 @@ impl SelectorSpec {
                 let mut state_iter = path.iter().filter(|state| state.entered_anything());
                 let mut selector_iter = path_items.iter().enumerate().rev();
                 let mut depth_match = false;
-                for state in &mut state_iter {
+                while let Some(state) = state_iter.next() {
                     match selector_iter.next() {
                         Some((i, path_item)) => {
-                            if !path_item.matches_state(pii, i, state) {
-                                return false;
+                            if !path_item.matches_state(pii, i, state, predicates) {
+                                // predicates on arrays and pair lists span the element and its
+                                // parent. The value of a pair is the element itself.
+                                let Some(parent) = state_iter.next() else {
+                                    return false;
+                                };
+
+                                let matches = path_item
+                                    .matches_element(pii, i, state, parent, predicates)
+                                    || (path_item.is_pair_value()
+                                        && is_pair_value_state(state)
+                                        && selector_iter.next().map_or(false, |(i, item)| {
+                                            item.matches_element(pii, i, state, parent, predicates)
+                                        }));
+
+                                if !matches {
+                                    return false;
+                                }
                             }
 
                             if matches!(path_item, SelectorPathItem::DeepWildcard) {
@@ -234,26 +392,48 @@ impl SelectorSpec {
                 // match of the selector.
                 let remaining_states = state_iter.collect::<SmallVec<[&ProcessingState<'_>; 16]>>();
                 let mut selector_iter = selector_iter.rev().peekable();
-                let (first_selector_i, first_selector_path) = match selector_iter.next() {
-                    Some(selector_path) => selector_path,
-                    None => return !remaining_states.is_empty(),
-                };
-                let mut path_match_iterator = remaining_states.iter().rev().skip_while(|state| {
-                    !first_selector_path.matches_state(pii, first_selector_i, state)
-                });
-                if path_match_iterator.next().is_none() {
-                    return false;
+                if selector_iter.peek().is_none() {
+                    return !remaining_states.is_empty();
                 }
 
                 // then we check all remaining items and that nothing is left of the selector
-                path_match_iterator
-                    .zip(&mut selector_iter)
-                    .all(|(state, (i, selector_path))| selector_path.matches_state(pii, i, state))
-                    && selector_iter.next().is_none()
+                let mut path_match_iterator = remaining_states.iter().rev().peekable();
+                let mut first = true;
+                while let Some((i, selector_path)) = selector_iter.next() {
+                    loop {
+                        let Some(state) = path_match_iterator.next() else {
+                            return false;
+                        };
+
+                        if selector_path.matches_state(pii, i, state, predicates) {
+                            break;
+                        }
+
+                        if let Some(element) = path_match_iterator.peek() {
+                            if selector_path.matches_element(pii, i, element, state, predicates) {
+                                // the value of a pair is the element itself
+                                if is_pair_value_state(element) {
+                                    selector_iter.next_if(|(_, item)| item.is_pair_value());
+                                }
+
+                                path_match_iterator.next();
+                                break;
+                            }
+                        }
+
+                        if !first {
+                            return false;
+                        }
+                    }
+
+                    first = false;
+                }
+
+                true
             }
-            SelectorSpec::And(ref xs) => xs.iter().all(|x| x.matches_path(path)),
-            SelectorSpec::Or(ref xs) => xs.iter().any(|x| x.matches_path(path)),
-            SelectorSpec::Not(ref x) => !x.matches_path(path),
+            SelectorSpec::And(ref xs) => xs.iter().all(|x| x.matches_path_with(path, predicates)),
+            SelectorSpec::Or(ref xs) => xs.iter().any(|x| x.matches_path_with(path, predicates)),
+            SelectorSpec::Not(ref x) => !x.matches_path_with(path, predicates),
         }
     }
 }
@@ -381,7 +561,7 @@ fn handle_selector(pair: Pair<Rule>) -> Result<SelectorSpec, InvalidSelectorErro
             let items: Vec<SelectorPathItem> = pair
                 .into_inner()
                 .map(|item| {
-                    let rv = handle_selector_path_item(item)?;
+                    let rv = handle_path_segment(item)?;
                     if rv == SelectorPathItem::DeepWildcard {
                         if used_deep_wildcard {
                             return Err(InvalidSelectorError::InvalidDeepWildcard);
@@ -411,6 +591,86 @@ fn handle_selector(pair: Pair<Rule>) -> Result<SelectorSpec, InvalidSelectorErro
     }
 }
 
+fn handle_path_segment(pair: Pair<Rule>) -> Result<SelectorPathItem, InvalidSelectorError> {
+    let mut inner = pair.into_inner();
+    let first = inner.next().unwrap();
+    let mut item = match first.as_rule() {
+        Rule::SelectorPathItem => handle_selector_path_item(first)?,
+        Rule::Index => SelectorPathItem::Index(
+            first
+                .as_str()
+                .parse()
+                .map_err(|_| InvalidSelectorError::InvalidIndex)?,
+        ),
+        rule => {
+            return Err(InvalidSelectorError::UnexpectedToken(
+                format!("{rule:?}"),
+                "a path segment",
+            ))
+        }
+    };
+
+    for predicate in inner {
+        if item == SelectorPathItem::DeepWildcard {
+            return Err(InvalidSelectorError::InvalidPredicate);
+        }
+
+        item = SelectorPathItem::Predicate(Box::new(item), handle_predicate(predicate)?);
+    }
+
+    Ok(item)
+}
+
+fn handle_predicate(pair: Pair<Rule>) -> Result<SelectorPredicate, InvalidSelectorError> {
+    let mut inner = pair.into_inner();
+    let (Some(path), Some(operator), Some(literal)) = (inner.next(), inner.next(), inner.next())
+    else {
+        return Err(InvalidSelectorError::InternalError);
+    };
+
+    let path = path
+        .into_inner()
+        .map(|key| {
+            let key = key.into_inner().next().unwrap();
+            match key.as_rule() {
+                Rule::Index => Ok(SelectorPathItem::Index(
+                    key.as_str()
+                        .parse()
+                        .map_err(|_| InvalidSelectorError::InvalidIndex)?,
+                )),
+                Rule::Key => Ok(SelectorPathItem::Key(handle_key(key)?)),
+                rule => Err(InvalidSelectorError::UnexpectedToken(
+                    format!("{rule:?}"),
+                    "a predicate key",
+                )),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+
+    let operator = match operator.as_str() {
+        "==" => PredicateOperator::Equal,
+        "!=" => PredicateOperator::NotEqual,
+        "~" => PredicateOperator::Glob,
+        other => {
+            return Err(InvalidSelectorError::UnexpectedToken(
+                other.to_owned(),
+                "a predicate operator",
+            ))
+        }
+    };
+
+    let mut value = String::new();
+    for token in literal.into_inner().flat_map(|quoted| quoted.into_inner()) {
+        value.push_str(token.as_str());
+    }
+
+    Ok(SelectorPredicate {
+        path,
+        operator,
+        literal: value,
+    })
+}
+
 fn handle_selector_path_item(pair: Pair<Rule>) -> Result<SelectorPathItem, InvalidSelectorError> {
     let pair = pair.into_inner().next().unwrap();
     match pair.as_rule() {
@@ -475,6 +735,17 @@ mod tests {
         check_roundtrip("!a && !b");
         check_roundtrip("!(a && !b)");
         check_roundtrip("!(a && b)");
+        check_roundtrip("$frame[abs_path ~ '*/vendor/*'].vars.**");
+        check_roundtrip("extra.headers.*[0 == 'Authorization'].1");
+        check_roundtrip("$user[geo.city != 'Vienna'][id == 'it''s'].email");
+    }
+
+    #[test]
+    fn test_bracket_index() {
+        assert_eq!(
+            SelectorSpec::from_str("extra.values[1]").unwrap(),
+            SelectorSpec::from_str("extra.values.1").unwrap(),
+        );
     }
 
     #[test]
@@ -487,6 +758,11 @@ mod tests {
             SelectorSpec::from_str("$frame.**.foo.**"),
             Err(InvalidSelectorError::InvalidDeepWildcard)
         ));
+        assert!(matches!(
+            SelectorSpec::from_str("$frame.**[a == 'b']"),
+            Err(InvalidSelectorError::InvalidPredicate)
+        ));
+        assert!(SelectorSpec::from_str("$frame[a = 'b']").is_err());
     }
 
     macro_rules! assert_matches_raw {
@@ -656,4 +932,21 @@ mod tests {
         // WAT.  We have the full path to a field here.
         assert_matches_pii_true!(minidump_state_inner, "$attachments.$minidump.$binary",);
     }
+
+    #[test]
+    fn test_predicate_matching() {
+        let pii = || Some(Cow::Owned(FieldAttrs::new().pii(Pii::True)));
+        let event_state = ProcessingState::new_root(None, Some(ValueType::Event));
+        let frame_state = event_state.enter_index(0, pii(), Some(ValueType::Frame));
+        let vars_state = frame_state.enter_static("vars", pii(), Some(ValueType::Object));
+
+        let selector: SelectorSpec = "$frame[abs_path ~ '*/vendor/*'].vars".parse().unwrap();
+        assert!(!selector.matches_path(&vars_state.path()));
+
+        let holds = |state: &ProcessingState<'_>, predicate: &SelectorPredicate| {
+            state.depth() == frame_state.depth() && predicate.literal == "*/vendor/*"
+        };
+        assert!(selector.matches_path_with(&vars_state.path(), &holds));
+        assert!(!selector.matches_path_with(&frame_state.path(), &holds));
+    }
 }
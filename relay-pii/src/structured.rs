@@ -0,0 +1,411 @@
+//! Scrubbing of structured attachments, such as JSON documents and log files.
+
+use std::str::Utf8Error;
+
+use relay_event_schema::processor::ValueType;
+use relay_protocol::{Annotated, Value};
+
+use crate::{utils, PiiAttachmentsProcessor};
+
+/// An error returned from [`PiiAttachmentsProcessor::scrub_json`] and
+/// [`PiiAttachmentsProcessor::scrub_log`].
+#[derive(Debug, thiserror::Error)]
+pub enum ScrubStructuredError {
+    /// The attachment is not valid JSON.
+    #[error("failed to parse attachment")]
+    InvalidJson(#[from] serde_json::Error),
+
+    /// The attachment is not valid UTF-8.
+    #[error("string decoding error")]
+    Decoding(#[from] Utf8Error),
+}
+
+/// Serializes a scrubbed JSON document while retaining the source text of unmodified parts.
+///
+/// Objects and arrays containing modified values are rewritten member by member, so that their
+/// whitespace and the order of their keys remain unchanged. Only the modified values themselves
+/// are serialized. The source must be valid JSON that parses into `original`.
+struct JsonRewriter<'a> {
+    source: &'a [u8],
+    position: usize,
+    output: Vec<u8>,
+}
+
+impl<'a> JsonRewriter<'a> {
+    fn rewrite(
+        source: &'a [u8],
+        original: &Value,
+        scrubbed: Option<&Value>,
+    ) -> Result<Vec<u8>, serde_json::Error> {
+        let mut rewriter = Self {
+            source,
+            position: 0,
+            output: Vec::with_capacity(source.len()),
+        };
+
+        rewriter.copy_whitespace();
+        rewriter.value(Some(original), scrubbed)?;
+        rewriter
+            .output
+            .extend_from_slice(&source[rewriter.position..]);
+
+        Ok(rewriter.output)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.position).copied()
+    }
+
+    /// Copies the source from the current position up to `end` into the output.
+    fn copy_to(&mut self, end: usize) {
+        let end = end.min(self.source.len());
+        self.output
+            .extend_from_slice(&self.source[self.position..end]);
+        self.position = end;
+    }
+
+    fn copy_whitespace(&mut self) {
+        let mut end = self.position;
+        while matches!(self.source.get(end), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            end += 1;
+        }
+        self.copy_to(end);
+    }
+
+    /// Copies a single punctuation character, such as a comma or colon, with its whitespace.
+    fn copy_punctuation(&mut self) {
+        self.copy_to(self.position + 1);
+        self.copy_whitespace();
+    }
+
+    /// Returns the end of the string starting at `start`.
+    fn string_end(&self, start: usize) -> usize {
+        let mut end = start + 1;
+        while let Some(byte) = self.source.get(end) {
+            match byte {
+                b'\\' => end += 2,
+                b'"' => return end + 1,
+                _ => end += 1,
+            }
+        }
+        self.source.len()
+    }
+
+    /// Returns the end of the value starting at the current position.
+    fn value_end(&self) -> usize {
+        match self.peek() {
+            Some(b'"') => self.string_end(self.position),
+            Some(b'{' | b'[') => {
+                let mut end = self.position;
+                let mut depth = 0usize;
+                while let Some(byte) = self.source.get(end) {
+                    match byte {
+                        b'"' => {
+                            end = self.string_end(end);
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return end + 1;
+                            }
+                        }
+                        _ => (),
+                    }
+                    end += 1;
+                }
+                end
+            }
+            _ => {
+                // Numbers, booleans, and `null` end at the next delimiter.
+                let mut end = self.position;
+                while let Some(byte) = self.source.get(end) {
+                    if matches!(byte, b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r') {
+                        break;
+                    }
+                    end += 1;
+                }
+                end
+            }
+        }
+    }
+
+    fn value(
+        &mut self,
+        original: Option<&Value>,
+        scrubbed: Option<&Value>,
+    ) -> Result<(), serde_json::Error> {
+        if original == scrubbed {
+            let end = self.value_end();
+            self.copy_to(end);
+            return Ok(());
+        }
+
+        match (original, scrubbed, self.peek()) {
+            (Some(Value::Object(original)), Some(Value::Object(scrubbed)), Some(b'{')) => {
+                self.copy_punctuation();
+                while self.peek().map_or(false, |byte| byte != b'}') {
+                    let key_end = self.string_end(self.position);
+                    let key =
+                        serde_json::from_slice::<String>(&self.source[self.position..key_end])?;
+                    self.copy_to(key_end);
+                    self.copy_whitespace();
+                    self.copy_punctuation(); // `:`
+
+                    self.value(
+                        original.get(&key).and_then(Annotated::value),
+                        scrubbed.get(&key).and_then(Annotated::value),
+                    )?;
+
+                    self.copy_whitespace();
+                    if self.peek() == Some(b',') {
+                        self.copy_punctuation();
+                    }
+                }
+                self.copy_to(self.position + 1);
+            }
+            (Some(Value::Array(original)), Some(Value::Array(scrubbed)), Some(b'[')) => {
+                self.copy_punctuation();
+                let mut index = 0;
+                while self.peek().map_or(false, |byte| byte != b']') {
+                    self.value(
+                        original.get(index).and_then(Annotated::value),
+                        scrubbed.get(index).and_then(Annotated::value),
+                    )?;
+                    index += 1;
+
+                    self.copy_whitespace();
+                    if self.peek() == Some(b',') {
+                        self.copy_punctuation();
+                    }
+                }
+                self.copy_to(self.position + 1);
+            }
+            _ => {
+                self.position = self.value_end();
+                serde_json::to_writer(&mut self.output, &scrubbed)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if PII rules left remarks on the value or any of its children.
+fn has_remarks(annotated: &Annotated<Value>) -> bool {
+    if !annotated.meta().is_empty() {
+        return true;
+    }
+
+    match annotated.value() {
+        Some(Value::Array(items)) => items.iter().any(has_remarks),
+        Some(Value::Object(items)) => items.values().any(has_remarks),
+        _ => false,
+    }
+}
+
+impl PiiAttachmentsProcessor<'_> {
+    /// Applies PII rules to a value tree parsed from the attachment with the given name.
+    ///
+    /// Returns `true`, if the value was modified.
+    fn scrub_value(&self, filename: &str, value: &mut Annotated<Value>) -> bool {
+        let value_type = match value.value() {
+            Some(Value::Array(_)) => ValueType::Array,
+            Some(Value::Object(_)) => ValueType::Object,
+            _ => ValueType::String,
+        };
+
+        let state = self.state(filename, value_type);
+        utils::scrub_value(self.compiled_config, value, &state);
+
+        has_remarks(value)
+    }
+
+    /// Applies PII rules to a JSON attachment, such as a view hierarchy.
+    ///
+    /// The attachment is parsed into a value tree, so that selectors address its fields instead of
+    /// byte patterns. For example, `$attachments.'data.json'.user.email` selects the `email`
+    /// field of the `user` object in `data.json`. Only scrubbed values are rewritten, all other
+    /// parts of the attachment retain their original formatting.
+    ///
+    /// Returns the scrubbed attachment, or `None` if it was not modified.
+    pub fn scrub_json(
+        &self,
+        filename: &str,
+        data: &[u8],
+    ) -> Result<Option<Vec<u8>>, ScrubStructuredError> {
+        let original = serde_json::from_slice::<Value>(data)?;
+        let mut value = Annotated::new(original.clone());
+
+        if !self.scrub_value(filename, &mut value) {
+            return Ok(None);
+        }
+
+        Ok(Some(JsonRewriter::rewrite(data, &original, value.value())?))
+    }
+
+    /// Applies PII rules to a newline-delimited log file.
+    ///
+    /// The log file is treated as an array of lines. Lines containing JSON objects are parsed into
+    /// value trees, all other lines are scrubbed as strings. For example,
+    /// `$attachments.'app.log'.*.user.email` selects the `email` field of the `user` object in
+    /// every line of `app.log`.
+    ///
+    /// Returns the scrubbed attachment, or `None` if it was not modified.
+    pub fn scrub_log(
+        &self,
+        filename: &str,
+        data: &[u8],
+    ) -> Result<Option<Vec<u8>>, ScrubStructuredError> {
+        let text = std::str::from_utf8(data)?;
+
+        let mut sources = Vec::new();
+        let mut carriage_returns = Vec::new();
+        let mut lines = Vec::new();
+        for line in text.split('\n') {
+            let stripped = line.strip_suffix('\r');
+            carriage_returns.push(stripped.is_some());
+            let line = stripped.unwrap_or(line);
+
+            let value = match serde_json::from_str::<Value>(line) {
+                Ok(value @ Value::Object(_)) => value,
+                _ => Value::String(line.to_owned()),
+            };
+            sources.push((line, value.clone()));
+            lines.push(Annotated::new(value));
+        }
+
+        let mut value = Annotated::new(Value::Array(lines));
+        if !self.scrub_value(filename, &mut value) {
+            return Ok(None);
+        }
+
+        let Some(Value::Array(lines)) = value.into_value() else {
+            // Logs with all lines removed become empty.
+            return Ok(Some(Vec::new()));
+        };
+
+        let mut output = Vec::with_capacity(data.len());
+        let lines = lines.into_iter().zip(sources).zip(carriage_returns);
+        for (index, ((line, (source, original)), carriage_return)) in lines.enumerate() {
+            if index > 0 {
+                output.push(b'\n');
+            }
+
+            match (line.value(), original) {
+                (Some(Value::String(line)), _) => output.extend_from_slice(line.as_bytes()),
+                (Some(value), original @ Value::Object(_)) => {
+                    let line = JsonRewriter::rewrite(source.as_bytes(), &original, Some(value))?;
+                    output.extend_from_slice(&line);
+                }
+                (Some(value), _) => serde_json::to_writer(&mut output, value)?,
+                (None, _) => (),
+            }
+
+            if carriage_return {
+                output.push(b'\r');
+            }
+        }
+
+        Ok(Some(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::applications_config;
+
+    #[test]
+    fn test_scrub_json_field() {
+        let config = applications_config(serde_json::json!({
+            "$attachments.'data.json'.user.email": ["@anything:remove"]
+        }));
+        let compiled = config.compiled();
+        let processor = PiiAttachmentsProcessor::new(compiled);
+
+        let data = br#"{"user": {"email": "jane@example.com", "name": "Jane"}}"#;
+        let scrubbed = processor.scrub_json("data.json", data).unwrap().unwrap();
+        assert_eq!(
+            std::str::from_utf8(&scrubbed).unwrap(),
+            r#"{"user": {"email": null, "name": "Jane"}}"#
+        );
+
+        // Other attachments are not affected by the selector.
+        assert_eq!(processor.scrub_json("other.json", data).unwrap(), None);
+    }
+
+    #[test]
+    fn test_scrub_json_rule() {
+        let config = applications_config(serde_json::json!({
+            "$attachments.**": ["@email:replace"]
+        }));
+        let compiled = config.compiled();
+        let processor = PiiAttachmentsProcessor::new(compiled);
+
+        let data = br#"{"windows": [{"type": "label", "text": "jane@example.com"}]}"#;
+        let scrubbed = processor
+            .scrub_json("view-hierarchy.json", data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            std::str::from_utf8(&scrubbed).unwrap(),
+            r#"{"windows": [{"type": "label", "text": "[email]"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_scrub_json_formatting() {
+        let config = applications_config(serde_json::json!({
+            "$attachments.**": ["@email:replace"]
+        }));
+        let compiled = config.compiled();
+        let processor = PiiAttachmentsProcessor::new(compiled);
+
+        let data = br#"{
+  "z": 1.50,
+  "user" : {"email": "jane@example.com"},
+  "a": ["x\"}", null]
+}
+"#;
+        let scrubbed = processor.scrub_json("data.json", data).unwrap().unwrap();
+        assert_eq!(
+            std::str::from_utf8(&scrubbed).unwrap(),
+            r#"{
+  "z": 1.50,
+  "user" : {"email": "[email]"},
+  "a": ["x\"}", null]
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_scrub_json_invalid() {
+        let config = applications_config(serde_json::json!({
+            "$attachments.**": ["@email:replace"]
+        }));
+        let compiled = config.compiled();
+        let processor = PiiAttachmentsProcessor::new(compiled);
+
+        assert!(processor.scrub_json("data.json", b"{invalid").is_err());
+    }
+
+    #[test]
+    fn test_scrub_log() {
+        let config = applications_config(serde_json::json!({
+            "$attachments.'app.log'.*.user": ["@anything:remove"],
+            "$attachments.'app.log'.$string": ["@ip:replace"]
+        }));
+        let compiled = config.compiled();
+        let processor = PiiAttachmentsProcessor::new(compiled);
+
+        let data = b"request from 127.0.0.1\r\n{\"level\":\"info\",\"user\":\"jane\"}\n";
+        let scrubbed = processor.scrub_log("app.log", data).unwrap().unwrap();
+        assert_eq!(
+            std::str::from_utf8(&scrubbed).unwrap(),
+            "request from [ip]\r\n{\"level\":\"info\",\"user\":null}\n"
+        );
+    }
+}
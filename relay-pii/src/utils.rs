@@ -1,14 +1,29 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
 use hmac::{Hmac, Mac};
 use relay_event_schema::processor::{
     self, ProcessValue, ProcessingResult, ProcessingState, Processor, ValueType,
 };
 use relay_event_schema::protocol::{AsPair, PairList};
+use relay_protocol::Annotated;
 use sha1::Sha1;
 
+use crate::{CompiledPiiConfig, PiiProcessor};
+
 pub fn process_pairlist<P: Processor, T: ProcessValue + AsPair>(
     slf: &mut P,
     value: &mut PairList<T>,
     state: &ProcessingState,
+) -> ProcessingResult {
+    process_pairlist_with(slf, value, state, |_, _| ())
+}
+
+/// Like [`process_pairlist`], but calls `before_pair` with each pair before processing its value.
+pub fn process_pairlist_with<P: Processor, T: ProcessValue + AsPair>(
+    slf: &mut P,
+    value: &mut PairList<T>,
+    state: &ProcessingState,
+    mut before_pair: impl FnMut(&mut P, &T),
 ) -> ProcessingResult {
     // View pairlists as objects just for the purpose of PII stripping (e.g. `event.tags.mykey`
     // instead of `event.tags.42.0`). For other purposes such as trimming we would run into
@@ -18,6 +33,8 @@ pub fn process_pairlist<P: Processor, T: ProcessValue + AsPair>(
 
     for (idx, annotated) in value.iter_mut().enumerate() {
         if let Some(ref mut pair) = annotated.value_mut() {
+            before_pair(slf, pair);
+
             let (ref mut key, ref mut value) = pair.as_pair_mut();
             let value_type = ValueType::for_field(value);
 
@@ -37,8 +54,140 @@ pub fn process_pairlist<P: Processor, T: ProcessValue + AsPair>(
     Ok(())
 }
 
+/// Applies the rules of a PII config to a value at the given state.
+pub fn scrub_value<T: ProcessValue>(
+    config: &CompiledPiiConfig,
+    value: &mut Annotated<T>,
+    state: &ProcessingState<'_>,
+) {
+    let mut processor = PiiProcessor::new(config);
+    // The PII processor only deletes values and never invalidates them.
+    processor::process_value(value, &mut processor, state).ok();
+}
+
+/// Creates a PII config with the given applications of rules to selectors.
+#[cfg(test)]
+pub fn applications_config(applications: serde_json::Value) -> crate::PiiConfig {
+    serde_json::from_value(serde_json::json!({ "applications": applications })).unwrap()
+}
+
 pub fn hash_value(data: &[u8]) -> String {
-    let mut mac = Hmac::<Sha1>::new_from_slice(&[]).unwrap();
+    hmac_value(&[], data)
+}
+
+pub fn hmac_value(key: &[u8], data: &[u8]) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).unwrap();
     mac.update(data);
     format!("{:X}", mac.finalize().into_bytes())
 }
+
+/// Returns a stream of pseudo-random bytes derived from the key and the data.
+fn key_stream<'a>(key: &'a [u8], data: &'a [u8]) -> impl Iterator<Item = u8> + 'a {
+    (0u32..).flat_map(move |counter| {
+        let mut mac = Hmac::<Sha1>::new_from_slice(key).unwrap();
+        mac.update(&counter.to_be_bytes());
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    })
+}
+
+/// Replaces the value with a pseudonym of the same shape, see [`Redaction::Tokenize`].
+///
+/// Only ASCII digits and letters are replaced, so that the pseudonym has the same byte length as
+/// the value. All other characters are kept.
+///
+/// [`Redaction::Tokenize`]: crate::Redaction::Tokenize
+pub fn tokenize_value(key: &[u8], value: &str) -> String {
+    let mut stream = key_stream(key, value.as_bytes());
+    let mut next = move || stream.next().unwrap_or_default();
+
+    match value.parse::<IpAddr>() {
+        Ok(IpAddr::V4(_)) => return Ipv4Addr::from([next(), next(), next(), next()]).to_string(),
+        Ok(IpAddr::V6(_)) => return Ipv6Addr::from([(); 16].map(|_| next())).to_string(),
+        Err(_) => (),
+    }
+
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_digit() {
+                char::from(b'0' + next() % 10)
+            } else if c.is_ascii_uppercase() {
+                char::from(b'A' + next() % 26)
+            } else if c.is_ascii_lowercase() {
+                char::from(b'a' + next() % 26)
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_value() {
+        assert_eq!(hmac_value(&[], b"127.0.0.1"), hash_value(b"127.0.0.1"));
+        assert_ne!(
+            hmac_value(b"secret", b"127.0.0.1"),
+            hash_value(b"127.0.0.1")
+        );
+        assert_ne!(
+            hmac_value(b"secret", b"127.0.0.1"),
+            hmac_value(b"other", b"127.0.0.1")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_value_shape() {
+        let token = tokenize_value(b"secret", "Jane.Doe-42@example.com");
+        assert_ne!(token, "Jane.Doe-42@example.com");
+        assert_eq!(token.len(), "Jane.Doe-42@example.com".len());
+
+        let shape = |s: &str| -> String {
+            s.chars()
+                .map(|c| match c {
+                    '0'..='9' => '9',
+                    'A'..='Z' => 'A',
+                    'a'..='z' => 'a',
+                    c => c,
+                })
+                .collect()
+        };
+        assert_eq!(shape(&token), shape("Jane.Doe-42@example.com"));
+    }
+
+    #[test]
+    fn test_tokenize_value_non_ascii() {
+        let value = "Zoë Ünal ٣٤ 東京";
+        let token = tokenize_value(b"secret", value);
+        assert_eq!(token.len(), value.len());
+
+        let non_ascii = |s: &str| -> Vec<char> { s.chars().filter(|c| !c.is_ascii()).collect() };
+        assert_eq!(non_ascii(&token), non_ascii(value));
+        assert_ne!(token, value);
+    }
+
+    #[test]
+    fn test_tokenize_value_stable() {
+        assert_eq!(
+            tokenize_value(b"secret", "4111 1111 1111 1111"),
+            tokenize_value(b"secret", "4111 1111 1111 1111")
+        );
+        assert_ne!(
+            tokenize_value(b"secret", "4111 1111 1111 1111"),
+            tokenize_value(b"other", "4111 1111 1111 1111")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_value_ip() {
+        let token = tokenize_value(b"secret", "127.0.0.1");
+        assert!(token.parse::<Ipv4Addr>().is_ok());
+
+        let token = tokenize_value(b"secret", "2001:db8::1");
+        assert!(token.parse::<Ipv6Addr>().is_ok());
+    }
+}
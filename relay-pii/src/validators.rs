@@ -0,0 +1,299 @@
+//! Validation of matches for built-in rules, such as check digits of national identifiers.
+//!
+//! Regular expressions alone cannot tell an identifier apart from any other number of the same
+//! length. Validating the check digits of matches keeps the number of false positives down.
+
+use data_encoding::BASE64URL_NOPAD;
+
+/// Returns the decimal digits in the given text.
+fn digits(text: &str) -> Vec<u8> {
+    text.bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|b| b - b'0')
+        .collect()
+}
+
+/// Validates the number of digits of a phone number.
+///
+/// E.164 allows up to 15 digits including the country code, and no phone number is shorter than
+/// seven digits.
+pub fn is_valid_phone(text: &str) -> bool {
+    (7..=15).contains(&digits(text).len())
+}
+
+/// Validates the prefix of a UK National Insurance number.
+///
+/// The format has no check digit, but some prefixes are never issued.
+pub fn is_valid_uk_nino(text: &str) -> bool {
+    !matches!(
+        text.get(..2),
+        Some("BG" | "GB" | "KN" | "NK" | "NT" | "TN" | "ZZ")
+    )
+}
+
+/// Validates a German tax identification number (Steuerliche Identifikationsnummer).
+///
+/// In the first ten digits, exactly one digit occurs twice or three times. The last digit is a
+/// check digit according to ISO 7064, MOD 11,10.
+pub fn is_valid_de_tax_id(text: &str) -> bool {
+    let digits = digits(text);
+    if digits.len() != 11 || digits[0] == 0 {
+        return false;
+    }
+
+    let mut counts = [0u8; 10];
+    for &digit in &digits[..10] {
+        counts[digit as usize] += 1;
+    }
+
+    let repeated = counts.iter().filter(|&&count| count > 1).count();
+    if repeated != 1 || counts.iter().any(|&count| count > 3) {
+        return false;
+    }
+
+    let mut product = 10;
+    for &digit in &digits[..10] {
+        let mut sum = (digit + product) % 10;
+        if sum == 0 {
+            sum = 10;
+        }
+        product = (sum * 2) % 11;
+    }
+
+    (11 - product) % 10 == digits[10]
+}
+
+/// Validates an Indian Aadhaar number using the Verhoeff algorithm.
+pub fn is_valid_aadhaar(text: &str) -> bool {
+    const D: [[u8; 10]; 10] = [
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        [1, 2, 3, 4, 0, 6, 7, 8, 9, 5],
+        [2, 3, 4, 0, 1, 7, 8, 9, 5, 6],
+        [3, 4, 0, 1, 2, 8, 9, 5, 6, 7],
+        [4, 0, 1, 2, 3, 9, 5, 6, 7, 8],
+        [5, 9, 8, 7, 6, 0, 4, 3, 2, 1],
+        [6, 5, 9, 8, 7, 1, 0, 4, 3, 2],
+        [7, 6, 5, 9, 8, 2, 1, 0, 4, 3],
+        [8, 7, 6, 5, 9, 3, 2, 1, 0, 4],
+        [9, 8, 7, 6, 5, 4, 3, 2, 1, 0],
+    ];
+    const P: [[u8; 10]; 8] = [
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        [1, 5, 7, 6, 2, 8, 3, 0, 9, 4],
+        [5, 8, 0, 3, 7, 9, 6, 1, 4, 2],
+        [8, 9, 1, 6, 0, 4, 3, 5, 2, 7],
+        [9, 4, 5, 3, 1, 2, 6, 8, 7, 0],
+        [4, 2, 8, 6, 5, 7, 3, 9, 0, 1],
+        [2, 7, 9, 3, 8, 0, 6, 4, 1, 5],
+        [7, 0, 4, 6, 9, 1, 3, 2, 5, 8],
+    ];
+
+    let digits = digits(text);
+    if digits.len() != 12 {
+        return false;
+    }
+
+    let checksum = digits.iter().rev().enumerate().fold(0, |c, (i, &digit)| {
+        D[c as usize][P[i % 8][digit as usize] as usize]
+    });
+
+    checksum == 0
+}
+
+/// Computes a check digit of the Brazilian CPF and CNPJ.
+fn mod11_check_digit(digits: &[u8], weights: impl Iterator<Item = u32>) -> u8 {
+    let sum: u32 = digits.iter().zip(weights).map(|(&d, w)| d as u32 * w).sum();
+    match sum % 11 {
+        0 | 1 => 0,
+        rest => (11 - rest) as u8,
+    }
+}
+
+/// Validates the two check digits of a Brazilian individual taxpayer number (CPF).
+pub fn is_valid_cpf(text: &str) -> bool {
+    let digits = digits(text);
+    if digits.len() != 11 || digits.iter().all(|&d| d == digits[0]) {
+        return false;
+    }
+
+    mod11_check_digit(&digits[..9], (2..=10).rev()) == digits[9]
+        && mod11_check_digit(&digits[..10], (2..=11).rev()) == digits[10]
+}
+
+/// Validates the two check digits of a Brazilian company registration number (CNPJ).
+pub fn is_valid_cnpj(text: &str) -> bool {
+    let digits = digits(text);
+    if digits.len() != 14 || digits.iter().all(|&d| d == digits[0]) {
+        return false;
+    }
+
+    // Weights cycle from 9 down to 2, ending with 2 on the last digit.
+    let weights = |len: u32| (0..len).map(move |i| (len - 1 - i) % 8 + 2);
+
+    mod11_check_digit(&digits[..12], weights(12)) == digits[12]
+        && mod11_check_digit(&digits[..13], weights(13)) == digits[13]
+}
+
+/// Computes the check digit of a field in a machine readable zone (MRZ).
+fn mrz_check_digit(field: &str) -> Option<u8> {
+    let mut sum = 0u32;
+    for (i, c) in field.bytes().enumerate() {
+        let value = match c {
+            b'0'..=b'9' => c - b'0',
+            b'A'..=b'Z' => c - b'A' + 10,
+            b'<' => 0,
+            _ => return None,
+        };
+        sum += value as u32 * [7, 3, 1][i % 3];
+    }
+    Some((sum % 10) as u8)
+}
+
+/// Validates the check digits of the second line of a passport's machine readable zone.
+///
+/// The line contains the document number, date of birth, and expiry date, each followed by a
+/// check digit, and a composite check digit at the end. Matches may include the first line of
+/// the MRZ, in which case only the last 44 characters are validated.
+pub fn is_valid_passport_mrz(text: &str) -> bool {
+    let text = text.trim_end();
+    let Some(line) = text.get(text.len().saturating_sub(44)..) else {
+        return false;
+    };
+
+    if line.len() != 44 {
+        return false;
+    }
+
+    let check = |field: &str, digit: &str| {
+        mrz_check_digit(field).map_or(false, |c| digit.as_bytes() == [b'0' + c])
+    };
+
+    let composite = format!("{}{}{}", &line[..10], &line[13..20], &line[21..43]);
+
+    check(&line[..9], &line[9..10])
+        && check(&line[13..19], &line[19..20])
+        && check(&line[21..27], &line[27..28])
+        && check(&composite, &line[43..44])
+}
+
+/// Validates that the header of a JSON Web Token is a JSON object with an algorithm.
+pub fn is_valid_jwt(text: &str) -> bool {
+    let Some((header, _)) = text.split_once('.') else {
+        return false;
+    };
+
+    match BASE64URL_NOPAD.decode(header.as_bytes()) {
+        Ok(header) => header.first() == Some(&b'{') && header.windows(5).any(|w| w == b"\"alg\""),
+        Err(_) => false,
+    }
+}
+
+/// Validates that a string looks randomly generated.
+///
+/// Requires upper-case letters, lower-case letters, and digits, as well as a Shannon entropy of at
+/// least four bits per character. This excludes identifiers, paths, and hexadecimal digests.
+pub fn is_high_entropy(text: &str) -> bool {
+    let bytes = text.trim_end_matches('=').as_bytes();
+
+    if !bytes.iter().any(u8::is_ascii_uppercase)
+        || !bytes.iter().any(u8::is_ascii_lowercase)
+        || !bytes.iter().any(u8::is_ascii_digit)
+    {
+        return false;
+    }
+
+    let mut counts = [0usize; 256];
+    for &byte in bytes {
+        counts[byte as usize] += 1;
+    }
+
+    let len = bytes.len() as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+
+    entropy >= 4.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phone() {
+        assert!(is_valid_phone("+44 20 7946 0958"));
+        assert!(is_valid_phone("(030) 1234567"));
+        assert!(!is_valid_phone("+49 123"));
+        assert!(!is_valid_phone("+1 2345 6789 0123 4567"));
+    }
+
+    #[test]
+    fn test_uk_nino() {
+        assert!(is_valid_uk_nino("AB123456C"));
+        assert!(!is_valid_uk_nino("GB123456C"));
+    }
+
+    #[test]
+    fn test_de_tax_id() {
+        assert!(is_valid_de_tax_id("86095742719"));
+        assert!(is_valid_de_tax_id("57 549 285 017"));
+        assert!(!is_valid_de_tax_id("86095742718"));
+        // All digits are distinct.
+        assert!(!is_valid_de_tax_id("12345678903"));
+    }
+
+    #[test]
+    fn test_aadhaar() {
+        assert!(is_valid_aadhaar("2341 2341 2346"));
+        assert!(!is_valid_aadhaar("2341 2341 2345"));
+    }
+
+    #[test]
+    fn test_cpf() {
+        assert!(is_valid_cpf("529.982.247-25"));
+        assert!(!is_valid_cpf("529.982.247-24"));
+        assert!(!is_valid_cpf("111.111.111-11"));
+    }
+
+    #[test]
+    fn test_cnpj() {
+        assert!(is_valid_cnpj("11.222.333/0001-81"));
+        assert!(!is_valid_cnpj("11.222.333/0001-82"));
+    }
+
+    #[test]
+    fn test_passport_mrz() {
+        assert!(is_valid_passport_mrz(
+            "L898902C36UTO7408122F1204159ZE184226B<<<<<10"
+        ));
+        assert!(is_valid_passport_mrz(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\nL898902C36UTO7408122F1204159ZE184226B<<<<<10"
+        ));
+        assert!(!is_valid_passport_mrz(
+            "L898902C37UTO7408122F1204159ZE184226B<<<<<10"
+        ));
+    }
+
+    #[test]
+    fn test_jwt() {
+        assert!(is_valid_jwt(
+            "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiIxMjM0NTY3ODkwIn0.signature"
+        ));
+        assert!(!is_valid_jwt("eyJfoo.eyJbar.baz"));
+    }
+
+    #[test]
+    fn test_high_entropy() {
+        assert!(is_high_entropy("wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY"));
+        assert!(!is_high_entropy("0123456789abcdef0123456789abcdef01234567"));
+        assert!(!is_high_entropy("ThisIsAVeryLongCamelCaseIdentifierName"));
+        assert!(!is_high_entropy(
+            "usr/local/lib/python3.8/site-packages/django"
+        ));
+    }
+}
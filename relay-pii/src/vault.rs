@@ -0,0 +1,272 @@
+//! Reversible pseudonymization of PII.
+//!
+//! The [`Redaction::Encrypt`](crate::Redaction::Encrypt) redaction replaces values with their
+//! ciphertext and marks it with a remark of type [`RemarkType::Encrypted`] in the event's
+//! metadata. Whoever holds the key can restore the original values using [`Vault::reveal`].
+//!
+//! Encryption is deterministic, so equal values always receive the same ciphertext, which makes
+//! it a stable pseudonym. The IV is computed with HMAC-SHA256 over the plain text, which also
+//! authenticates the ciphertext. The plain text is encrypted with a key stream derived from the IV.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use data_encoding::BASE64URL_NOPAD;
+use hmac::{Hmac, Mac};
+use relay_event_schema::processor::{process_chunked_value, Chunk};
+use relay_protocol::{Annotated, Meta, RemarkType, Value};
+use sha2::Sha256;
+
+/// The version of the ciphertext format, stored as first byte of every ciphertext.
+const VERSION: u8 = 1;
+
+/// The length of the synthetic IV in bytes.
+const IV_LEN: usize = 16;
+
+/// An error returned when decrypting a value from the vault.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum VaultError {
+    /// The ciphertext is not valid base64 or decrypts to invalid UTF-8.
+    #[error("invalid ciphertext encoding")]
+    InvalidEncoding,
+    /// The ciphertext was created by an unsupported version.
+    #[error("unsupported ciphertext version")]
+    UnsupportedVersion,
+    /// The ciphertext was encrypted with a different key or has been tampered with.
+    #[error("ciphertext does not match the key")]
+    KeyMismatch,
+}
+
+/// Encrypts and decrypts values with a secret key.
+#[derive(Clone)]
+pub struct Vault {
+    mac_key: [u8; 32],
+    enc_key: [u8; 32],
+}
+
+impl Vault {
+    /// Creates a vault from the secret key.
+    pub fn new(key: &str) -> Self {
+        Self {
+            mac_key: derive_key(key.as_bytes(), b"relay-pii-vault-mac"),
+            enc_key: derive_key(key.as_bytes(), b"relay-pii-vault-enc"),
+        }
+    }
+
+    /// Encrypts a value and returns the ciphertext, encoded in URL-safe base64.
+    pub fn encrypt(&self, value: &str) -> String {
+        let mut mac = new_mac(&self.mac_key);
+        mac.update(value.as_bytes());
+        let tag = mac.finalize().into_bytes();
+        let iv = &tag[..IV_LEN];
+
+        let mut data = Vec::with_capacity(1 + IV_LEN + value.len());
+        data.push(VERSION);
+        data.extend_from_slice(iv);
+        data.extend_from_slice(value.as_bytes());
+        self.apply_key_stream(iv, &mut data[1 + IV_LEN..]);
+
+        BASE64URL_NOPAD.encode(&data)
+    }
+
+    /// Decrypts a ciphertext created by [`encrypt`](Self::encrypt).
+    pub fn decrypt(&self, ciphertext: &str) -> Result<String, VaultError> {
+        let mut data = BASE64URL_NOPAD
+            .decode(ciphertext.as_bytes())
+            .map_err(|_| VaultError::InvalidEncoding)?;
+
+        match data.first() {
+            Some(&VERSION) => (),
+            Some(_) => return Err(VaultError::UnsupportedVersion),
+            None => return Err(VaultError::InvalidEncoding),
+        }
+
+        if data.len() < 1 + IV_LEN {
+            return Err(VaultError::InvalidEncoding);
+        }
+
+        let (iv, plain) = data[1..].split_at_mut(IV_LEN);
+        self.apply_key_stream(iv, plain);
+
+        let mut mac = new_mac(&self.mac_key);
+        mac.update(plain);
+        mac.verify_truncated_left(iv)
+            .map_err(|_| VaultError::KeyMismatch)?;
+
+        String::from_utf8(plain.to_vec()).map_err(|_| VaultError::InvalidEncoding)
+    }
+
+    /// Restores all encrypted values in the given value and its children.
+    ///
+    /// Ciphertexts marked by remarks of type [`RemarkType::Encrypted`] are replaced with their
+    /// original values and the remarks are removed. Returns the number of restored values.
+    pub fn reveal(&self, annotated: &mut Annotated<Value>) -> Result<usize, VaultError> {
+        let Annotated(value, meta) = annotated;
+
+        match value {
+            Some(Value::String(string)) => self.reveal_string(string, meta),
+            Some(Value::Array(items)) => {
+                let mut count = 0;
+                for item in items {
+                    count += self.reveal(item)?;
+                }
+                Ok(count)
+            }
+            Some(Value::Object(items)) => {
+                let mut count = 0;
+                for item in items.values_mut() {
+                    count += self.reveal(item)?;
+                }
+                Ok(count)
+            }
+            _ => Ok(0),
+        }
+    }
+
+    fn reveal_string(&self, value: &mut String, meta: &mut Meta) -> Result<usize, VaultError> {
+        let mut count = 0;
+        let mut error = None;
+
+        process_chunked_value(value, meta, |chunks| {
+            chunks
+                .into_iter()
+                .map(|chunk| {
+                    let revealed = match chunk {
+                        Chunk::Redaction {
+                            ty: RemarkType::Encrypted,
+                            ref text,
+                            ..
+                        } => Some(self.decrypt(text)),
+                        _ => None,
+                    };
+
+                    match revealed {
+                        Some(Ok(text)) => {
+                            count += 1;
+                            Chunk::Text {
+                                text: Cow::Owned(text),
+                            }
+                        }
+                        Some(Err(err)) => {
+                            error.get_or_insert(err);
+                            chunk
+                        }
+                        None => chunk,
+                    }
+                })
+                .collect()
+        });
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(count),
+        }
+    }
+
+    fn apply_key_stream(&self, iv: &[u8], data: &mut [u8]) {
+        for (counter, block) in data.chunks_mut(32).enumerate() {
+            let mut mac = new_mac(&self.enc_key);
+            mac.update(iv);
+            mac.update(&(counter as u32).to_be_bytes());
+
+            let stream = mac.finalize().into_bytes();
+            for (byte, key) in block.iter_mut().zip(stream) {
+                *byte ^= key;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Vault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vault").finish_non_exhaustive()
+    }
+}
+
+fn new_mac(key: &[u8]) -> Hmac<Sha256> {
+    Hmac::<Sha256>::new_from_slice(key).unwrap()
+}
+
+fn derive_key(key: &[u8], label: &[u8]) -> [u8; 32] {
+    let mut mac = new_mac(key);
+    mac.update(label);
+    mac.finalize().into_bytes().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use relay_protocol::Remark;
+
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let vault = Vault::new("secret");
+
+        let ciphertext = vault.encrypt("jane@example.com");
+        assert_eq!(vault.decrypt(&ciphertext).unwrap(), "jane@example.com");
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let vault = Vault::new("secret");
+
+        assert_eq!(
+            vault.encrypt("jane@example.com"),
+            vault.encrypt("jane@example.com")
+        );
+        assert_ne!(
+            vault.encrypt("jane@example.com"),
+            vault.encrypt("john@example.com")
+        );
+        assert_ne!(
+            vault.encrypt("jane@example.com"),
+            Vault::new("other").encrypt("jane@example.com")
+        );
+    }
+
+    #[test]
+    fn test_decrypt_wrong_key() {
+        let ciphertext = Vault::new("secret").encrypt("jane@example.com");
+
+        assert_eq!(
+            Vault::new("other").decrypt(&ciphertext),
+            Err(VaultError::KeyMismatch)
+        );
+        assert_eq!(
+            Vault::new("secret").decrypt("not base64!"),
+            Err(VaultError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn test_reveal() {
+        let vault = Vault::new("secret");
+        let ciphertext = vault.encrypt("jane@example.com");
+
+        let text = format!("mail from {ciphertext}");
+        let mut meta = Meta::default();
+        meta.add_remark(Remark::with_range(
+            RemarkType::Encrypted,
+            "@email:encrypt",
+            (10, text.len()),
+        ));
+
+        let mut value = Annotated::<Value>::from_json(r#"{"user": {"email": null}}"#).unwrap();
+        if let Some(Value::Object(ref mut user)) = value.value_mut() {
+            user.insert(
+                "email".to_owned(),
+                Annotated(Some(Value::String(text)), meta),
+            );
+        }
+
+        assert_eq!(vault.reveal(&mut value), Ok(1));
+
+        let Some(Value::Object(user)) = value.value() else {
+            panic!("expected an object");
+        };
+        let email = user.get("email").unwrap();
+        assert_eq!(email.as_str(), Some("mail from jane@example.com"));
+        assert_eq!(email.meta().iter_remarks().count(), 0);
+    }
+}
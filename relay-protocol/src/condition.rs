@@ -2,6 +2,10 @@
 //!
 //! The root type is [`RuleCondition`].
 
+use std::fmt;
+
+use once_cell::sync::OnceCell;
+use regex::Regex;
 use relay_common::glob3::GlobPatterns;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -186,6 +190,189 @@ impl GlobCondition {
     }
 }
 
+/// A condition that matches an anchored regular expression.
+///
+/// The pattern must match the entire string value of the field. The regular expression is compiled
+/// lazily on first use and cached for subsequent checks. Invalid patterns never match and make the
+/// condition [unsupported](RuleCondition::supported).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RegexCondition {
+    /// Path of the field that should match the value.
+    pub name: String,
+    /// The regular expression to match.
+    pub value: String,
+    #[serde(skip)]
+    regex: OnceCell<Option<Regex>>,
+}
+
+impl RegexCondition {
+    /// Creates a condition that matches an anchored regular expression.
+    pub fn new(field: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            name: field.into(),
+            value: pattern.into(),
+            regex: OnceCell::new(),
+        }
+    }
+
+    fn regex(&self) -> Option<&Regex> {
+        self.regex
+            .get_or_init(|| Regex::new(&format!("^(?:{})$", self.value)).ok())
+            .as_ref()
+    }
+
+    fn supported(&self) -> bool {
+        self.regex().is_some()
+    }
+
+    fn matches<T>(&self, instance: &T) -> bool
+    where
+        T: Getter + ?Sized,
+    {
+        match (instance.get_value(self.name.as_str()), self.regex()) {
+            (Some(Val::String(s)), Some(regex)) => regex.is_match(s),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for RegexCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegexCondition")
+            .field("name", &self.name)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl PartialEq for RegexCondition {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.value == other.value
+    }
+}
+
+/// A condition that checks if a value is contained in a list of values.
+///
+/// In contrast to [`EqCondition`], this supports strings, numbers, booleans, and UUIDs in the same
+/// list. Numbers are compared by value regardless of their representation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InCondition {
+    /// Path of the field that should match the value.
+    pub name: String,
+    /// The list of values to check against.
+    pub value: Vec<Value>,
+}
+
+impl InCondition {
+    /// Creates a condition that checks if a value is contained in a list of values.
+    pub fn new<I, V>(field: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        Self {
+            name: field.into(),
+            value: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    fn matches<T>(&self, instance: &T) -> bool
+    where
+        T: Getter + ?Sized,
+    {
+        let Some(field) = instance.get_value(self.name.as_str()) else {
+            return false;
+        };
+
+        self.value.iter().any(|value| match value {
+            Value::Bool(b) => field.as_bool() == Some(*b),
+            Value::String(s) => match field {
+                Val::String(f) => f == s.as_str(),
+                Val::Uuid(f) => s.parse().ok() == Some(f),
+                _ => false,
+            },
+            Value::Number(n) => {
+                if let (Some(a), Some(b)) = (field.as_i64(), n.as_i64()) {
+                    a == b
+                } else if let (Some(a), Some(b)) = (field.as_u64(), n.as_u64()) {
+                    a == b
+                } else if let (Some(a), Some(b)) = (field.as_f64(), n.as_f64()) {
+                    a == b
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        })
+    }
+}
+
+/// A condition that checks if a numeric value is within a range.
+///
+/// The range is half-open: it includes `min` and excludes `max`. Either bound can be omitted to
+/// create a range that is unbounded on that side.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangeCondition {
+    /// Path of the field that should match the value.
+    pub name: String,
+    /// The inclusive lower bound of the range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// The exclusive upper bound of the range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+impl RangeCondition {
+    /// Creates a condition that checks if a numeric value is within a range.
+    pub fn new(field: impl Into<String>, min: Option<f64>, max: Option<f64>) -> Self {
+        Self {
+            name: field.into(),
+            min,
+            max,
+        }
+    }
+
+    fn matches<T>(&self, instance: &T) -> bool
+    where
+        T: Getter + ?Sized,
+    {
+        let Some(value) = instance
+            .get_value(self.name.as_str())
+            .and_then(|v| v.as_f64())
+        else {
+            return false;
+        };
+
+        self.min.map_or(true, |min| value >= min) && self.max.map_or(true, |max| value < max)
+    }
+}
+
+/// A condition that checks if a field exists.
+///
+/// This matches for fields with any value, including arrays accessed through
+/// [`Getter::get_iter`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExistsCondition {
+    /// Path of the field that should exist.
+    pub name: String,
+}
+
+impl ExistsCondition {
+    /// Creates a condition that checks if a field exists.
+    pub fn new(field: impl Into<String>) -> Self {
+        Self { name: field.into() }
+    }
+
+    fn matches<T>(&self, instance: &T) -> bool
+    where
+        T: Getter + ?Sized,
+    {
+        instance.get_value(self.name.as_str()).is_some()
+            || instance.get_iter(self.name.as_str()).is_some()
+    }
+}
+
 /// A type that can be converted to a list of strings.
 pub trait IntoStrings {
     /// Creates a list of strings from this type.
@@ -307,6 +494,67 @@ impl NotCondition {
     }
 }
 
+/// Applies a condition to the items of an array and matches if **any** item matches.
+///
+/// The array is accessed through [`Getter::get_iter`]. The inner condition is evaluated on every
+/// item with paths relative to the item. This condition does not match empty or missing arrays.
+///
+/// See [`RuleCondition::any`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnyCondition {
+    /// Path of the array.
+    pub name: String,
+    /// An inner rule to apply to the items.
+    pub inner: Box<RuleCondition>,
+}
+
+impl AnyCondition {
+    fn supported(&self) -> bool {
+        self.inner.supported()
+    }
+
+    fn matches<T>(&self, value: &T) -> bool
+    where
+        T: Getter + ?Sized,
+    {
+        match value.get_iter(self.name.as_str()) {
+            Some(mut items) => items.any(|item| self.inner.matches(item)),
+            None => false,
+        }
+    }
+}
+
+/// Applies a condition to the items of an array and matches if **all** items match.
+///
+/// The array is accessed through [`Getter::get_iter`]. The inner condition is evaluated on every
+/// item with paths relative to the item. This condition matches empty arrays, but it does not match
+/// if the array is missing.
+///
+/// See [`RuleCondition::every`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AllCondition {
+    /// Path of the array.
+    pub name: String,
+    /// An inner rule to apply to the items.
+    pub inner: Box<RuleCondition>,
+}
+
+impl AllCondition {
+    fn supported(&self) -> bool {
+        self.inner.supported()
+    }
+
+    fn matches<T>(&self, value: &T) -> bool
+    where
+        T: Getter + ?Sized,
+    {
+        match value.get_iter(self.name.as_str()) {
+            Some(mut items) => items.all(|item| self.inner.matches(item)),
+            None => false,
+        }
+    }
+}
+
 /// A condition that can be evaluated on structured data.
 ///
 /// The basic conditions are [`eq`](Self::eq), [`glob`](Self::glob), and the comparison operators.
@@ -314,8 +562,13 @@ impl NotCondition {
 /// If the field's value [matches](Self::matches) the values declared in the rule, the condition
 /// returns `true`.
 ///
+/// Further conditions check values against a [regular expression](Self::regex), a
+/// [list of values](Self::in_list), a [numeric range](Self::range), or check whether a field
+/// [exists](Self::exists).
+///
 /// Conditions can be combined with the logical operators [`and`](Self::and), [`or`](Self::or), and
-/// [`not` (negate)](Self::negate).
+/// [`not` (negate)](Self::negate). The quantifiers [`any`](Self::any) and [`every`](Self::every)
+/// apply a condition to the items of an array.
 ///
 /// # Data Access
 ///
@@ -410,6 +663,50 @@ pub enum RuleCondition {
     /// ```
     Glob(GlobCondition),
 
+    /// A condition that matches an anchored regular expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// let condition = RuleCondition::regex("obj.name", r"error: \d+");
+    /// ```
+    Regex(RegexCondition),
+
+    /// A condition that checks if a value is contained in a list of values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// let condition = RuleCondition::in_list("obj.status_code", [500, 502, 503]);
+    /// ```
+    In(InCondition),
+
+    /// A condition that checks if a numeric value is within a half-open range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// let condition = RuleCondition::range("obj.length", 10.0, 20.0);
+    /// ```
+    Range(RangeCondition),
+
+    /// A condition that checks if a field exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// let condition = RuleCondition::exists("obj.name");
+    /// ```
+    Exists(ExistsCondition),
+
     /// Combines multiple conditions using logical OR.
     ///
     /// # Example
@@ -445,6 +742,28 @@ pub enum RuleCondition {
     /// ```
     Not(NotCondition),
 
+    /// Matches if any item of an array matches the inner condition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// let condition = RuleCondition::any("obj.items", RuleCondition::eq("status", "invalid"));
+    /// ```
+    Any(AnyCondition),
+
+    /// Matches if all items of an array match the inner condition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// let condition = RuleCondition::every("obj.items", RuleCondition::eq("status", "invalid"));
+    /// ```
+    All(AllCondition),
+
     /// An unsupported condition for future compatibility.
     #[serde(other)]
     Unsupported,
@@ -572,6 +891,120 @@ impl RuleCondition {
         Self::Lte(LteCondition::new(field, value))
     }
 
+    /// Creates a condition that matches an anchored regular expression.
+    ///
+    /// The pattern must match the entire value. Use `.*` to match a prefix or suffix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// let condition = RuleCondition::regex("obj.name", r"error: \d+");
+    /// ```
+    pub fn regex(field: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self::Regex(RegexCondition::new(field, pattern))
+    }
+
+    /// Creates a condition that checks if a value is contained in a list of values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// // Matches any of the given numbers:
+    /// let condition = RuleCondition::in_list("obj.status_code", [500, 502, 503]);
+    ///
+    /// // Matches any of the given strings:
+    /// let condition = RuleCondition::in_list("obj.status", ["invalid", "unknown"]);
+    /// ```
+    pub fn in_list<I, V>(field: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        Self::In(InCondition::new(field, values))
+    }
+
+    /// Creates a condition that checks if a numeric value is within a range.
+    ///
+    /// The range includes `min` and excludes `max`. Pass `None` to leave a side unbounded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// // Matches values from 10 up to, but not including, 20:
+    /// let condition = RuleCondition::range("obj.length", 10.0, 20.0);
+    ///
+    /// // Matches values of at least 10:
+    /// let condition = RuleCondition::range("obj.length", 10.0, None);
+    /// ```
+    pub fn range(
+        field: impl Into<String>,
+        min: impl Into<Option<f64>>,
+        max: impl Into<Option<f64>>,
+    ) -> Self {
+        Self::Range(RangeCondition::new(field, min.into(), max.into()))
+    }
+
+    /// Creates a condition that checks if a field exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// let condition = RuleCondition::exists("obj.name");
+    /// ```
+    pub fn exists(field: impl Into<String>) -> Self {
+        Self::Exists(ExistsCondition::new(field))
+    }
+
+    /// Creates a condition that matches if any item of an array matches the inner condition.
+    ///
+    /// Paths in the inner condition are relative to the items of the array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// let condition = RuleCondition::any(
+    ///     "event.exception.values",
+    ///     RuleCondition::glob("type", "ChunkLoadError"),
+    /// );
+    /// ```
+    pub fn any(field: impl Into<String>, inner: RuleCondition) -> Self {
+        Self::Any(AnyCondition {
+            name: field.into(),
+            inner: Box::new(inner),
+        })
+    }
+
+    /// Creates a condition that matches if all items of an array match the inner condition.
+    ///
+    /// Paths in the inner condition are relative to the items of the array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use relay_protocol::RuleCondition;
+    ///
+    /// let condition = RuleCondition::every(
+    ///     "event.exception.values",
+    ///     RuleCondition::eq("mechanism.handled", true),
+    /// );
+    /// ```
+    pub fn every(field: impl Into<String>, inner: RuleCondition) -> Self {
+        Self::All(AllCondition {
+            name: field.into(),
+            inner: Box::new(inner),
+        })
+    }
+
     /// Combines this condition and another condition with a logical AND operator.
     ///
     /// The short-hand operator for this combinator is `&`.
@@ -650,11 +1083,42 @@ impl RuleCondition {
             | RuleCondition::Gt(_)
             | RuleCondition::Lt(_)
             | RuleCondition::Eq(_)
-            | RuleCondition::Glob(_) => true,
+            | RuleCondition::Glob(_)
+            | RuleCondition::In(_)
+            | RuleCondition::Range(_)
+            | RuleCondition::Exists(_) => true,
+            RuleCondition::Regex(condition) => condition.supported(),
             // dig down for embedded conditions
             RuleCondition::And(rules) => rules.supported(),
             RuleCondition::Or(rules) => rules.supported(),
             RuleCondition::Not(rule) => rule.supported(),
+            RuleCondition::Any(rule) => rule.supported(),
+            RuleCondition::All(rule) => rule.supported(),
+        }
+    }
+
+    /// Returns `true` if the condition or any of its nested conditions reads the given field.
+    ///
+    /// Conditions nested in [`Any`](Self::Any) and [`All`](Self::All) read fields relative to the
+    /// items of an array, so only the path of the array itself is compared.
+    pub fn references(&self, field: &str) -> bool {
+        match self {
+            RuleCondition::Eq(condition) => condition.name == field,
+            RuleCondition::Lte(condition) => condition.name == field,
+            RuleCondition::Gte(condition) => condition.name == field,
+            RuleCondition::Gt(condition) => condition.name == field,
+            RuleCondition::Lt(condition) => condition.name == field,
+            RuleCondition::Glob(condition) => condition.name == field,
+            RuleCondition::Regex(condition) => condition.name == field,
+            RuleCondition::In(condition) => condition.name == field,
+            RuleCondition::Range(condition) => condition.name == field,
+            RuleCondition::Exists(condition) => condition.name == field,
+            RuleCondition::And(conditions) => conditions.inner.iter().any(|c| c.references(field)),
+            RuleCondition::Or(conditions) => conditions.inner.iter().any(|c| c.references(field)),
+            RuleCondition::Not(condition) => condition.inner.references(field),
+            RuleCondition::Any(condition) => condition.name == field,
+            RuleCondition::All(condition) => condition.name == field,
+            RuleCondition::Unsupported => false,
         }
     }
 
@@ -670,9 +1134,15 @@ impl RuleCondition {
             RuleCondition::Gt(condition) => condition.matches(value),
             RuleCondition::Lt(condition) => condition.matches(value),
             RuleCondition::Glob(condition) => condition.matches(value),
+            RuleCondition::Regex(condition) => condition.matches(value),
+            RuleCondition::In(condition) => condition.matches(value),
+            RuleCondition::Range(condition) => condition.matches(value),
+            RuleCondition::Exists(condition) => condition.matches(value),
             RuleCondition::And(conditions) => conditions.matches(value),
             RuleCondition::Or(conditions) => conditions.matches(value),
             RuleCondition::Not(condition) => condition.matches(value),
+            RuleCondition::Any(condition) => condition.matches(value),
+            RuleCondition::All(condition) => condition.matches(value),
             RuleCondition::Unsupported => false,
         }
     }
@@ -736,6 +1206,12 @@ mod tests {
         }
     }
 
+    fn json(value: Value) -> crate::Value {
+        crate::Annotated::<crate::Value>::from(value)
+            .into_value()
+            .unwrap()
+    }
+
     #[test]
     fn deserialize() {
         let serialized_rules = r#"[
@@ -1093,4 +1569,167 @@ mod tests {
             assert!(!condition.matches(&dsc), "{failure_name}");
         }
     }
+
+    #[test]
+    fn test_deserialize_extended() {
+        let json = r#"[
+            {"op": "regex", "name": "field_1", "value": "\\d+"},
+            {"op": "in", "name": "field_2", "value": [1, "a", true]},
+            {"op": "range", "name": "field_3", "min": 1.5},
+            {"op": "exists", "name": "field_4"},
+            {"op": "any", "name": "field_5", "inner": {"op": "exists", "name": "a"}},
+            {"op": "all", "name": "field_6", "inner": {"op": "exists", "name": "b"}}
+        ]"#;
+
+        let rules: Vec<RuleCondition> = serde_json::from_str(json).unwrap();
+        assert!(rules.iter().all(RuleCondition::supported));
+        assert_eq!(
+            rules,
+            vec![
+                RuleCondition::regex("field_1", r"\d+"),
+                RuleCondition::in_list("field_2", [Value::from(1), "a".into(), true.into()]),
+                RuleCondition::range("field_3", 1.5, None),
+                RuleCondition::exists("field_4"),
+                RuleCondition::any("field_5", RuleCondition::exists("a")),
+                RuleCondition::every("field_6", RuleCondition::exists("b")),
+            ]
+        );
+
+        let serialized = serde_json::to_value(&rules).unwrap();
+        assert_eq!(serialized, serde_json::from_str::<Value>(json).unwrap());
+    }
+
+    #[test]
+    fn test_regex() {
+        let dsc = mock_dsc();
+
+        assert!(RuleCondition::regex("trace.release", r"1\.\d\.1").matches(&dsc));
+        assert!(RuleCondition::regex("trace.transaction", "trans.*").matches(&dsc));
+        // Patterns are anchored on both sides.
+        assert!(!RuleCondition::regex("trace.transaction", "trans").matches(&dsc));
+        assert!(!RuleCondition::regex("trace.transaction", "action1").matches(&dsc));
+        assert!(!RuleCondition::regex("trace.missing", ".*").matches(&dsc));
+
+        let invalid = RuleCondition::regex("trace.transaction", "(");
+        assert!(!invalid.supported());
+        assert!(!invalid.matches(&dsc));
+    }
+
+    #[test]
+    fn test_in_list() {
+        let dsc = mock_dsc();
+
+        assert!(RuleCondition::in_list("trace.environment", ["prod", "debug"]).matches(&dsc));
+        assert!(!RuleCondition::in_list("trace.environment", ["prod", "DEBUG"]).matches(&dsc));
+        assert!(!RuleCondition::in_list("trace.environment", Vec::<Value>::new()).matches(&dsc));
+
+        let value = json(serde_json::json!({"code": 503, "ratio": 0.5, "flag": true}));
+        assert!(RuleCondition::in_list("code", [500, 503]).matches(&value));
+        assert!(RuleCondition::in_list("ratio", [0.5]).matches(&value));
+        assert!(RuleCondition::in_list("flag", [true]).matches(&value));
+        assert!(!RuleCondition::in_list("code", ["503"]).matches(&value));
+    }
+
+    #[test]
+    fn test_range() {
+        let value = json(serde_json::json!({"a": 10, "b": 2.5, "c": "10"}));
+
+        assert!(RuleCondition::range("a", 10.0, 20.0).matches(&value));
+        assert!(!RuleCondition::range("a", 0.0, 10.0).matches(&value));
+        assert!(RuleCondition::range("a", None, 11.0).matches(&value));
+        assert!(RuleCondition::range("b", 2.0, None).matches(&value));
+        assert!(RuleCondition::range("b", None, None).matches(&value));
+        assert!(!RuleCondition::range("c", None, None).matches(&value));
+        assert!(!RuleCondition::range("d", None, None).matches(&value));
+    }
+
+    #[test]
+    fn test_exists() {
+        let dsc = mock_dsc();
+        assert!(RuleCondition::exists("trace.release").matches(&dsc));
+        assert!(!RuleCondition::exists("trace.replay_id").matches(&dsc));
+
+        let value = json(serde_json::json!({"items": [], "nested": {"a": 1}}));
+        assert!(RuleCondition::exists("items").matches(&value));
+        assert!(RuleCondition::exists("nested.a").matches(&value));
+        assert!(!RuleCondition::exists("nested.b").matches(&value));
+    }
+
+    #[test]
+    fn test_quantifiers() {
+        let value = json(serde_json::json!({
+            "exceptions": [
+                {"type": "TypeError", "handled": true},
+                {"type": "ChunkLoadError", "handled": true},
+            ],
+            "empty": [],
+            "scalars": [1, 2, 3],
+        }));
+
+        let conditions = [
+            (
+                "any glob",
+                true,
+                RuleCondition::any("exceptions", RuleCondition::glob("type", "ChunkLoad*")),
+            ),
+            (
+                "any none",
+                false,
+                RuleCondition::any("exceptions", RuleCondition::eq("type", "ValueError")),
+            ),
+            (
+                "all",
+                true,
+                RuleCondition::every("exceptions", RuleCondition::eq("handled", true)),
+            ),
+            (
+                "all mismatch",
+                false,
+                RuleCondition::every("exceptions", RuleCondition::glob("type", "Type*")),
+            ),
+            (
+                "any empty",
+                false,
+                RuleCondition::any("empty", RuleCondition::all()),
+            ),
+            (
+                "all empty",
+                true,
+                RuleCondition::every("empty", RuleCondition::never()),
+            ),
+            (
+                "any missing",
+                false,
+                RuleCondition::any("missing", RuleCondition::all()),
+            ),
+            (
+                "all missing",
+                false,
+                RuleCondition::every("missing", RuleCondition::all()),
+            ),
+            (
+                "scalars",
+                true,
+                RuleCondition::every("scalars", RuleCondition::range("", 1.0, 4.0)),
+            ),
+        ];
+
+        for (rule_test_name, expected, condition) in conditions.iter() {
+            let failure_name = format!("Failed on test: '{rule_test_name}'!!!");
+            assert!(condition.matches(&value) == *expected, "{failure_name}");
+        }
+    }
+
+    #[test]
+    fn test_references() {
+        let condition = RuleCondition::eq("event.release", "1.0")
+            & !RuleCondition::glob("event.grouping_hash", "abc*");
+        assert!(condition.references("event.grouping_hash"));
+        assert!(condition.references("event.release"));
+        assert!(!condition.references("event.transaction"));
+
+        let condition = RuleCondition::any("event.exceptions", RuleCondition::eq("value", "x"));
+        assert!(condition.references("event.exceptions"));
+        assert!(!condition.references("value"));
+    }
 }
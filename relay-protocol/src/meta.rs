@@ -29,7 +29,9 @@ pub enum RemarkType {
     /// The original value was replaced through pseudonymization.
     #[serde(rename = "p")]
     Pseudonymized,
-    /// The original value was encrypted (not implemented yet).
+    /// The original value was encrypted.
+    ///
+    /// The ciphertext replaces the original value within the range of the remark.
     #[serde(rename = "e")]
     Encrypted,
 }
@@ -195,4 +195,20 @@ pub trait IntoValue: Debug + Empty {
 pub trait Getter {
     /// Returns the serialized value of a field pointed to by a `path`.
     fn get_value(&self, path: &str) -> Option<Val<'_>>;
+
+    /// Returns an iterator over the items of an array pointed to by a `path`.
+    ///
+    /// Every item is exposed as a [`Getter`] of its own. Paths into items are relative to the item
+    /// and do not have a root component. For example, the `type` of every exception in an event is
+    /// accessed with the path `type` on the items of `event.exception.values`.
+    ///
+    /// Returns `None` if the path does not point to an array. The default implementation does not
+    /// support any arrays.
+    fn get_iter(&self, path: &str) -> Option<GetterIter<'_>> {
+        let _ = path;
+        None
+    }
 }
+
+/// An iterator over the items of an array, returned by [`Getter::get_iter`].
+pub type GetterIter<'a> = Box<dyn Iterator<Item = &'a dyn Getter> + 'a>;
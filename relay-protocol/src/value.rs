@@ -9,6 +9,7 @@ use uuid::Uuid;
 
 use crate::annotated::Annotated;
 use crate::meta::Meta;
+use crate::traits::{Getter, GetterIter};
 
 /// Alias for typed arrays.
 pub type Array<T> = Vec<Annotated<T>>;
@@ -502,6 +503,57 @@ impl<'a> From<&'a Value> for Val<'a> {
     }
 }
 
+/// Returns the value nested at a relative path of `.`-separated object keys.
+///
+/// An empty path returns the value itself. Dots in keys are escaped with `\.`.
+fn value_at<'a>(mut value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+
+    let escaped = path.replace("\\.", "\0");
+    for key in escaped.split('.') {
+        let Value::Object(map) = value else {
+            return None;
+        };
+        value = map.get(&key.replace('\0', "."))?.value()?;
+    }
+
+    Some(value)
+}
+
+/// Values are addressed by relative paths without a root component.
+///
+/// This allows to evaluate conditions on the items of arrays, see [`Getter::get_iter`]. The empty
+/// path refers to the value itself.
+impl Getter for Value {
+    fn get_value(&self, path: &str) -> Option<Val<'_>> {
+        Some(value_at(self, path)?.into())
+    }
+
+    fn get_iter(&self, path: &str) -> Option<GetterIter<'_>> {
+        let Value::Array(items) = value_at(self, path)? else {
+            return None;
+        };
+
+        Some(Box::new(
+            items
+                .iter()
+                .filter_map(Annotated::value)
+                .map(|item| item as &dyn Getter),
+        ))
+    }
+}
+
+/// Strings are addressed by the empty path, which refers to the string itself.
+///
+/// This allows to evaluate conditions on the items of string arrays, see [`Getter::get_iter`].
+impl Getter for String {
+    fn get_value(&self, path: &str) -> Option<Val<'_>> {
+        path.is_empty().then(|| self.as_str().into())
+    }
+}
+
 impl PartialEq for Val<'_> {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
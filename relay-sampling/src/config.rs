@@ -233,7 +233,7 @@ impl TimeRange {
 }
 
 /// Specifies how to interpolate sample rates for rules with bounded time window.
-#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "type")]
 pub enum DecayingFunction {
@@ -247,6 +247,45 @@ pub enum DecayingFunction {
         decayed_value: f64,
     },
 
+    /// Apply exponential decay of the sample rate from the beginning of the time window.
+    ///
+    /// The rule will start to apply with the configured sample rate at the beginning of the time
+    /// window. Afterwards, the difference to `decayed_value` halves every `half_life` seconds.
+    #[serde(rename_all = "camelCase")]
+    Exponential {
+        /// The value the sample rate approaches over time.
+        decayed_value: f64,
+        /// The number of seconds after which the sample rate is halfway to `decayed_value`.
+        half_life: u64,
+    },
+
+    /// Decrease the sample rate in equal steps over the time window.
+    ///
+    /// The time window is divided into `steps + 1` equal intervals. The first interval applies the
+    /// configured sample rate and every following interval lowers it by the same amount, so that
+    /// the last interval applies `decayed_value`.
+    #[serde(rename_all = "camelCase")]
+    Step {
+        /// The value applied in the last interval of the time window.
+        decayed_value: f64,
+        /// The number of times the sample rate is lowered.
+        steps: u32,
+    },
+
+    /// Apply sample rates defined at specific points in time.
+    ///
+    /// Between two points, the sample rate is interpolated linearly. Before the first point, the
+    /// configured sample rate of the rule applies, and after the last point, the value of the last
+    /// point applies until the end of the time window.
+    #[serde(rename_all = "camelCase")]
+    Schedule {
+        /// The points of the schedule in ascending order of time.
+        ///
+        /// Points are sorted by time when the schedule is deserialized.
+        #[serde(deserialize_with = "deserialize_schedule_points")]
+        points: Vec<SchedulePoint>,
+    },
+
     /// Apply the sample rate of the rule for the full time window with hard cutoff.
     #[default]
     Constant,
@@ -262,29 +301,106 @@ impl DecayingFunction {
     ) -> Option<f64> {
         match self {
             DecayingFunction::Linear { decayed_value } => {
-                let (Some(start), Some(end)) = (time_range.start, time_range.end) else {
-                    return None;
-                };
+                let progress_ratio = progress_ratio(now, time_range)?;
 
                 if sample_rate < *decayed_value {
                     return None;
                 }
 
-                let now = now.timestamp() as f64;
-                let start = start.timestamp() as f64;
-                let end = end.timestamp() as f64;
-
-                let progress_ratio = ((now - start) / (end - start)).clamp(0.0, 1.0);
-
                 // This interval will always be < 0.
                 let interval = decayed_value - sample_rate;
                 Some(sample_rate + (interval * progress_ratio))
             }
+            DecayingFunction::Exponential {
+                decayed_value,
+                half_life,
+            } => {
+                let start = time_range.start?;
+
+                if sample_rate < *decayed_value || *half_life == 0 {
+                    return None;
+                }
+
+                let elapsed = (now.timestamp() - start.timestamp()).max(0) as f64;
+                let remaining = 0.5f64.powf(elapsed / *half_life as f64);
+                Some(decayed_value + (sample_rate - decayed_value) * remaining)
+            }
+            DecayingFunction::Step {
+                decayed_value,
+                steps,
+            } => {
+                let progress_ratio = progress_ratio(now, time_range)?;
+
+                if sample_rate < *decayed_value || *steps == 0 {
+                    return None;
+                }
+
+                let steps = *steps as f64;
+                let step = (progress_ratio * (steps + 1.0)).floor().min(steps);
+                let interval = decayed_value - sample_rate;
+                Some(sample_rate + (interval * step / steps))
+            }
+            DecayingFunction::Schedule { points } => {
+                let Some(first) = points.first() else {
+                    return Some(sample_rate);
+                };
+
+                if now < first.time {
+                    return Some(sample_rate);
+                }
+
+                for window in points.windows(2) {
+                    let [from, to] = window else { continue };
+                    if now < to.time {
+                        let range = TimeRange {
+                            start: Some(from.time),
+                            end: Some(to.time),
+                        };
+                        let progress_ratio = progress_ratio(now, range)?;
+                        return Some(from.value + (to.value - from.value) * progress_ratio);
+                    }
+                }
+
+                points.last().map(|point| point.value)
+            }
             DecayingFunction::Constant => Some(sample_rate),
         }
     }
 }
 
+/// Returns the progress of `now` in a closed time range as ratio between `0.0` and `1.0`.
+fn progress_ratio(now: DateTime<Utc>, time_range: TimeRange) -> Option<f64> {
+    let (Some(start), Some(end)) = (time_range.start, time_range.end) else {
+        return None;
+    };
+
+    let now = now.timestamp() as f64;
+    let start = start.timestamp() as f64;
+    let end = end.timestamp() as f64;
+
+    Some(((now - start) / (end - start)).clamp(0.0, 1.0))
+}
+
+/// A point of a [`DecayingFunction::Schedule`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulePoint {
+    /// The time at which the value applies.
+    pub time: DateTime<Utc>,
+    /// The sample rate or factor that applies at this time.
+    pub value: f64,
+}
+
+/// Deserializes the points of a schedule and sorts them in ascending order of time.
+fn deserialize_schedule_points<'de, D>(deserializer: D) -> Result<Vec<SchedulePoint>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let mut points = Vec::<SchedulePoint>::deserialize(deserializer)?;
+    points.sort_by_key(|point| point.time);
+    Ok(points)
+}
+
 /// Defines which population of items a dynamic sample rate applies to.
 ///
 /// SDKs with client side sampling reduce the number of items sent to Relay, where dynamic sampling
@@ -323,7 +439,7 @@ impl Default for SamplingMode {
 
 #[cfg(test)]
 mod tests {
-    use chrono::TimeZone;
+    use chrono::{Duration, TimeZone};
 
     use super::*;
 
@@ -594,4 +710,172 @@ mod tests {
             .adjust_sample_rate(1.0, halfway, time_range_without_end)
             .is_none());
     }
+
+    /// Checks if the sample rate decays exponentially if `DecayingFunction::Exponential` is set.
+    #[test]
+    fn test_decay_fn_exponential() {
+        let decaying_fn = DecayingFunction::Exponential {
+            decayed_value: 0.2,
+            half_life: 3600,
+        };
+        let start = Utc.with_ymd_and_hms(1970, 10, 10, 0, 0, 0).unwrap();
+        let time_range = TimeRange {
+            start: Some(start),
+            end: None,
+        };
+
+        assert_eq!(
+            decaying_fn.adjust_sample_rate(1.0, start, time_range),
+            Some(1.0)
+        );
+
+        // After one half-life, the sample rate is halfway to the decayed value.
+        assert_eq!(
+            decaying_fn.adjust_sample_rate(1.0, start + Duration::hours(1), time_range),
+            Some(0.6000000000000001)
+        );
+
+        // After two half-lives, another half of the remaining difference is gone.
+        assert_eq!(
+            decaying_fn.adjust_sample_rate(1.0, start + Duration::hours(2), time_range),
+            Some(0.4)
+        );
+
+        // Without a start, the exponential decay shouldn't be run.
+        assert!(decaying_fn
+            .adjust_sample_rate(1.0, start, TimeRange::default())
+            .is_none());
+
+        // The sample rate must not be lower than the decayed value.
+        assert!(decaying_fn
+            .adjust_sample_rate(0.1, start, time_range)
+            .is_none());
+    }
+
+    /// Checks if the sample rate decreases in steps if `DecayingFunction::Step` is set.
+    #[test]
+    fn test_decay_fn_step() {
+        let decaying_fn = DecayingFunction::Step {
+            decayed_value: 0.25,
+            steps: 3,
+        };
+        let start = Utc.with_ymd_and_hms(1970, 10, 10, 0, 0, 0).unwrap();
+        let time_range = TimeRange {
+            start: Some(start),
+            end: Some(start + Duration::hours(4)),
+        };
+
+        let sample_rate_at = |minutes| {
+            decaying_fn.adjust_sample_rate(1.0, start + Duration::minutes(minutes), time_range)
+        };
+
+        assert_eq!(sample_rate_at(0), Some(1.0));
+        assert_eq!(sample_rate_at(59), Some(1.0));
+        assert_eq!(sample_rate_at(60), Some(0.75));
+        assert_eq!(sample_rate_at(150), Some(0.5));
+        assert_eq!(sample_rate_at(180), Some(0.25));
+        assert_eq!(sample_rate_at(239), Some(0.25));
+
+        // Without steps, the decay shouldn't be run.
+        let decaying_fn = DecayingFunction::Step {
+            decayed_value: 0.25,
+            steps: 0,
+        };
+        assert!(decaying_fn
+            .adjust_sample_rate(1.0, start, time_range)
+            .is_none());
+    }
+
+    /// Checks if the sample rate follows the points if `DecayingFunction::Schedule` is set.
+    #[test]
+    fn test_decay_fn_schedule() {
+        let start = Utc.with_ymd_and_hms(1970, 10, 10, 0, 0, 0).unwrap();
+        let decaying_fn = DecayingFunction::Schedule {
+            points: vec![
+                SchedulePoint {
+                    time: start + Duration::hours(1),
+                    value: 0.8,
+                },
+                SchedulePoint {
+                    time: start + Duration::hours(2),
+                    value: 0.4,
+                },
+                SchedulePoint {
+                    time: start + Duration::hours(3),
+                    value: 0.2,
+                },
+            ],
+        };
+
+        let sample_rate_at = |minutes| {
+            decaying_fn.adjust_sample_rate(
+                1.0,
+                start + Duration::minutes(minutes),
+                TimeRange::default(),
+            )
+        };
+
+        // Before the first point, the sample rate of the rule applies.
+        assert_eq!(sample_rate_at(0), Some(1.0));
+        assert_eq!(sample_rate_at(60), Some(0.8));
+        assert_eq!(sample_rate_at(90), Some(0.6000000000000001));
+        assert_eq!(sample_rate_at(120), Some(0.4));
+        // After the last point, its value applies.
+        assert_eq!(sample_rate_at(240), Some(0.2));
+
+        // An empty schedule does not change the sample rate.
+        let decaying_fn = DecayingFunction::Schedule { points: vec![] };
+        assert_eq!(
+            decaying_fn.adjust_sample_rate(0.5, start, TimeRange::default()),
+            Some(0.5)
+        );
+    }
+
+    #[test]
+    fn test_schedule_deserialization() {
+        let decaying_fn: DecayingFunction = serde_json::from_value(serde_json::json!({
+            "type": "schedule",
+            "points": [
+                {"time": "2022-10-10T00:00:00Z", "value": 0.5},
+                {"time": "2022-10-11T00:00:00Z", "value": 0.1}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            decaying_fn,
+            DecayingFunction::Schedule {
+                points: vec![
+                    SchedulePoint {
+                        time: Utc.with_ymd_and_hms(2022, 10, 10, 0, 0, 0).unwrap(),
+                        value: 0.5
+                    },
+                    SchedulePoint {
+                        time: Utc.with_ymd_and_hms(2022, 10, 11, 0, 0, 0).unwrap(),
+                        value: 0.1
+                    },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_schedule_deserialization_unordered() {
+        let decaying_fn: DecayingFunction = serde_json::from_value(serde_json::json!({
+            "type": "schedule",
+            "points": [
+                {"time": "2022-10-12T00:00:00Z", "value": 0.1},
+                {"time": "2022-10-10T00:00:00Z", "value": 0.5},
+                {"time": "2022-10-11T00:00:00Z", "value": 0.3}
+            ]
+        }))
+        .unwrap();
+
+        let DecayingFunction::Schedule { points } = decaying_fn else {
+            panic!("expected a schedule");
+        };
+
+        let values: Vec<f64> = points.iter().map(|point| point.value).collect();
+        assert_eq!(values, [0.5, 0.3, 0.1]);
+    }
 }
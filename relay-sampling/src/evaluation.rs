@@ -89,6 +89,14 @@ impl<'a> ReservoirEvaluator<'a> {
         Ok(val)
     }
 
+    #[cfg(feature = "redis")]
+    fn redis_get(&self, key: &ReservoirRuleKey, redis_pool: &RedisPool) -> anyhow::Result<i64> {
+        let mut redis_client = redis_pool.client()?;
+        let mut redis_connection = redis_client.connection()?;
+
+        redis_sampling::get_redis_reservoir_count(&mut redis_connection, key)
+    }
+
     /// Evaluates a reservoir rule, returning `true` if it should be sampled.
     pub fn incr_local(&self, rule: RuleId, limit: i64) -> bool {
         let Ok(mut map_guard) = self.counters.lock() else {
@@ -137,6 +145,37 @@ impl<'a> ReservoirEvaluator<'a> {
 
         self.incr_local(rule, limit)
     }
+
+    /// Returns the amount of samples taken by a reservoir rule.
+    ///
+    /// For processing relays, this is the global count from Redis, which is also used to update
+    /// the local counter. Otherwise, this is the local counter.
+    pub fn consumed(&self, rule: RuleId) -> i64 {
+        #[cfg(feature = "redis")]
+        if let Some((org_id, redis_pool)) = self.org_id_and_redis_pool {
+            let key = ReservoirRuleKey::new(org_id, rule);
+            match self.redis_get(&key, redis_pool) {
+                Ok(redis_count) => {
+                    if let Ok(mut map_guard) = self.counters.lock() {
+                        if let Some(value) = map_guard.get_mut(&rule) {
+                            *value = redis_count.max(*value);
+                        }
+                    }
+                    return redis_count;
+                }
+                Err(e) => {
+                    relay_log::error!(error = &*e, "failed to read reservoir rule count");
+                }
+            }
+        }
+
+        let Ok(map_guard) = self.counters.lock() else {
+            relay_log::error!("failed to lock reservoir counter mutex");
+            return 0;
+        };
+
+        map_guard.get(&rule).copied().unwrap_or(0)
+    }
 }
 
 /// State machine for dynamic sampling.
@@ -501,6 +540,21 @@ mod tests {
         assert!(!evaluator.evaluate(rule, limit, None));
     }
 
+    #[test]
+    fn test_reservoir_evaluator_consumed() {
+        let evaluator = mock_reservoir_evaluator(vec![(1, 0)]);
+
+        let rule = RuleId(1);
+        assert_eq!(evaluator.consumed(rule), 0);
+
+        evaluator.evaluate(rule, 3, None);
+        evaluator.evaluate(rule, 3, None);
+        assert_eq!(evaluator.consumed(rule), 2);
+
+        // Unknown rules have not taken any samples.
+        assert_eq!(evaluator.consumed(RuleId(2)), 0);
+    }
+
     #[test]
     fn test_adjust_sample_rate() {
         // return the same as input if no client sample rate set in the sampling evaluator.
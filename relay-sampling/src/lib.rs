@@ -78,6 +78,8 @@ pub mod dsc;
 pub mod evaluation;
 #[cfg(feature = "redis")]
 mod redis_sampling;
+pub mod reservoir;
+pub mod simulation;
 
 pub use config::SamplingConfig;
 pub use dsc::DynamicSamplingContext;
@@ -29,6 +29,21 @@ pub fn increment_redis_reservoir_count(
     Ok(val)
 }
 
+/// Returns the reservoir count for a given rule in redis.
+///
+/// - GET docs: [`https://redis.io/commands/get/`]
+/// - If the counter doesn't exist in redis, zero is returned.
+pub fn get_redis_reservoir_count(
+    redis_connection: &mut relay_redis::Connection,
+    key: &ReservoirRuleKey,
+) -> anyhow::Result<i64> {
+    let val: Option<i64> = relay_redis::redis::cmd("GET")
+        .arg(key.as_str())
+        .query(redis_connection)?;
+
+    Ok(val.unwrap_or(0))
+}
+
 /// Sets the expiry time for a reservoir rule count.
 pub fn set_redis_expiry(
     redis_connection: &mut relay_redis::Connection,
@@ -0,0 +1,176 @@
+//! Synchronization and status of reservoir sampling rules.
+//!
+//! Processing Relays share reservoir counters through Redis. All other Relays only know about the
+//! samples they have taken themselves. To keep reservoirs in sync, these Relays periodically report
+//! their local consumption to the upstream and receive the consumption known to the upstream back.
+//!
+//! Since every sampled event is forwarded to the upstream, where it counts towards the reservoir
+//! again, the upstream never adds reported consumption to its own counters. Instead, both sides
+//! only ever raise their counters to the highest known value.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{RuleId, SamplingConfig, SamplingValue};
+use crate::evaluation::ReservoirCounters;
+
+/// Returns the local consumption of all reservoir rules with a counter.
+pub fn local_consumption(counters: &ReservoirCounters) -> BTreeMap<RuleId, i64> {
+    match counters.lock() {
+        Ok(guard) => guard.clone(),
+        Err(_) => {
+            relay_log::error!("failed to lock reservoir counter mutex");
+            BTreeMap::new()
+        }
+    }
+}
+
+/// Merges the consumption of reservoir rules returned by the upstream into the local counters.
+///
+/// Local counters never decrease. Rules without a local counter are ignored, since they have been
+/// removed from the sampling config in the meanwhile.
+pub fn merge_consumption(counters: &ReservoirCounters, consumption: &BTreeMap<RuleId, i64>) {
+    let Ok(mut guard) = counters.lock() else {
+        relay_log::error!("failed to lock reservoir counter mutex");
+        return;
+    };
+
+    for (rule, consumed) in consumption {
+        if let Some(value) = guard.get_mut(rule) {
+            *value = (*consumed).max(*value);
+        }
+    }
+}
+
+/// The consumption of a reservoir rule.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservoirStatus {
+    /// The ID of the reservoir rule.
+    pub rule_id: RuleId,
+    /// The maximum amount of samples the rule takes.
+    pub limit: i64,
+    /// The amount of samples taken by the rule as known to this Relay.
+    pub consumed: i64,
+    /// The time after which the rule no longer applies, if any.
+    pub expiry: Option<DateTime<Utc>>,
+}
+
+impl ReservoirStatus {
+    /// Returns the status of all reservoir rules in the sampling config.
+    ///
+    /// Rules that have not been matched yet have a consumption of zero.
+    pub fn collect(config: &SamplingConfig, counters: &ReservoirCounters) -> Vec<Self> {
+        let Ok(guard) = counters.lock() else {
+            relay_log::error!("failed to lock reservoir counter mutex");
+            return Vec::new();
+        };
+
+        config
+            .rules
+            .iter()
+            .filter_map(|rule| match rule.sampling_value {
+                SamplingValue::Reservoir { limit } => Some(Self {
+                    rule_id: rule.id,
+                    limit,
+                    consumed: guard.get(&rule.id).copied().unwrap_or(0),
+                    expiry: rule.time_range.end,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    fn counters(values: &[(u32, i64)]) -> ReservoirCounters {
+        let map = values
+            .iter()
+            .map(|(rule, count)| (RuleId(*rule), *count))
+            .collect();
+        Arc::new(Mutex::new(map))
+    }
+
+    fn get(counters: &ReservoirCounters, rule: u32) -> Option<i64> {
+        counters.lock().unwrap().get(&RuleId(rule)).copied()
+    }
+
+    #[test]
+    fn test_local_consumption() {
+        let counters = counters(&[(1, 3), (2, 0)]);
+
+        let consumption = local_consumption(&counters);
+        assert_eq!(
+            consumption,
+            BTreeMap::from([(RuleId(1), 3), (RuleId(2), 0)])
+        );
+    }
+
+    #[test]
+    fn test_merge_consumption() {
+        let counters = counters(&[(1, 3), (2, 5)]);
+
+        let consumption = BTreeMap::from([(RuleId(1), 8), (RuleId(2), 1), (RuleId(3), 4)]);
+        merge_consumption(&counters, &consumption);
+
+        assert_eq!(get(&counters, 1), Some(8));
+        // Counters never decrease.
+        assert_eq!(get(&counters, 2), Some(5));
+        // Rules without a local counter are ignored.
+        assert_eq!(get(&counters, 3), None);
+    }
+
+    #[test]
+    fn test_status() {
+        let config: SamplingConfig = serde_json::from_value(serde_json::json!({
+            "rules": [
+                {
+                    "id": 1,
+                    "type": "transaction",
+                    "condition": {"op": "and", "inner": []},
+                    "samplingValue": {"type": "reservoir", "limit": 10},
+                    "timeRange": {"end": "2024-01-01T00:00:00Z"}
+                },
+                {
+                    "id": 2,
+                    "type": "transaction",
+                    "condition": {"op": "and", "inner": []},
+                    "samplingValue": {"type": "reservoir", "limit": 5}
+                },
+                {
+                    "id": 3,
+                    "type": "transaction",
+                    "condition": {"op": "and", "inner": []},
+                    "samplingValue": {"type": "sampleRate", "value": 0.5}
+                }
+            ]
+        }))
+        .unwrap();
+
+        let status = ReservoirStatus::collect(&config, &counters(&[(1, 4)]));
+        assert_eq!(
+            serde_json::to_value(status).unwrap(),
+            serde_json::json!([
+                {
+                    "ruleId": 1,
+                    "limit": 10,
+                    "consumed": 4,
+                    "expiry": "2024-01-01T00:00:00Z"
+                },
+                {
+                    "ruleId": 2,
+                    "limit": 5,
+                    "consumed": 0,
+                    "expiry": null
+                }
+            ])
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! Simulation of sampling configs on a corpus of events and trace contexts.
+//!
+//! Before new sampling rules are rolled out, a [`SamplingConfig`] can be evaluated against a
+//! representative corpus with [`simulate`]. The simulation runs at several points in time within
+//! a [`SimulationPeriod`], so that time ranges and decaying functions of rules are taken into
+//! account, and reports the resulting sample rates, matched rules, and kept volume.
+
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+
+use chrono::{DateTime, Utc};
+use relay_protocol::Getter;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::{RuleId, RuleType, SamplingConfig, SamplingMode};
+use crate::evaluation::{ReservoirCounters, ReservoirEvaluator, SamplingEvaluator, SamplingMatch};
+use crate::DynamicSamplingContext;
+
+/// The maximum number of points in time evaluated by a simulation.
+pub const MAX_SIMULATION_STEPS: u32 = 100;
+
+/// The maximum number of items in a simulated corpus.
+///
+/// Callers are responsible for enforcing this limit before running a simulation.
+pub const MAX_SIMULATION_ITEMS: usize = 1000;
+
+/// The period of time over which a sampling config is simulated.
+///
+/// The simulation evaluates the corpus at `steps` evenly distributed points in time, including
+/// `start` and `end`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationPeriod {
+    /// The first point in time of the simulation.
+    pub start: DateTime<Utc>,
+    /// The last point in time of the simulation.
+    pub end: DateTime<Utc>,
+    /// The number of points in time to evaluate.
+    ///
+    /// Defaults to `1`, which only evaluates the start of the period. The number of steps is
+    /// capped at [`MAX_SIMULATION_STEPS`].
+    #[serde(default = "SimulationPeriod::default_steps")]
+    pub steps: u32,
+}
+
+impl SimulationPeriod {
+    /// Creates a period that only evaluates a single point in time.
+    pub fn at(time: DateTime<Utc>) -> Self {
+        Self {
+            start: time,
+            end: time,
+            steps: 1,
+        }
+    }
+
+    /// Returns all points in time evaluated by the simulation.
+    pub fn points(&self) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        let steps = self.steps.clamp(1, MAX_SIMULATION_STEPS);
+        // Any period between two valid dates fits into `i64` milliseconds with enough headroom to
+        // multiply with the number of steps.
+        let millis = (self.end - self.start).num_milliseconds();
+
+        (0..steps).map(move |step| match steps {
+            1 => self.start,
+            _ => {
+                let offset = millis * i64::from(step) / i64::from(steps - 1);
+                self.start + chrono::Duration::milliseconds(offset)
+            }
+        })
+    }
+
+    const fn default_steps() -> u32 {
+        1
+    }
+}
+
+/// A single item of the simulated corpus.
+///
+/// Transaction rules are matched against the event and trace rules against the dynamic sampling
+/// context, in the same way Relay samples transactions.
+#[derive(Debug)]
+pub struct SimulationItem<'a, G> {
+    /// A transaction event along with its ID, which is used as the sampling seed.
+    pub event: Option<(Uuid, &'a G)>,
+    /// The dynamic sampling context of the trace.
+    pub dsc: Option<&'a DynamicSamplingContext>,
+}
+
+/// The amount of items that were sampled with a specific sample rate.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampleRateCount {
+    /// The sample rate applied to the items.
+    pub sample_rate: f64,
+    /// The number of items sampled with this rate.
+    pub count: u64,
+}
+
+/// The result of evaluating the corpus at a single point in time.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationStep {
+    /// The point in time at which the rules were evaluated.
+    pub timestamp: DateTime<Utc>,
+    /// The number of items in the corpus.
+    pub total: u64,
+    /// The number of items that did not match any rule and are kept.
+    pub unmatched: u64,
+    /// The number of items kept based on their sampling seed.
+    pub kept: u64,
+    /// The expected number of kept items, which is the sum of all sample rates.
+    pub projected_kept: f64,
+    /// The distribution of sample rates, ordered by ascending sample rate.
+    ///
+    /// Items that do not match any rule are counted with a sample rate of `1.0`.
+    pub sample_rates: Vec<SampleRateCount>,
+    /// The number of items matched by each rule.
+    pub matched_rules: BTreeMap<RuleId, u64>,
+}
+
+/// The result of a simulation, see [`simulate`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    /// The results for every point in time of the simulation period.
+    pub steps: Vec<SimulationStep>,
+}
+
+/// Simulates a sampling config on a corpus over a period of time.
+///
+/// At every point in time of the period, all items of the corpus are evaluated against the rules.
+/// Reservoirs start empty at every point in time, so the corpus is treated as the traffic that
+/// arrives at this time.
+pub fn simulate<G: Getter>(
+    config: &SamplingConfig,
+    period: &SimulationPeriod,
+    corpus: &[SimulationItem<'_, G>],
+) -> SimulationReport {
+    let steps = period
+        .points()
+        .map(|now| simulate_step(config, now, corpus))
+        .collect();
+
+    SimulationReport { steps }
+}
+
+fn simulate_step<G: Getter>(
+    config: &SamplingConfig,
+    now: DateTime<Utc>,
+    corpus: &[SimulationItem<'_, G>],
+) -> SimulationStep {
+    let reservoir = ReservoirEvaluator::new(ReservoirCounters::default());
+
+    let mut step = SimulationStep {
+        timestamp: now,
+        total: 0,
+        unmatched: 0,
+        kept: 0,
+        projected_kept: 0.0,
+        sample_rates: Vec::new(),
+        matched_rules: BTreeMap::new(),
+    };
+
+    let mut sample_rates = Vec::new();
+    for item in corpus {
+        step.total += 1;
+
+        let sample_rate = match evaluate(config, now, &reservoir, item) {
+            Some(sampling_match) => {
+                if sampling_match.should_keep() {
+                    step.kept += 1;
+                }
+
+                let sample_rate = sampling_match.sample_rate();
+                for rule_id in sampling_match.into_matched_rules().0 {
+                    *step.matched_rules.entry(rule_id).or_default() += 1;
+                }
+                sample_rate
+            }
+            None => {
+                step.unmatched += 1;
+                step.kept += 1;
+                1.0
+            }
+        };
+
+        step.projected_kept += sample_rate;
+        sample_rates.push(sample_rate);
+    }
+
+    sample_rates.sort_by(f64::total_cmp);
+    for sample_rate in sample_rates {
+        match step.sample_rates.last_mut() {
+            Some(last) if last.sample_rate == sample_rate => last.count += 1,
+            _ => step.sample_rates.push(SampleRateCount {
+                sample_rate,
+                count: 1,
+            }),
+        }
+    }
+
+    step
+}
+
+/// Evaluates the rules on a single item of the corpus.
+///
+/// Returns `None` if no rule matches the item or the sampling mode is not supported, in which case
+/// Relay keeps the item.
+fn evaluate<G: Getter>(
+    config: &SamplingConfig,
+    now: DateTime<Utc>,
+    reservoir: &ReservoirEvaluator,
+    item: &SimulationItem<'_, G>,
+) -> Option<SamplingMatch> {
+    let adjustment_rate = match config.mode {
+        SamplingMode::Received => None,
+        SamplingMode::Total => item.dsc.and_then(|dsc| dsc.sample_rate),
+        SamplingMode::Unsupported => return None,
+    };
+
+    let mut evaluator = SamplingEvaluator::new(now)
+        .adjust_client_sample_rate(adjustment_rate)
+        .set_reservoir(reservoir);
+
+    if let Some((seed, event)) = item.event {
+        let rules = config.filter_rules(RuleType::Transaction);
+        evaluator = match evaluator.match_rules(seed, event, rules) {
+            ControlFlow::Continue(evaluator) => evaluator,
+            ControlFlow::Break(sampling_match) => return Some(sampling_match),
+        };
+    }
+
+    let dsc = item.dsc?;
+    let rules = config.filter_rules(RuleType::Trace);
+    match evaluator.match_rules(dsc.trace_id, dsc, rules) {
+        ControlFlow::Continue(_) => None,
+        ControlFlow::Break(sampling_match) => Some(sampling_match),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    fn mocked_dsc(release: &str) -> DynamicSamplingContext {
+        DynamicSamplingContext {
+            trace_id: Uuid::new_v4(),
+            public_key: "12345678123456781234567812345678".parse().unwrap(),
+            release: Some(release.to_owned()),
+            environment: None,
+            transaction: None,
+            sample_rate: None,
+            user: Default::default(),
+            replay_id: None,
+            sampled: None,
+            other: Default::default(),
+        }
+    }
+
+    fn mocked_config() -> SamplingConfig {
+        serde_json::from_value(serde_json::json!({
+            "version": 2,
+            "rules": [
+                {
+                    "id": 1,
+                    "type": "trace",
+                    "condition": {"op": "glob", "name": "trace.release", "value": ["1.*"]},
+                    "samplingValue": {"type": "sampleRate", "value": 1.0},
+                    "timeRange": {
+                        "start": "2024-01-01T00:00:00Z",
+                        "end": "2024-01-01T02:00:00Z"
+                    }
+                },
+                {
+                    "id": 2,
+                    "type": "trace",
+                    "condition": {"op": "and", "inner": []},
+                    "samplingValue": {"type": "sampleRate", "value": 0.0}
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_period_points() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let period = SimulationPeriod {
+            start,
+            end: start + Duration::hours(2),
+            steps: 3,
+        };
+
+        let points = period.points().collect::<Vec<_>>();
+        assert_eq!(
+            points,
+            vec![
+                start,
+                start + Duration::hours(1),
+                start + Duration::hours(2)
+            ]
+        );
+
+        assert_eq!(SimulationPeriod::at(start).points().count(), 1);
+    }
+
+    #[test]
+    fn test_period_points_large() {
+        let period = SimulationPeriod {
+            start: DateTime::<Utc>::MIN_UTC,
+            end: DateTime::<Utc>::MAX_UTC,
+            steps: u32::MAX,
+        };
+
+        let points = period.points().collect::<Vec<_>>();
+        assert_eq!(points.len(), MAX_SIMULATION_STEPS as usize);
+        assert_eq!(points.first(), Some(&period.start));
+        assert!(points.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_simulate_time_range() {
+        let config = mocked_config();
+        let dscs = [mocked_dsc("1.0"), mocked_dsc("1.0"), mocked_dsc("2.0")];
+        let corpus = dscs
+            .iter()
+            .map(|dsc| SimulationItem::<DynamicSamplingContext> {
+                event: None,
+                dsc: Some(dsc),
+            })
+            .collect::<Vec<_>>();
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let period = SimulationPeriod {
+            start,
+            end: start + Duration::hours(2),
+            steps: 2,
+        };
+
+        let report = simulate(&config, &period, &corpus);
+        let [during, after] = report.steps.as_slice() else {
+            panic!("expected two steps");
+        };
+
+        // The boost rule is active at the start of the period.
+        assert_eq!(during.total, 3);
+        assert_eq!(during.kept, 2);
+        assert_eq!(during.projected_kept, 2.0);
+        assert_eq!(
+            during.matched_rules,
+            BTreeMap::from([(RuleId(1), 2), (RuleId(2), 1)])
+        );
+        assert_eq!(
+            during.sample_rates,
+            vec![
+                SampleRateCount {
+                    sample_rate: 0.0,
+                    count: 1
+                },
+                SampleRateCount {
+                    sample_rate: 1.0,
+                    count: 2
+                },
+            ]
+        );
+
+        // The boost rule has ended at the end of the period.
+        assert_eq!(after.kept, 0);
+        assert_eq!(after.matched_rules, BTreeMap::from([(RuleId(2), 3)]));
+    }
+
+    #[test]
+    fn test_simulate_unmatched() {
+        let config = SamplingConfig::new();
+        let dsc = mocked_dsc("1.0");
+        let corpus = [SimulationItem::<DynamicSamplingContext> {
+            event: None,
+            dsc: Some(&dsc),
+        }];
+
+        let report = simulate(&config, &SimulationPeriod::at(Utc::now()), &corpus);
+        let step = &report.steps[0];
+        assert_eq!(step.unmatched, 1);
+        assert_eq!(step.kept, 1);
+        assert_eq!(step.projected_kept, 1.0);
+    }
+}
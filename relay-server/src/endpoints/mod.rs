@@ -18,8 +18,11 @@ mod logs;
 mod minidump;
 mod monitor;
 mod nel;
+mod pii_dry_run;
 mod project_configs;
 mod public_keys;
+mod reservoirs;
+mod sampling_simulation;
 mod security_report;
 mod spans;
 mod statics;
@@ -49,7 +52,8 @@ where
     // Relay-internal routes pointing to /api/relay/
     let internal_routes = Router::new()
         .route("/api/relay/healthcheck/:kind/", get(health_check::handle))
-        .route("/api/relay/events/:event_id/", get(events::handle));
+        .route("/api/relay/events/:event_id/", get(events::handle))
+        .route("/api/relay/reservoirs/", get(reservoirs::handle_status));
     #[cfg(feature = "dashboard")]
     let internal_routes = internal_routes
         .route("/api/relay/logs/", get(logs::handle))
@@ -58,12 +62,19 @@ where
         // Fallback route, but with a name, and just on `/api/relay/*`.
         .route("/api/relay/*not_found", any(statics::not_found));
 
+    // Relay-internal routes with a request body that must be signed by a known Relay.
+    let signed_internal_routes = Router::new()
+        .route("/api/relay/sampling/simulate/", post(sampling_simulation::handle))
+        .route("/api/relay/pii/dry-run/", post(pii_dry_run::handle))
+        .route_layer(DefaultBodyLimit::max(crate::constants::MAX_JSON_SIZE));
+
     // Sentry Web API routes pointing to /api/0/relays/
     let web_routes = Router::new()
         .route("/api/0/relays/projectconfigs/", post(project_configs::handle))
         .route("/api/0/relays/publickeys/", post(public_keys::handle))
         .route("/api/0/relays/outcomes/", post(batch_outcomes::handle))
         .route("/api/0/relays/metrics/", post(batch_metrics::handle))
+        .route("/api/0/relays/reservoirs/", post(reservoirs::handle_sync))
         // Network connectivity check for downstream Relays, same as the internal health check.
         .route("/api/0/relays/live/", get(health_check::handle_live))
         .route_layer(DefaultBodyLimit::max(crate::constants::MAX_JSON_SIZE));
@@ -96,6 +107,7 @@ where
     let router = router.merge(dashboard);
 
     router.merge(internal_routes)
+        .merge(signed_internal_routes)
         .merge(web_routes)
         .merge(store_routes)
         // Forward all other API routes to the upstream. This will 404 for non-API routes.
@@ -0,0 +1,55 @@
+//! Dry runs of data privacy settings.
+
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use relay_event_schema::protocol::Event;
+use relay_pii::{PiiFieldChange, PrivacySettings};
+use relay_protocol::Annotated;
+use serde::{Deserialize, Serialize};
+
+use crate::extractors::SignedJson;
+
+/// Request to compare two data privacy settings on a sample event.
+#[derive(Debug, Deserialize)]
+pub struct PiiDryRun {
+    /// The current settings of the project.
+    #[serde(default)]
+    old: PrivacySettings,
+    /// The proposed settings of the project.
+    #[serde(default)]
+    new: PrivacySettings,
+    /// The sample event to scrub.
+    #[serde(deserialize_with = "Annotated::deserialize_with_meta")]
+    event: Annotated<Event>,
+}
+
+/// Response of a dry run with all fields that are scrubbed differently.
+#[derive(Debug, Serialize)]
+struct PiiDryRunReport {
+    changes: Vec<PiiFieldChange>,
+}
+
+/// Scrubs the sample event with the old and new settings and responds with the differences.
+///
+/// The request must be signed by a known Relay. Scrubbing is CPU bound and runs outside of the
+/// async runtime.
+pub async fn handle(body: SignedJson<PiiDryRun>) -> impl IntoResponse {
+    let dry_run = body.inner;
+    let result = tokio::task::spawn_blocking(move || {
+        relay_pii::diff_privacy_settings(&dry_run.event, &dry_run.old, &dry_run.new)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(changes)) => Ok(Json(PiiDryRunReport { changes })),
+        Ok(Err(error)) => Err((StatusCode::BAD_REQUEST, error.to_string())),
+        Err(error) => {
+            relay_log::error!(
+                error = &error as &dyn std::error::Error,
+                "pii dry run failed"
+            );
+            Err((StatusCode::INTERNAL_SERVER_ERROR, String::new()))
+        }
+    }
+}
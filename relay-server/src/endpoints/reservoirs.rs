@@ -0,0 +1,39 @@
+//! Synchronization and status of reservoir sampling rules.
+
+use axum::response::IntoResponse;
+use axum::Json;
+
+use crate::endpoints::common::ServiceUnavailable;
+use crate::extractors::{SignedBytes, SignedJson};
+use crate::service::ServiceState;
+use crate::services::project_cache::{GetReservoirStatus, SyncDownstreamReservoirs};
+use crate::services::reservoir::SyncReservoirs;
+
+/// Handles the reservoir synchronization endpoint for downstream Relays.
+///
+/// Responds with the consumption of each reported rule as known to this Relay, which includes all
+/// samples forwarded by downstream Relays.
+pub async fn handle_sync(
+    state: ServiceState,
+    body: SignedJson<SyncReservoirs>,
+) -> Result<impl IntoResponse, ServiceUnavailable> {
+    let message = SyncDownstreamReservoirs {
+        relay: body.relay,
+        projects: body.inner.projects,
+    };
+
+    let response = state.project_cache().send(message).await?;
+    Ok(Json(response))
+}
+
+/// Returns the consumption and expiry of all reservoir rules known to this Relay.
+///
+/// The request must be signed by a known Relay. Since the request has no body, the signature is
+/// computed over an empty payload.
+pub async fn handle_status(
+    state: ServiceState,
+    _body: SignedBytes,
+) -> Result<impl IntoResponse, ServiceUnavailable> {
+    let response = state.project_cache().send(GetReservoirStatus).await?;
+    Ok(Json(response))
+}
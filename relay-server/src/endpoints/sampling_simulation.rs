@@ -0,0 +1,32 @@
+//! Simulation of dynamic sampling configs.
+
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use relay_sampling::simulation::MAX_SIMULATION_ITEMS;
+
+use crate::extractors::SignedJson;
+use crate::utils::SamplingSimulation;
+
+/// Simulates a sampling config on the submitted corpus and responds with the report.
+///
+/// The request must be signed by a known Relay. The simulation is CPU bound and runs outside of
+/// the async runtime.
+pub async fn handle(body: SignedJson<SamplingSimulation>) -> impl IntoResponse {
+    let simulation = body.inner;
+    if simulation.corpus.len() > MAX_SIMULATION_ITEMS {
+        let message = format!("corpus exceeds {MAX_SIMULATION_ITEMS} items");
+        return Err((StatusCode::BAD_REQUEST, message));
+    }
+
+    match tokio::task::spawn_blocking(move || simulation.run()).await {
+        Ok(report) => Ok(Json(report)),
+        Err(error) => {
+            relay_log::error!(
+                error = &error as &dyn std::error::Error,
+                "sampling simulation failed"
+            );
+            Err((StatusCode::INTERNAL_SERVER_ERROR, String::new()))
+        }
+    }
+}
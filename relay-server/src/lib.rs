@@ -276,6 +276,8 @@ use relay_system::{Controller, Service};
 use crate::service::{Runtimes, ServiceState};
 use crate::services::server::HttpServer;
 
+pub use crate::utils::{CorpusItem, SamplingSimulation};
+
 /// Runs a relay web server and spawns all internal worker threads.
 ///
 /// This effectively boots the entire server application. It blocks the current thread until a
@@ -35,8 +35,10 @@ pub mod processor;
 pub mod project;
 pub mod project_cache;
 pub mod project_local;
+pub mod project_updates;
 pub mod project_upstream;
 pub mod relays;
+pub mod reservoir;
 pub mod server;
 pub mod spooler;
 pub mod test_store;
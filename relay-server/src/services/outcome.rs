@@ -163,6 +163,9 @@ pub enum Outcome {
     /// The event has been rate limited.
     RateLimited(Option<ReasonCode>),
 
+    /// The event repeats an event that was seen shortly before.
+    Deduplicated,
+
     /// The event/metric has been cardinality limited.
     #[cfg(feature = "processing")]
     CardinalityLimited,
@@ -182,7 +185,9 @@ impl Outcome {
     /// Returns the raw numeric value of this outcome for the JSON and Kafka schema.
     fn to_outcome_id(&self) -> OutcomeId {
         match self {
-            Outcome::Filtered(_) | Outcome::FilteredSampling(_) => OutcomeId::FILTERED,
+            Outcome::Filtered(_) | Outcome::FilteredSampling(_) | Outcome::Deduplicated => {
+                OutcomeId::FILTERED
+            }
             Outcome::RateLimited(_) => OutcomeId::RATE_LIMITED,
             #[cfg(feature = "processing")]
             Outcome::CardinalityLimited => OutcomeId::RATE_LIMITED,
@@ -199,6 +204,7 @@ impl Outcome {
             Outcome::Invalid(discard_reason) => Some(Cow::Borrowed(discard_reason.name())),
             Outcome::Filtered(filter_key) => Some(filter_key.clone().name()),
             Outcome::FilteredSampling(rule_ids) => Some(Cow::Owned(format!("Sampled:{rule_ids}"))),
+            Outcome::Deduplicated => Some(Cow::Borrowed("deduplicated")),
             //TODO can we do better ? (not re copying the string )
             Outcome::RateLimited(code_opt) => code_opt
                 .as_ref()
@@ -233,6 +239,7 @@ impl fmt::Display for Outcome {
         match self {
             Outcome::Filtered(key) => write!(f, "filtered by {key}"),
             Outcome::FilteredSampling(rule_ids) => write!(f, "sampling rule {rule_ids}"),
+            Outcome::Deduplicated => write!(f, "duplicate event"),
             Outcome::RateLimited(None) => write!(f, "rate limited"),
             Outcome::RateLimited(Some(reason)) => write!(f, "rate limited with reason {reason}"),
             #[cfg(feature = "processing")]
@@ -654,7 +661,7 @@ impl ClientReportOutcomeProducer {
 
         // The outcome type determines what field to place the outcome in:
         let discarded_events = match msg.outcome {
-            Outcome::Filtered(_) => &mut client_report.filtered_events,
+            Outcome::Filtered(_) | Outcome::Deduplicated => &mut client_report.filtered_events,
             Outcome::FilteredSampling(_) => &mut client_report.filtered_sampling_events,
             Outcome::RateLimited(_) => &mut client_report.rate_limited_events,
             _ => {
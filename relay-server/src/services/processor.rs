@@ -15,9 +15,11 @@ use flate2::write::{GzEncoder, ZlibEncoder};
 use flate2::Compression;
 use fnv::FnvHasher;
 use relay_base_schema::project::{ProjectId, ProjectKey};
+use relay_cardinality::limiter::{CardinalityLimiter, Limiter};
+use relay_cardinality::{CardinalityLimit, InMemoryLimiter, InMemoryLimiterOptions};
 use relay_common::time::UnixTimestamp;
 use relay_config::{Config, HttpEncoding};
-use relay_dynamic_config::{ErrorBoundary, Feature};
+use relay_dynamic_config::{CardinalityLimiterMode, ErrorBoundary, Feature};
 use relay_event_normalization::{
     normalize_event, validate_event_timestamps, validate_transaction, ClockDriftProcessor,
     DynamicMeasurementsConfig, EventValidationConfig, MeasurementsConfig, NormalizationConfig,
@@ -28,6 +30,7 @@ use relay_event_schema::processor::ProcessingAction;
 use relay_event_schema::protocol::{
     ClientReport, Event, EventId, EventType, IpAddr, Metrics, NetworkReportError,
 };
+use relay_filter::abuse::AbuseFilter;
 use relay_filter::FilterStatKey;
 use relay_metrics::aggregator::AggregatorConfig;
 use relay_metrics::{Bucket, BucketView, BucketsView, MergeBuckets, MetricMeta, MetricNamespace};
@@ -44,12 +47,9 @@ use tokio::sync::Semaphore;
 
 #[cfg(feature = "processing")]
 use {
-    crate::services::store::{Store, StoreEnvelope},
-    crate::utils::{EnvelopeLimiter, ItemAction, MetricsLimiter},
-    relay_cardinality::{
-        CardinalityLimit, CardinalityLimiter, RedisSetLimiter, RedisSetLimiterOptions,
-    },
-    relay_dynamic_config::CardinalityLimiterMode,
+    crate::services::store::{Store, StoreCardinalityReports, StoreEnvelope},
+    crate::utils::{EnvelopeLimiter, MetricsLimiter},
+    relay_cardinality::{RedisSetLimiter, RedisSetLimiterOptions},
     relay_metrics::{Aggregator, RedisMetricMetaStore},
     relay_quotas::{RateLimitingError, RedisRateLimiter},
     relay_redis::RedisPool,
@@ -73,16 +73,19 @@ use crate::services::upstream::{
     SendRequest, UpstreamRelay, UpstreamRequest, UpstreamRequestError,
 };
 use crate::statsd::{RelayCounters, RelayHistograms, RelayTimers};
-use crate::utils::{self, ExtractionMode, ManagedEnvelope, SamplingResult};
+use crate::utils::{self, ExtractionMode, ItemAction, ManagedEnvelope, SamplingResult};
 
 mod attachment;
+mod dedup;
 mod dynamic_sampling;
 mod event;
 mod profile;
 mod replay;
 mod report;
 mod session;
+mod sourcemaps;
 mod span;
+mod symbolication;
 #[cfg(feature = "processing")]
 mod unreal;
 
@@ -286,6 +289,9 @@ pub enum ProcessingError {
     #[error("event filtered with reason: {0:?}")]
     EventFiltered(FilterStatKey),
 
+    #[error("duplicate event")]
+    DuplicateEvent,
+
     #[error("missing or invalid required event timestamp")]
     InvalidTimestamp,
 
@@ -338,6 +344,7 @@ impl ProcessingError {
             // These outcomes are emitted at the source.
             Self::MissingProjectId => None,
             Self::EventFiltered(_) => None,
+            Self::DuplicateEvent => None,
         }
     }
 
@@ -451,6 +458,9 @@ struct ProcessEnvelopeState<'a> {
 
     /// Reservoir evaluator that we use for dynamic sampling.
     reservoir: ReservoirEvaluator<'a>,
+
+    /// Deduplication keys of the event, which are remembered once the event is accepted.
+    dedup_keys: Vec<dedup::DedupKey>,
 }
 
 impl<'a> ProcessEnvelopeState<'a> {
@@ -756,10 +766,15 @@ struct InnerProcessor {
     #[cfg(feature = "processing")]
     rate_limiter: Option<RedisRateLimiter>,
     geoip_lookup: Option<GeoIpLookup>,
+    sourcemap_cache: Option<sourcemaps::SourceMapCache>,
+    symbol_cache: Option<symbolication::SymbolCache>,
+    abuse_filter: AbuseFilter,
+    deduplicator: dedup::Deduplicator,
     #[cfg(feature = "processing")]
     metric_meta_store: Option<RedisMetricMetaStore>,
     #[cfg(feature = "processing")]
-    cardinality_limiter: Option<CardinalityLimiter>,
+    cardinality_limiter: Option<CardinalityLimiter<RedisSetLimiter>>,
+    local_cardinality_limiter: Option<CardinalityLimiter<InMemoryLimiter>>,
     #[cfg(feature = "processing")]
     store_forwarder: Option<Addr<Store>>,
 }
@@ -778,15 +793,29 @@ impl EnvelopeProcessorService {
         #[cfg(feature = "processing")] aggregator: Addr<Aggregator>,
         #[cfg(feature = "processing")] store_forwarder: Option<Addr<Store>>,
     ) -> Self {
-        let geoip_lookup = config.geoip_path().and_then(|p| {
-            match GeoIpLookup::open(p).context(ServiceError::GeoIp) {
-                Ok(geoip) => Some(geoip),
-                Err(err) => {
-                    relay_log::error!("failed to open GeoIP db {p:?}: {err:?}");
-                    None
-                }
+        let mut geoip = GeoIpLookup::default();
+
+        if let Some(p) = config.geoip_path() {
+            if let Err(err) = geoip.open_city(p).context(ServiceError::GeoIp) {
+                relay_log::error!("failed to open GeoIP db {p:?}: {err:?}");
             }
-        });
+        }
+
+        if let Some(p) = config.geoip_asn_path() {
+            if let Err(err) = geoip.open_asn(p).context(ServiceError::GeoIp) {
+                relay_log::error!("failed to open GeoIP ASN db {p:?}: {err:?}");
+            }
+        }
+
+        if let Some(p) = config.geoip_anonymous_ip_path() {
+            if let Err(err) = geoip.open_anonymous_ip(p).context(ServiceError::GeoIp) {
+                relay_log::error!("failed to open GeoIP Anonymous IP db {p:?}: {err:?}");
+            }
+        }
+
+        let geoip_lookup = (!geoip.is_empty()).then_some(geoip);
+
+        let sourcemap_cache = sourcemaps::SourceMapCache::new(&config, upstream_relay.clone());
 
         let inner = InnerProcessor {
             global_config,
@@ -801,6 +830,10 @@ impl EnvelopeProcessorService {
             upstream_relay,
             test_store,
             geoip_lookup,
+            sourcemap_cache,
+            symbol_cache: symbolication::SymbolCache::new(&config),
+            abuse_filter: AbuseFilter::new(),
+            deduplicator: dedup::Deduplicator::new(&config),
             #[cfg(feature = "processing")]
             aggregator,
             #[cfg(feature = "processing")]
@@ -820,6 +853,11 @@ impl EnvelopeProcessorService {
                     )
                 })
                 .map(CardinalityLimiter::new),
+            local_cardinality_limiter: config.cardinality_limiter_local_enforcement().then(|| {
+                CardinalityLimiter::new(InMemoryLimiter::new(InMemoryLimiterOptions {
+                    cache_vacuum_interval: config.cardinality_limiter_cache_vacuum_interval(),
+                }))
+            }),
             #[cfg(feature = "processing")]
             store_forwarder,
             config,
@@ -830,15 +868,56 @@ impl EnvelopeProcessorService {
         }
     }
 
+    /// Applies generic inbound filters to monitor check-ins in non-processing Relays.
+    ///
+    /// Check-ins that cannot be parsed are kept here and rejected by the upstream. Processing
+    /// Relays filter check-ins in `process_check_ins` instead, to parse them only once.
+    fn filter_check_ins(&self, state: &mut ProcessEnvelopeState) {
+        let filter_settings = &state.project_state.config.filter_settings;
+        if !filter_settings.has_generic_filters() {
+            return;
+        }
+
+        state.managed_envelope.retain_items(|item| {
+            if item.ty() != &ItemType::CheckIn {
+                return ItemAction::Keep;
+            }
+
+            let Ok(check_in) = serde_json::from_slice::<relay_monitors::CheckIn>(&item.payload())
+            else {
+                return ItemAction::Keep;
+            };
+
+            match relay_filter::should_filter_item(&check_in, filter_settings) {
+                Ok(()) => ItemAction::Keep,
+                Err(filter_stat_key) => ItemAction::Drop(Outcome::Filtered(filter_stat_key)),
+            }
+        })
+    }
+
     /// Normalize monitor check-ins and remove invalid ones.
+    ///
+    /// Generic inbound filters are applied to the parsed check-ins before normalization.
     #[cfg(feature = "processing")]
     fn process_check_ins(&self, state: &mut ProcessEnvelopeState) {
+        let filter_settings = &state.project_state.config.filter_settings;
         state.managed_envelope.retain_items(|item| {
             if item.ty() != &ItemType::CheckIn {
                 return ItemAction::Keep;
             }
 
-            match relay_monitors::process_check_in(&item.payload(), state.project_id) {
+            let result = serde_json::from_slice::<relay_monitors::CheckIn>(&item.payload())
+                .map_err(relay_monitors::ProcessCheckInError::from);
+
+            if let Ok(ref check_in) = result {
+                if let Err(filter_stat_key) =
+                    relay_filter::should_filter_item(check_in, filter_settings)
+                {
+                    return ItemAction::Drop(Outcome::Filtered(filter_stat_key));
+                }
+            }
+
+            match result.and_then(|c| relay_monitors::normalize_check_in(c, state.project_id)) {
                 Ok(result) => {
                     item.set_routing_hint(result.routing_hint);
                     item.set_payload(ContentType::Json, result.payload);
@@ -922,6 +1001,7 @@ impl EnvelopeProcessorService {
             managed_envelope,
             profile_id: None,
             reservoir,
+            dedup_keys: Vec::new(),
         })
     }
 
@@ -1106,6 +1186,7 @@ impl EnvelopeProcessorService {
                     state.project_state.config().measurements.as_ref(),
                     global_config.measurements.as_ref(),
                 )),
+                tag_rules: &state.project_state.config.tag_rules,
             };
 
             metric!(timer(RelayTimers::EventProcessingLightNormalization), {
@@ -1139,7 +1220,14 @@ impl EnvelopeProcessorService {
 
         event::finalize(state, &self.inner.config)?;
         self.light_normalize_event(state)?;
+        event::tag_bot_score(state, self.inner.geoip_lookup.as_ref());
         event::filter(state)?;
+        sourcemaps::process(state, self.inner.sourcemap_cache.as_ref());
+        symbolication::process(state, self.inner.symbol_cache.as_ref());
+        event::compute_grouping_hash(state);
+        event::filter_grouped(state)?;
+        event::filter_abuse(state, &self.inner.abuse_filter)?;
+        dedup::process(state, &self.inner.deduplicator)?;
         dynamic_sampling::tag_error_with_sampling_decision(state, &self.inner.config);
 
         if_processing!(self.inner.config, {
@@ -1153,6 +1241,7 @@ impl EnvelopeProcessorService {
         }
 
         attachment::scrub(state);
+        dedup::record(state, &self.inner.deduplicator);
 
         Ok(())
     }
@@ -1173,7 +1262,9 @@ impl EnvelopeProcessorService {
         event::finalize(state, &self.inner.config)?;
         self.light_normalize_event(state)?;
         dynamic_sampling::normalize(state);
+        event::tag_bot_score(state, self.inner.geoip_lookup.as_ref());
         event::filter(state)?;
+        event::filter_abuse(state, &self.inner.abuse_filter)?;
         dynamic_sampling::run(state, &self.inner.config);
 
         // We avoid extracting metrics if we are not sampling the event while in non-processing
@@ -1256,10 +1347,15 @@ impl EnvelopeProcessorService {
     }
 
     /// Processes cron check-ins.
-    fn process_checkins(&self, _state: &mut ProcessEnvelopeState) -> Result<(), ProcessingError> {
+    fn process_checkins(&self, state: &mut ProcessEnvelopeState) -> Result<(), ProcessingError> {
+        if !self.inner.config.processing_enabled() {
+            self.filter_check_ins(state);
+        }
         if_processing!(self.inner.config, {
-            self.enforce_quotas(_state)?;
-            self.process_check_ins(_state);
+            // Check-ins are filtered while they are normalized, so that filtered check-ins do not
+            // count towards quotas.
+            self.process_check_ins(state);
+            self.enforce_quotas(state)?;
         });
         Ok(())
     }
@@ -1744,9 +1840,9 @@ impl EnvelopeProcessorService {
     }
 
     /// Cardinality limits the passed buckets and returns a filtered vector of only accepted buckets.
-    #[cfg(feature = "processing")]
-    fn cardinality_limit_buckets(
+    fn cardinality_limit_buckets<T: Limiter>(
         &self,
+        limiter: &CardinalityLimiter<T>,
         scoping: Scoping,
         limits: &[CardinalityLimit],
         buckets: Vec<Bucket>,
@@ -1759,10 +1855,6 @@ impl EnvelopeProcessorService {
             return buckets;
         }
 
-        let Some(ref limiter) = self.inner.cardinality_limiter else {
-            return buckets;
-        };
-
         let cardinality_scope = relay_cardinality::Scoping {
             organization_id: scoping.organization_id,
             project_id: scoping.project_id,
@@ -1780,6 +1872,16 @@ impl EnvelopeProcessorService {
             }
         };
 
+        #[cfg(feature = "processing")]
+        if limits.has_passive_rejections() {
+            if let Some(ref store_forwarder) = self.inner.store_forwarder {
+                let top_n = self.inner.config.cardinality_limiter_report_top_n();
+                store_forwarder.send(StoreCardinalityReports {
+                    reports: limits.passive_reports(top_n),
+                });
+            }
+        }
+
         if matches!(cardinality_limiter_mode, CardinalityLimiterMode::Passive) {
             return limits.into_source();
         }
@@ -1816,7 +1918,10 @@ impl EnvelopeProcessorService {
             let limits = project_state.get_cardinality_limits();
 
             if project_state.has_feature(Feature::CardinalityLimiter) {
-                buckets = self.cardinality_limit_buckets(scoping, limits, buckets, mode);
+                if let Some(ref limiter) = self.inner.cardinality_limiter {
+                    buckets =
+                        self.cardinality_limit_buckets(limiter, scoping, limits, buckets, mode);
+                }
             }
 
             if self.rate_limit_batches(scoping, &buckets, &project_state, mode) {
@@ -1995,7 +2100,32 @@ impl EnvelopeProcessorService {
         }
     }
 
-    fn handle_encode_metrics(&self, message: EncodeMetrics) {
+    /// Cardinality limits buckets of all scopes with the in-memory cardinality limiter.
+    ///
+    /// This only runs in non-processing Relays with local enforcement enabled. Processing Relays
+    /// enforce cardinality limits through Redis in [`Self::encode_metrics_processing`].
+    fn cardinality_limit_locally(&self, message: &mut EncodeMetrics) {
+        let Some(ref limiter) = self.inner.local_cardinality_limiter else {
+            return;
+        };
+
+        for (scoping, metrics) in message.scopes.iter_mut() {
+            if !metrics
+                .project_state
+                .has_feature(Feature::CardinalityLimiter)
+            {
+                continue;
+            }
+
+            let mode = metrics.project_state.get_extraction_mode();
+            let limits = metrics.project_state.get_cardinality_limits();
+            let buckets = std::mem::take(&mut metrics.buckets);
+            metrics.buckets =
+                self.cardinality_limit_buckets(limiter, *scoping, limits, buckets, mode);
+        }
+    }
+
+    fn handle_encode_metrics(&self, mut message: EncodeMetrics) {
         #[cfg(feature = "processing")]
         if self.inner.config.processing_enabled() {
             if let Some(ref store_forwarder) = self.inner.store_forwarder {
@@ -2003,6 +2133,8 @@ impl EnvelopeProcessorService {
             }
         }
 
+        self.cardinality_limit_locally(&mut message);
+
         if self.inner.config.http_global_metrics() {
             self.encode_metrics_global(message)
         } else {
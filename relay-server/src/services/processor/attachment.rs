@@ -6,7 +6,7 @@ use std::time::Instant;
 use relay_pii::PiiAttachmentsProcessor;
 use relay_statsd::metric;
 
-use crate::envelope::{AttachmentType, ContentType};
+use crate::envelope::{AttachmentType, ContentType, Item, ItemType};
 use crate::services::processor::ProcessEnvelopeState;
 use crate::statsd::RelayTimers;
 
@@ -38,14 +38,61 @@ pub fn create_placeholders(state: &mut ProcessEnvelopeState) {
     }
 }
 
+/// The format of an attachment that is scrubbed field by field.
+enum StructuredAttachment {
+    /// A JSON document, such as a view hierarchy.
+    Json,
+    /// A newline-delimited log file, optionally containing JSON objects.
+    Log,
+    /// A plain text Apple crash report.
+    AppleCrashReport,
+    /// The XML crash context of an Unreal crash report.
+    UnrealContext,
+}
+
+/// Returns the structured format of an attachment, if it has one.
+fn structured_attachment(item: &Item) -> Option<StructuredAttachment> {
+    if item.ty() != &ItemType::Attachment {
+        return None;
+    }
+
+    match item.attachment_type() {
+        Some(AttachmentType::ViewHierarchy) => return Some(StructuredAttachment::Json),
+        Some(AttachmentType::AppleCrashReport) => {
+            return Some(StructuredAttachment::AppleCrashReport)
+        }
+        Some(AttachmentType::UnrealContext) => return Some(StructuredAttachment::UnrealContext),
+        Some(AttachmentType::Attachment) | None => (),
+        Some(_) => return None,
+    }
+
+    let filename = item.filename().unwrap_or_default();
+    if item.content_type() == Some(&ContentType::Json) || filename.ends_with(".json") {
+        Some(StructuredAttachment::Json)
+    } else if [".log", ".jsonl", ".ndjson"]
+        .iter()
+        .any(|extension| filename.ends_with(extension))
+    {
+        Some(StructuredAttachment::Log)
+    } else {
+        None
+    }
+}
+
 /// Apply data privacy rules to attachments in the envelope.
 ///
 /// This only applies the new PII rules that explicitly select `ValueType::Binary` or one of the
 /// attachment types. When special attachments are detected, these are scrubbed with custom
 /// logic; otherwise the entire attachment is treated as a single binary blob.
+///
+/// JSON attachments, view hierarchies, log files, Apple crash reports, and Unreal crash contexts
+/// are parsed, so that rules can select individual fields. If parsing fails, they are scrubbed as
+/// binary blobs instead.
 pub fn scrub(state: &mut ProcessEnvelopeState) {
     let envelope = state.managed_envelope.envelope_mut();
     if let Some(ref config) = state.project_state.config.pii_config {
+        let processor = PiiAttachmentsProcessor::new(config.compiled());
+
         let minidump = envelope
             .get_item_by_mut(|item| item.attachment_type() == Some(&AttachmentType::Minidump));
 
@@ -53,8 +100,6 @@ pub fn scrub(state: &mut ProcessEnvelopeState) {
             let filename = item.filename().unwrap_or_default();
             let mut payload = item.payload().to_vec();
 
-            let processor = PiiAttachmentsProcessor::new(config.compiled());
-
             // Minidump scrubbing can fail if the minidump cannot be parsed. In this case, we
             // must be conservative and treat it as a plain attachment. Under extreme
             // conditions, this could destroy stack memory.
@@ -88,5 +133,54 @@ pub fn scrub(state: &mut ProcessEnvelopeState) {
 
             item.set_payload(content_type, payload);
         }
+
+        for item in envelope.items_mut() {
+            let Some(format) = structured_attachment(item) else {
+                continue;
+            };
+
+            metric!(timer(RelayTimers::AttachmentScrubbing), {
+                let filename = item.filename().unwrap_or_default();
+                let payload = item.payload();
+
+                let result: Result<_, Box<dyn Error>> = match format {
+                    StructuredAttachment::Json => {
+                        processor.scrub_json(filename, &payload).map_err(Box::from)
+                    }
+                    StructuredAttachment::Log => {
+                        processor.scrub_log(filename, &payload).map_err(Box::from)
+                    }
+                    StructuredAttachment::AppleCrashReport => processor
+                        .scrub_apple_crash_report(filename, &payload)
+                        .map_err(Box::from),
+                    StructuredAttachment::UnrealContext => processor
+                        .scrub_unreal_context(filename, &payload)
+                        .map_err(Box::from),
+                };
+
+                let scrubbed = match result {
+                    Ok(scrubbed) => scrubbed,
+                    Err(scrub_error) => {
+                        relay_log::warn!(
+                            error = scrub_error.as_ref(),
+                            "failed to scrub structured attachment",
+                        );
+
+                        let mut data = payload.to_vec();
+                        processor
+                            .scrub_attachment(filename, &mut data)
+                            .then_some(data)
+                    }
+                };
+
+                if let Some(scrubbed) = scrubbed {
+                    let content_type = item
+                        .content_type()
+                        .cloned()
+                        .unwrap_or(ContentType::OctetStream);
+                    item.set_payload(content_type, scrubbed);
+                }
+            })
+        }
     }
 }
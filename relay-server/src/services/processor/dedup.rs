@@ -0,0 +1,322 @@
+//! Deduplication of repeated error events.
+//!
+//! SDKs in retry loops, and clients that re-send envelopes after upstream timeouts, can submit
+//! the same event many times. If enabled for a project, events are dropped when their event ID or
+//! their grouping hash was already seen within the configured window.
+//!
+//! Events are only remembered once they are accepted, so that events dropped by rate limits or
+//! processing errors can be retried. Seen events are kept in memory of a single Relay instance and
+//! are not shared between Relays.
+
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+use relay_base_schema::project::ProjectId;
+use relay_config::Config;
+use relay_event_schema::protocol::EventId;
+
+use crate::services::outcome::Outcome;
+use crate::services::processor::{ProcessEnvelopeState, ProcessingError};
+
+/// Identifies an event within a project.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum DedupKey {
+    /// The ID of the event, which is retained when clients re-send an envelope.
+    EventId(ProjectId, EventId),
+    /// The grouping hash of the event, see [`compute_grouping_hash`].
+    ///
+    /// [`compute_grouping_hash`]: crate::services::processor::event::compute_grouping_hash
+    Content(ProjectId, String),
+}
+
+/// Remembers recently seen events of all projects.
+///
+/// The deduplicator is shared between all projects and must be kept for the lifetime of the
+/// service.
+pub struct Deduplicator {
+    seen: Mutex<LruCache<DedupKey, Instant>>,
+}
+
+impl Deduplicator {
+    /// Creates a deduplicator bounded by the configured cache size.
+    pub fn new(config: &Config) -> Self {
+        let capacity = NonZeroUsize::new(config.deduplication_cache_size().max(1)).unwrap();
+        Self {
+            seen: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Returns `true` if any of the keys was seen within the window.
+    fn is_duplicate(&self, keys: &[DedupKey], window: Duration, now: Instant) -> bool {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        keys.iter().any(|key| {
+            seen.get(key).map_or(false, |first| {
+                now.saturating_duration_since(*first) < window
+            })
+        })
+    }
+
+    /// Remembers the keys of an accepted event.
+    ///
+    /// Only the first occurrence starts the window, so that a constantly repeating event is still
+    /// let through once per window.
+    fn record(&self, keys: &[DedupKey], window: Duration, now: Instant) {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+
+        for key in keys {
+            match seen.get(key) {
+                Some(first) if now.saturating_duration_since(*first) < window => (),
+                _ => {
+                    seen.put(key.clone(), now);
+                }
+            }
+        }
+    }
+}
+
+/// Drops the event if the project enables deduplication and the event was seen recently.
+///
+/// This must run after [`compute_grouping_hash`], so that events are also compared by content.
+///
+/// Duplicates are rejected with [`Outcome::Deduplicated`]. Otherwise, the keys of the event are
+/// kept in the state until they are remembered by [`record`].
+///
+/// [`compute_grouping_hash`]: crate::services::processor::event::compute_grouping_hash
+pub fn process(
+    state: &mut ProcessEnvelopeState,
+    deduplicator: &Deduplicator,
+) -> Result<(), ProcessingError> {
+    let config = state.project_state.config.deduplication;
+    if config.is_disabled() {
+        return Ok(());
+    }
+
+    let Some(event) = state.event.value() else {
+        return Ok(());
+    };
+
+    let mut keys = Vec::with_capacity(2);
+
+    let event_id = event
+        .id
+        .value()
+        .copied()
+        .or_else(|| state.managed_envelope.envelope().event_id());
+    if let Some(event_id) = event_id {
+        keys.push(DedupKey::EventId(state.project_id, event_id));
+    }
+
+    if let Some(grouping_hash) = event.grouping_hash.as_str() {
+        keys.push(DedupKey::Content(
+            state.project_id,
+            grouping_hash.to_owned(),
+        ));
+    }
+
+    let window = Duration::from_secs(config.window);
+    if deduplicator.is_duplicate(&keys, window, Instant::now()) {
+        state.managed_envelope.reject(Outcome::Deduplicated);
+        return Err(ProcessingError::DuplicateEvent);
+    }
+
+    state.dedup_keys = keys;
+    Ok(())
+}
+
+/// Remembers the event checked by [`process`] once it has been accepted.
+///
+/// This must run after rate limiting and all other steps that can drop the event, so that events
+/// that are rejected do not cause later retries to be dropped as duplicates.
+pub fn record(state: &mut ProcessEnvelopeState, deduplicator: &Deduplicator) {
+    let keys = std::mem::take(&mut state.dedup_keys);
+    if keys.is_empty() || !state.has_event() {
+        return;
+    }
+
+    let window = Duration::from_secs(state.project_state.config.deduplication.window);
+    deduplicator.record(&keys, window, Instant::now());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use relay_event_schema::protocol::{Event, EventType};
+    use relay_protocol::Annotated;
+    use relay_sampling::evaluation::{ReservoirCounters, ReservoirEvaluator};
+    use relay_test::mock_service;
+
+    use crate::services::outcome::TrackOutcome;
+    use crate::services::processor::ProcessingGroup;
+    use crate::services::project::ProjectState;
+    use crate::testutils::empty_envelope;
+    use crate::utils::{ManagedEnvelope, SamplingResult};
+
+    use super::*;
+
+    fn deduplicator(cache_size: usize) -> Deduplicator {
+        let config = Config::from_json_value(serde_json::json!({
+            "deduplication": {"cache_size": cache_size}
+        }))
+        .unwrap();
+
+        Deduplicator::new(&config)
+    }
+
+    /// Checks the keys and records them if they are not a duplicate.
+    fn check(deduplicator: &Deduplicator, keys: &[DedupKey], now: Instant) -> bool {
+        let window = Duration::from_secs(60);
+        let duplicate = deduplicator.is_duplicate(keys, window, now);
+        if !duplicate {
+            deduplicator.record(keys, window, now);
+        }
+        duplicate
+    }
+
+    fn event_id() -> EventId {
+        "52df9022835246eeb317dbd739ccd059".parse().unwrap()
+    }
+
+    fn event_id_key(id: &str) -> DedupKey {
+        DedupKey::EventId(ProjectId::new(42), id.parse().unwrap())
+    }
+
+    #[test]
+    fn test_window() {
+        let deduplicator = deduplicator(10);
+        let key = event_id_key("52df9022835246eeb317dbd739ccd059");
+        let now = Instant::now();
+
+        assert!(!check(&deduplicator, &[key.clone()], now));
+        assert!(check(
+            &deduplicator,
+            &[key.clone()],
+            now + Duration::from_secs(30)
+        ));
+        assert!(check(
+            &deduplicator,
+            &[key.clone()],
+            now + Duration::from_secs(59)
+        ));
+
+        // The window starts over once it has elapsed.
+        assert!(!check(
+            &deduplicator,
+            &[key.clone()],
+            now + Duration::from_secs(60)
+        ));
+        assert!(check(&deduplicator, &[key], now + Duration::from_secs(61)));
+    }
+
+    #[test]
+    fn test_any_key() {
+        let deduplicator = deduplicator(10);
+        let first = event_id_key("52df9022835246eeb317dbd739ccd059");
+        let second = event_id_key("a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5");
+        let content = DedupKey::Content(ProjectId::new(42), "a".repeat(32));
+        let now = Instant::now();
+
+        assert!(!check(&deduplicator, &[first, content.clone()], now));
+        assert!(check(&deduplicator, &[second, content], now));
+    }
+
+    #[test]
+    fn test_projects() {
+        let deduplicator = deduplicator(10);
+        let now = Instant::now();
+
+        let content = DedupKey::Content(ProjectId::new(42), "a".repeat(32));
+        assert!(!check(&deduplicator, &[content], now));
+
+        let content = DedupKey::Content(ProjectId::new(43), "a".repeat(32));
+        assert!(!check(&deduplicator, &[content], now));
+    }
+
+    #[test]
+    fn test_cache_size() {
+        let deduplicator = deduplicator(1);
+        let first = event_id_key("52df9022835246eeb317dbd739ccd059");
+        let second = event_id_key("a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5");
+        let now = Instant::now();
+
+        assert!(!check(&deduplicator, &[first.clone()], now));
+        assert!(!check(&deduplicator, &[second], now));
+        assert!(!check(&deduplicator, &[first], now));
+    }
+
+    #[tokio::test]
+    async fn test_process() {
+        let deduplicator = deduplicator(10);
+        let (outcome_aggregator, handle) = mock_service(
+            "outcome_aggregator",
+            vec![],
+            |outcomes: &mut Vec<TrackOutcome>, msg| outcomes.push(msg),
+        );
+        let (test_store, _) = mock_service("test_store", (), |&mut (), _| {});
+
+        let mut project_state = ProjectState::allowed();
+        project_state.config.deduplication.is_enabled = true;
+        let project_state = Arc::new(project_state);
+
+        let get_state = || ProcessEnvelopeState {
+            event: Annotated::new(Event {
+                id: Annotated::new(event_id()),
+                ty: Annotated::new(EventType::Error),
+                grouping_hash: Annotated::new("a".repeat(32)),
+                ..Event::default()
+            }),
+            event_metrics_extracted: false,
+            metrics: Default::default(),
+            sample_rates: None,
+            sampling_result: SamplingResult::Pending,
+            extracted_metrics: Default::default(),
+            project_state: project_state.clone(),
+            sampling_project_state: None,
+            project_id: ProjectId::new(42),
+            managed_envelope: ManagedEnvelope::standalone(
+                empty_envelope(),
+                outcome_aggregator.clone(),
+                test_store.clone(),
+                ProcessingGroup::Error,
+            ),
+            profile_id: None,
+            reservoir: ReservoirEvaluator::new(ReservoirCounters::default()),
+            dedup_keys: Vec::new(),
+        };
+
+        // Events are not remembered until they are recorded, for instance if rate limited.
+        let mut state = get_state();
+        assert!(process(&mut state, &deduplicator).is_ok());
+        assert_eq!(
+            state.dedup_keys,
+            vec![
+                DedupKey::EventId(ProjectId::new(42), event_id()),
+                DedupKey::Content(ProjectId::new(42), "a".repeat(32)),
+            ]
+        );
+        drop(state);
+
+        let mut state = get_state();
+        assert!(process(&mut state, &deduplicator).is_ok());
+        record(&mut state, &deduplicator);
+        assert!(state.dedup_keys.is_empty());
+        drop(state);
+
+        let mut state = get_state();
+        assert!(matches!(
+            process(&mut state, &deduplicator),
+            Err(ProcessingError::DuplicateEvent)
+        ));
+        drop(state);
+
+        drop(outcome_aggregator);
+        let outcomes = handle.await.unwrap();
+        let deduplicated = outcomes
+            .iter()
+            .filter(|o| o.outcome == Outcome::Deduplicated)
+            .count();
+        assert_eq!(deduplicated, 1);
+    }
+}
@@ -478,6 +478,7 @@ mod tests {
                 profile_id: None,
                 event_metrics_extracted: false,
                 reservoir: dummy_reservoir(),
+                dedup_keys: Vec::new(),
             }
         };
 
@@ -6,15 +6,19 @@ use chrono::Duration as SignedDuration;
 use once_cell::sync::OnceCell;
 use relay_auth::RelayVersion;
 use relay_base_schema::events::EventType;
+use relay_common::time::UnixTimestamp;
 use relay_config::Config;
 use relay_dynamic_config::Feature;
-use relay_event_normalization::{nel, ClockDriftProcessor};
+use relay_event_normalization::grouping::{self, GroupingStrategy};
+use relay_event_normalization::{nel, ClockDriftProcessor, GeoIpLookup};
 use relay_event_schema::processor::{self, ProcessingState};
 use relay_event_schema::protocol::{
     Breadcrumb, Csp, Event, ExpectCt, ExpectStaple, Hpkp, LenientString, NetworkReportError,
     OtelContext, RelayInfo, SecurityReportType, Timestamp, Values,
 };
-use relay_pii::PiiProcessor;
+use relay_filter::abuse::AbuseFilter;
+use relay_filter::bot_score::{self, RequestSignals};
+use relay_pii::ScrubEventError;
 use relay_protocol::{Annotated, Array, FromValue, Object, Value};
 use relay_quotas::DataCategory;
 use relay_statsd::metric;
@@ -22,7 +26,7 @@ use serde_json::Value as SerdeValue;
 
 #[cfg(feature = "processing")]
 use {
-    relay_event_normalization::{GeoIpLookup, StoreConfig, StoreProcessor},
+    relay_event_normalization::{StoreConfig, StoreProcessor},
     relay_event_schema::protocol::IpAddr,
 };
 
@@ -265,6 +269,69 @@ pub fn finalize(state: &mut ProcessEnvelopeState, config: &Config) -> Result<(),
     Ok(())
 }
 
+/// Computes the bot score of the event and writes it to the event's tags.
+///
+/// This only runs if the project configures the bot score filter, and must run before
+/// [`filter`]. If the event does not have geo information yet, the user's IP address is looked
+/// up in the GeoIP database.
+pub fn tag_bot_score(state: &mut ProcessEnvelopeState, geoip_lookup: Option<&GeoIpLookup>) {
+    if state
+        .project_state
+        .config
+        .filter_settings
+        .bot_score
+        .is_empty()
+    {
+        return;
+    }
+
+    let Some(event) = state.event.value_mut() else {
+        return;
+    };
+
+    let ip_geo = match (event.user.value(), geoip_lookup) {
+        (Some(user), Some(geoip_lookup)) if user.geo.value().is_none() => user
+            .ip_address
+            .value()
+            .and_then(|ip_address| geoip_lookup.lookup(ip_address.as_str()).ok().flatten()),
+        _ => None,
+    };
+
+    let meta = state.managed_envelope.envelope().meta();
+    let request = RequestSignals {
+        user_agent: meta.user_agent(),
+        has_client_hints: meta.client_hints().sec_ch_ua.is_some(),
+        has_origin: meta.origin().is_some(),
+        geo: ip_geo.as_ref(),
+    };
+
+    let score = bot_score::score(event, &request);
+    bot_score::set_score(event, score);
+}
+
+/// Computes the grouping hash of the event with the project's grouping strategy.
+///
+/// Hashes sent by clients are always overwritten, and removed if the project's grouping config is
+/// not supported by Relay. This must run after stack traces have been resolved and before
+/// [`filter_grouped`], [`filter_abuse`], and deduplication, which all use the grouping hash.
+pub fn compute_grouping_hash(state: &mut ProcessEnvelopeState) {
+    let strategy = state
+        .project_state
+        .config
+        .grouping_config
+        .as_ref()
+        .and_then(GroupingStrategy::from_config);
+
+    let Some(event) = state.event.value_mut() else {
+        return;
+    };
+
+    event.grouping_hash = match strategy {
+        Some(strategy) => Annotated::from(grouping::compute_hash(event, strategy)),
+        None => Annotated::empty(),
+    };
+}
+
 pub fn filter(state: &mut ProcessEnvelopeState) -> Result<(), ProcessingError> {
     let event = match state.event.value_mut() {
         Some(event) => event,
@@ -286,35 +353,71 @@ pub fn filter(state: &mut ProcessEnvelopeState) -> Result<(), ProcessingError> {
     })
 }
 
+/// Applies generic inbound filters with conditions on the grouping hash of the event.
+///
+/// All other filters are applied by [`filter`] before stack traces are resolved, so that filtered
+/// events do not incur the cost of resolution.
+pub fn filter_grouped(state: &mut ProcessEnvelopeState) -> Result<(), ProcessingError> {
+    let Some(event) = state.event.value() else {
+        return Ok(());
+    };
+
+    let filter_settings = &state.project_state.config.filter_settings;
+
+    metric!(timer(RelayTimers::EventProcessingFiltering), {
+        relay_filter::should_filter_grouped(event, filter_settings).map_err(|err| {
+            state
+                .managed_envelope
+                .reject(Outcome::Filtered(err.clone()));
+            ProcessingError::EventFiltered(err)
+        })
+    })
+}
+
+/// Applies the abuse filter, which counts events by their sources.
+///
+/// The abuse filter counts events, so it must only see events that passed all other filters. For
+/// error events, this runs after [`compute_grouping_hash`] to count events by their grouping hash.
+pub fn filter_abuse(
+    state: &mut ProcessEnvelopeState,
+    abuse_filter: &AbuseFilter,
+) -> Result<(), ProcessingError> {
+    let Some(event) = state.event.value() else {
+        return Ok(());
+    };
+
+    let client_ip = state.managed_envelope.envelope().meta().client_addr();
+    let config = &state.project_state.config.filter_settings.abuse;
+
+    metric!(timer(RelayTimers::EventProcessingFiltering), {
+        abuse_filter
+            .should_filter(state.project_id, event, client_ip, config, UnixTimestamp::now())
+            .map_err(|err| {
+                state
+                    .managed_envelope
+                    .reject(Outcome::Filtered(err.clone()));
+                ProcessingError::EventFiltered(err)
+            })
+    })
+}
+
 /// Apply data privacy rules to the event payload.
 ///
 /// This uses both the general `datascrubbing_settings`, as well as the the PII rules.
 pub fn scrub(state: &mut ProcessEnvelopeState) -> Result<(), ProcessingError> {
-    let event = &mut state.event;
     let config = &state.project_state.config;
 
-    if config.datascrubbing_settings.scrub_data {
-        if let Some(event) = event.value_mut() {
-            relay_pii::scrub_graphql(event);
-        }
-    }
-
     metric!(timer(RelayTimers::EventProcessingPii), {
-        if let Some(ref config) = config.pii_config {
-            let mut processor = PiiProcessor::new(config.compiled());
-            processor::process_value(event, &mut processor, ProcessingState::root())?;
-        }
-        let pii_config = config
-            .datascrubbing_settings
-            .pii_config()
-            .map_err(|e| ProcessingError::PiiConfigError(e.clone()))?;
-        if let Some(config) = pii_config {
-            let mut processor = PiiProcessor::new(config.compiled());
-            processor::process_value(event, &mut processor, ProcessingState::root())?;
-        }
-    });
-
-    Ok(())
+        relay_pii::scrub_event(
+            &mut state.event,
+            config.pii_config.as_ref(),
+            &config.datascrubbing_settings,
+        )
+    })
+    .map_err(|error| match error {
+        ScrubEventError::InvalidConfig(error) => ProcessingError::PiiConfigError(error),
+        ScrubEventError::ProcessingFailed(action) => ProcessingError::ProcessingFailed(action),
+    })
 }
 
 pub fn serialize(state: &mut ProcessEnvelopeState) -> Result<(), ProcessingError> {
@@ -49,6 +49,7 @@ pub fn process(state: &mut ProcessEnvelopeState, config: &Config) -> Result<(),
         client_hints: meta.client_hints().as_deref(),
     };
 
+    let mut filtered = None;
     state.managed_envelope.retain_items(|item| match item.ty() {
         ItemType::ReplayEvent => {
             if !replays_enabled {
@@ -69,14 +70,11 @@ pub fn process(state: &mut ProcessEnvelopeState, config: &Config) -> Result<(),
                         ItemAction::Keep
                     }
                 },
-                Err(error) => {
-                    relay_log::warn!(error = &error as &dyn Error, "invalid replay event");
-                    ItemAction::Drop(Outcome::Invalid(match error {
-                        ReplayError::NoContent => DiscardReason::InvalidReplayEventNoPayload,
-                        ReplayError::CouldNotScrub(_) => DiscardReason::InvalidReplayEventPii,
-                        ReplayError::CouldNotParse(_) => DiscardReason::InvalidReplayEvent,
-                        ReplayError::InvalidPayload(_) => DiscardReason::InvalidReplayEvent,
-                    }))
+                Err(outcome) => {
+                    if let Outcome::Filtered(ref filter_stat_key) = outcome {
+                        filtered = Some(filter_stat_key.clone());
+                    }
+                    ItemAction::Drop(outcome)
                 }
             }
         }
@@ -113,29 +111,64 @@ pub fn process(state: &mut ProcessEnvelopeState, config: &Config) -> Result<(),
         _ => ItemAction::Keep,
     });
 
+    // Recordings belong to the replay event, so they are dropped along with a filtered event.
+    if let Some(filter_stat_key) = filtered {
+        state.managed_envelope.retain_items(|item| match item.ty() {
+            ItemType::ReplayRecording => {
+                ItemAction::Drop(Outcome::Filtered(filter_stat_key.clone()))
+            }
+            _ => ItemAction::Keep,
+        });
+    }
+
     Ok(())
 }
 
-/// Validates, normalizes, and scrubs PII from a replay event.
+/// Returns the outcome for an invalid replay event.
+fn invalid_replay_event(error: ReplayError) -> Outcome {
+    relay_log::warn!(error = &error as &dyn Error, "invalid replay event");
+    Outcome::Invalid(match error {
+        ReplayError::NoContent => DiscardReason::InvalidReplayEventNoPayload,
+        ReplayError::CouldNotScrub(_) => DiscardReason::InvalidReplayEventPii,
+        ReplayError::CouldNotParse(_) => DiscardReason::InvalidReplayEvent,
+        ReplayError::InvalidPayload(_) => DiscardReason::InvalidReplayEvent,
+    })
+}
+
+/// Validates, normalizes, filters, and scrubs PII from a replay event.
+///
+/// Inbound filters are applied after normalization and before scrubbing, so that their conditions
+/// see the original user data.
 fn process_replay_event(
     payload: &Bytes,
     config: &ProjectConfig,
     client_ip: Option<IpAddr>,
     user_agent: &RawUserAgentInfo<&str>,
-) -> Result<Annotated<Replay>, ReplayError> {
-    let mut replay =
-        Annotated::<Replay>::from_json_bytes(payload).map_err(ReplayError::CouldNotParse)?;
+) -> Result<Annotated<Replay>, Outcome> {
+    let mut replay = Annotated::<Replay>::from_json_bytes(payload)
+        .map_err(|e| invalid_replay_event(ReplayError::CouldNotParse(e)))?;
 
     if let Some(replay_value) = replay.value_mut() {
-        replay::validate(replay_value)?;
+        replay::validate(replay_value).map_err(invalid_replay_event)?;
         replay::normalize(replay_value, client_ip, user_agent);
+        relay_filter::should_filter_item(&*replay_value, &config.filter_settings)
+            .map_err(Outcome::Filtered)?;
     } else {
-        return Err(ReplayError::NoContent);
+        return Err(invalid_replay_event(ReplayError::NoContent));
     }
 
+    scrub_replay_event(&mut replay, config).map_err(invalid_replay_event)?;
+    Ok(replay)
+}
+
+/// Applies the PII config and datascrubbing settings of the project to a replay event.
+fn scrub_replay_event(
+    replay: &mut Annotated<Replay>,
+    config: &ProjectConfig,
+) -> Result<(), ReplayError> {
     if let Some(ref config) = config.pii_config {
         let mut processor = PiiProcessor::new(config.compiled());
-        processor::process_value(&mut replay, &mut processor, ProcessingState::root())
+        processor::process_value(replay, &mut processor, ProcessingState::root())
             .map_err(|e| ReplayError::CouldNotScrub(e.to_string()))?;
     }
 
@@ -145,9 +178,9 @@ fn process_replay_event(
         .map_err(|e| ReplayError::CouldNotScrub(e.to_string()))?;
     if let Some(config) = pii_config {
         let mut processor = PiiProcessor::new(config.compiled());
-        processor::process_value(&mut replay, &mut processor, ProcessingState::root())
+        processor::process_value(replay, &mut processor, ProcessingState::root())
             .map_err(|e| ReplayError::CouldNotScrub(e.to_string()))?;
     }
 
-    Ok(replay)
+    Ok(())
 }
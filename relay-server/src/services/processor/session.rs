@@ -8,24 +8,28 @@ use relay_config::Config;
 use relay_dynamic_config::SessionMetricsConfig;
 use relay_event_normalization::ClockDriftProcessor;
 use relay_event_schema::protocol::{
-    IpAddr, SessionAggregates, SessionAttributes, SessionStatus, SessionUpdate,
+    IpAddr, SessionAggregates, SessionAttributes, SessionLike, SessionStatus, SessionUpdate,
 };
+use relay_filter::{FilterStatKey, FiltersConfig};
 use relay_metrics::Bucket;
+use relay_quotas::DataCategory;
 use relay_statsd::metric;
 
 use crate::envelope::{ContentType, Item, ItemType};
+use crate::services::outcome::Outcome;
 use crate::services::processor::{ProcessEnvelopeState, MINIMUM_CLOCK_DRIFT};
 use crate::statsd::RelayTimers;
 use crate::utils::ItemAction;
 
 /// Validates all sessions and session aggregates in the envelope, if any.
 ///
-/// Both are removed from the envelope if they contain invalid JSON or if their timestamps
-/// are out of range after clock drift correction.
+/// Both are removed from the envelope if they contain invalid JSON, if their timestamps
+/// are out of range after clock drift correction, or if they match a generic inbound filter.
 pub fn process(state: &mut ProcessEnvelopeState, config: &Config) {
     let received = state.managed_envelope.received_at();
     let extracted_metrics = &mut state.extracted_metrics.project_metrics;
     let metrics_config = state.project_state.config().session_metrics;
+    let filter_settings = &state.project_state.config().filter_settings;
     let envelope = state.managed_envelope.envelope_mut();
     let client = envelope.meta().client().map(|x| x.to_owned());
     let client_addr = envelope.meta().client_addr();
@@ -33,8 +37,9 @@ pub fn process(state: &mut ProcessEnvelopeState, config: &Config) {
     let clock_drift_processor =
         ClockDriftProcessor::new(envelope.sent_at(), received).at_least(MINIMUM_CLOCK_DRIFT);
 
+    let mut filtered = Vec::new();
     state.managed_envelope.retain_items(|item| {
-        let should_keep = match item.ty() {
+        let action = match item.ty() {
             ItemType::Session => process_session(
                 item,
                 config,
@@ -42,6 +47,7 @@ pub fn process(state: &mut ProcessEnvelopeState, config: &Config) {
                 client.as_deref(),
                 client_addr,
                 metrics_config,
+                filter_settings,
                 &clock_drift_processor,
                 extracted_metrics,
             ),
@@ -52,17 +58,41 @@ pub fn process(state: &mut ProcessEnvelopeState, config: &Config) {
                 client.as_deref(),
                 client_addr,
                 metrics_config,
+                filter_settings,
                 &clock_drift_processor,
                 extracted_metrics,
             ),
-            _ => true, // Keep all other item types
+            _ => SessionAction::Keep, // Keep all other item types
         };
-        if should_keep {
-            ItemAction::Keep
-        } else {
-            ItemAction::DropSilently // sessions never log outcomes.
+        match action {
+            SessionAction::Keep => ItemAction::Keep,
+            SessionAction::Drop => ItemAction::DropSilently, // sessions never log outcomes.
+            SessionAction::Filter(filter_stat_key, quantity) => {
+                filtered.push((filter_stat_key, quantity));
+                ItemAction::DropSilently
+            }
         }
     });
+
+    // Sessions have no outcome category, so outcomes of filtered sessions are tracked here.
+    for (filter_stat_key, quantity) in filtered {
+        state.managed_envelope.track_outcome(
+            Outcome::Filtered(filter_stat_key),
+            DataCategory::Session,
+            quantity as usize,
+        );
+    }
+}
+
+/// The decision on a session item, see [`process`].
+#[derive(Debug, PartialEq)]
+enum SessionAction {
+    /// Keep the item.
+    Keep,
+    /// Drop the item without logging an outcome.
+    Drop,
+    /// Drop the item and log a filtered outcome for the contained number of sessions.
+    Filter(FilterStatKey, u32),
 }
 
 /// Returns Ok(true) if attributes were modified.
@@ -129,7 +159,7 @@ fn is_valid_session_timestamp(
     true
 }
 
-/// Returns true if the item should be kept.
+/// Returns whether the item should be kept.
 #[allow(clippy::too_many_arguments)]
 fn process_session(
     item: &mut Item,
@@ -138,9 +168,10 @@ fn process_session(
     client: Option<&str>,
     client_addr: Option<net::IpAddr>,
     metrics_config: SessionMetricsConfig,
+    filter_settings: &FiltersConfig,
     clock_drift_processor: &ClockDriftProcessor,
     extracted_metrics: &mut Vec<Bucket>,
-) -> bool {
+) -> SessionAction {
     let mut changed = false;
     let payload = item.payload();
     let max_secs_in_future = config.max_secs_in_future();
@@ -154,13 +185,13 @@ fn process_session(
                 error = &error as &dyn Error,
                 "skipping invalid session payload"
             );
-            return false;
+            return SessionAction::Drop;
         }
     };
 
     if session.sequence == u64::MAX {
         relay_log::trace!("skipping session due to sequence overflow");
-        return false;
+        return SessionAction::Drop;
     };
 
     if clock_drift_processor.is_drifted() {
@@ -188,7 +219,7 @@ fn process_session(
     // Validate timestamps
     for t in [session.timestamp, session.started] {
         if !is_valid_session_timestamp(received, t, max_secs_in_future, max_session_secs_in_past) {
-            return false;
+            return SessionAction::Drop;
         }
     }
 
@@ -200,8 +231,13 @@ fn process_session(
         }
     }
 
+    if let Err(filter_stat_key) = relay_filter::should_filter_item(&session, filter_settings) {
+        relay_log::trace!("skipping session filtered by {filter_stat_key}");
+        return SessionAction::Filter(filter_stat_key, 1);
+    }
+
     if config.processing_enabled() && matches!(session.status, SessionStatus::Unknown(_)) {
-        return false;
+        return SessionAction::Drop;
     }
 
     // Extract metrics if they haven't been extracted by a prior Relay
@@ -221,7 +257,7 @@ fn process_session(
 
     // Drop the session if metrics have been extracted in this or a prior Relay
     if metrics_config.should_drop() && item.metrics_extracted() {
-        return false;
+        return SessionAction::Drop;
     }
 
     if changed {
@@ -229,14 +265,14 @@ fn process_session(
             Ok(json) => json,
             Err(err) => {
                 relay_log::error!(error = &err as &dyn Error, "failed to serialize session");
-                return false;
+                return SessionAction::Drop;
             }
         };
 
         item.set_payload(ContentType::Json, json_string);
     }
 
-    true
+    SessionAction::Keep
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -247,9 +283,10 @@ fn process_session_aggregates(
     client: Option<&str>,
     client_addr: Option<net::IpAddr>,
     metrics_config: SessionMetricsConfig,
+    filter_settings: &FiltersConfig,
     clock_drift_processor: &ClockDriftProcessor,
     extracted_metrics: &mut Vec<Bucket>,
-) -> bool {
+) -> SessionAction {
     let mut changed = false;
     let payload = item.payload();
     let max_secs_in_future = config.max_secs_in_future();
@@ -262,7 +299,7 @@ fn process_session_aggregates(
                 error = &error as &dyn Error,
                 "skipping invalid sessions payload"
             );
-            return false;
+            return SessionAction::Drop;
         }
     };
 
@@ -286,7 +323,7 @@ fn process_session_aggregates(
 
     // Aftter timestamp validation, aggregates could now be empty
     if session.aggregates.is_empty() {
-        return false;
+        return SessionAction::Drop;
     }
 
     // Validate attributes
@@ -297,6 +334,14 @@ fn process_session_aggregates(
         }
     }
 
+    if let Err(filter_stat_key) = relay_filter::should_filter_item(&session, filter_settings) {
+        relay_log::trace!("skipping session filtered by {filter_stat_key}");
+        let quantity = session.aggregates.iter().fold(0u32, |count, aggregate| {
+            count.saturating_add(aggregate.total_count())
+        });
+        return SessionAction::Filter(filter_stat_key, quantity);
+    }
+
     // Extract metrics if they haven't been extracted by a prior Relay
     if metrics_config.is_enabled() && !item.metrics_extracted() {
         for aggregate in &session.aggregates {
@@ -313,7 +358,7 @@ fn process_session_aggregates(
 
     // Drop the aggregate if metrics have been extracted in this or a prior Relay
     if metrics_config.should_drop() && item.metrics_extracted() {
-        return false;
+        return SessionAction::Drop;
     }
 
     if changed {
@@ -321,14 +366,14 @@ fn process_session_aggregates(
             Ok(json) => json,
             Err(err) => {
                 relay_log::error!(error = &err as &dyn Error, "failed to serialize session");
-                return false;
+                return SessionAction::Drop;
             }
         };
 
         item.set_payload(ContentType::Json, json_string);
     }
 
-    true
+    SessionAction::Keep
 }
 
 #[cfg(test)]
@@ -343,12 +388,17 @@ mod tests {
         client: Option<&'a str>,
         client_addr: Option<net::IpAddr>,
         metrics_config: SessionMetricsConfig,
+        filter_settings: FiltersConfig,
         clock_drift_processor: ClockDriftProcessor,
         extracted_metrics: Vec<Bucket>,
     }
 
     impl<'a> TestProcessSessionArguments<'a> {
         fn run_session_producer(&mut self) -> bool {
+            self.run() == SessionAction::Keep
+        }
+
+        fn run(&mut self) -> SessionAction {
             process_session(
                 &mut self.item,
                 &Config::default(),
@@ -356,6 +406,7 @@ mod tests {
                 self.client,
                 self.client_addr,
                 self.metrics_config,
+                &self.filter_settings,
                 &self.clock_drift_processor,
                 &mut self.extracted_metrics,
             )
@@ -392,6 +443,7 @@ mod tests {
         }",
                 )
                 .unwrap(),
+                filter_settings: FiltersConfig::default(),
                 clock_drift_processor: ClockDriftProcessor::new(None, received),
                 extracted_metrics: vec![],
             }
@@ -448,4 +500,26 @@ mod tests {
         args.item.set_metrics_extracted(true);
         assert!(!args.run_session_producer());
     }
+
+    #[test]
+    fn test_process_session_filtered() {
+        let mut args = TestProcessSessionArguments::default();
+        args.filter_settings = serde_json::from_str(
+            r#"{
+                "generic": {
+                    "version": 1,
+                    "filters": [{
+                        "id": "oldReleases",
+                        "isEnabled": true,
+                        "condition": {"op": "glob", "name": "session.release", "value": ["1.*"]}
+                    }]
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            args.run(),
+            SessionAction::Filter(FilterStatKey::GenericFilter("oldReleases".to_owned()), 1)
+        );
+    }
 }
@@ -0,0 +1,390 @@
+//! Source map resolution of JavaScript stack traces.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use relay_base_schema::project::ProjectId;
+use relay_config::Config;
+use relay_event_normalization::sourcemap::{SourceMap, SourceMapProcessor};
+use relay_event_schema::processor::{self, ProcessingState};
+use relay_system::Addr;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::http::StatusCode;
+use crate::services::processor::ProcessEnvelopeState;
+use crate::services::upstream::{Method, RequestPriority, SendQuery, UpstreamQuery, UpstreamRelay};
+use crate::utils::FileCache;
+
+/// The maximum number of source maps fetched from the upstream at the same time.
+///
+/// Lookups of other source maps are skipped while this many fetches are in flight.
+const MAX_CONCURRENT_FETCHES: usize = 10;
+
+/// Upstream query to fetch the source map of a minified file.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSourceMap {
+    /// The project that uploaded the source map.
+    pub project_id: ProjectId,
+    /// The release of the event.
+    pub release: String,
+    /// The distribution of the event, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dist: Option<String>,
+    /// The absolute URL of the minified file.
+    pub url: String,
+}
+
+/// Response of the [`GetSourceMap`] query.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetSourceMapResponse {
+    /// The JSON contents of the source map, if the upstream has one for the file.
+    pub sourcemap: Option<String>,
+}
+
+impl UpstreamQuery for GetSourceMap {
+    type Response = GetSourceMapResponse;
+
+    fn method(&self) -> Method {
+        Method::POST
+    }
+
+    fn path(&self) -> Cow<'static, str> {
+        Cow::Borrowed("/api/0/relays/sourcemaps/")
+    }
+
+    fn priority() -> RequestPriority {
+        RequestPriority::Low
+    }
+
+    fn retry() -> bool {
+        false
+    }
+
+    fn route(&self) -> &'static str {
+        "sourcemaps"
+    }
+}
+
+/// Identifies a cached source map.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum CacheKey {
+    /// A source map in the local directory.
+    Local(PathBuf),
+    /// A source map fetched from the upstream.
+    Upstream(GetSourceMap),
+}
+
+/// Fetches source maps from the upstream in the background.
+struct Upstream {
+    relay: Addr<UpstreamRelay>,
+    /// Source maps that are currently fetched, bounded by [`MAX_CONCURRENT_FETCHES`].
+    pending: Mutex<HashSet<GetSourceMap>>,
+    /// Set if the upstream does not serve source maps.
+    disabled: AtomicBool,
+}
+
+/// Loads source maps from a local directory and the upstream, and caches them in memory.
+#[derive(Clone)]
+pub struct SourceMapCache {
+    path: Option<PathBuf>,
+    upstream: Option<Arc<Upstream>>,
+    entries: Arc<FileCache<CacheKey, SourceMap>>,
+}
+
+impl SourceMapCache {
+    /// Creates a source map cache from the config, or `None` if source maps are disabled.
+    pub fn new(config: &Config, upstream_relay: Addr<UpstreamRelay>) -> Option<Self> {
+        if !config.sourcemaps_enabled() {
+            return None;
+        }
+
+        let upstream = config.sourcemaps_fetch_upstream().then(|| {
+            Arc::new(Upstream {
+                relay: upstream_relay,
+                pending: Mutex::new(HashSet::new()),
+                disabled: AtomicBool::new(false),
+            })
+        });
+
+        Some(Self {
+            path: config.sourcemaps_path().map(|path| path.to_path_buf()),
+            upstream,
+            entries: Arc::new(FileCache::new(
+                config.sourcemaps_max_cache_size(),
+                config.sourcemaps_cache_expiry(),
+                SourceMap::size,
+            )),
+        })
+    }
+
+    /// Loads the source map of a minified file from the local directory.
+    fn load_local(
+        &self,
+        project_id: ProjectId,
+        release: &str,
+        abs_path: &str,
+    ) -> Option<Arc<SourceMap>> {
+        let root = self.path.as_deref()?;
+        let relative = relative_path(abs_path)?;
+        let path = root
+            .join(project_id.to_string())
+            .join(release_segment(release)?)
+            .join(&relative);
+
+        self.entries
+            .get_or_load(&CacheKey::Local(path.clone()), || {
+                let data = std::fs::read(&path).ok()?;
+                let source_map = parse(&data, abs_path)?;
+                // Release artifacts are referenced relative to the root of the release.
+                Some(source_map.with_url(format!("~/{relative}")))
+            })
+    }
+
+    /// Returns the source map fetched from the upstream, or starts fetching it in the background.
+    fn load_upstream(&self, query: GetSourceMap) -> Option<Arc<SourceMap>> {
+        let upstream = self.upstream.as_ref()?;
+        if upstream.disabled.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let key = CacheKey::Upstream(query.clone());
+        if let Some(source_map) = self.entries.get(&key) {
+            return source_map;
+        }
+
+        {
+            let mut pending = upstream.pending.lock().unwrap_or_else(|e| e.into_inner());
+            if pending.len() >= MAX_CONCURRENT_FETCHES || !pending.insert(query.clone()) {
+                return None;
+            }
+        }
+
+        let upstream = upstream.clone();
+        let entries = self.entries.clone();
+        tokio::spawn(async move {
+            let url = query.url.clone();
+            let result = upstream.relay.send(SendQuery(query.clone())).await;
+
+            match result {
+                Ok(Ok(response)) => {
+                    let source_map = response
+                        .sourcemap
+                        .and_then(|data| parse(data.as_bytes(), &url))
+                        .map(|source_map| source_map.with_url(format!("{url}.map")));
+                    entries.insert(key, source_map);
+                }
+                Ok(Err(error)) if error.status_code() == Some(StatusCode::NOT_FOUND) => {
+                    relay_log::warn!("upstream does not serve source maps, disabling fetching");
+                    upstream.disabled.store(true, Ordering::Relaxed);
+                }
+                Ok(Err(error)) => {
+                    relay_log::debug!(error = &error as &dyn Error, "failed to fetch source map");
+                    entries.insert(key, None);
+                }
+                Err(_) => (),
+            }
+
+            let mut pending = upstream.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.remove(&query);
+        });
+
+        None
+    }
+
+    /// Looks up the source map of a minified file for a release of a project.
+    ///
+    /// Source maps in the local directory take precedence over those fetched from the upstream.
+    fn lookup(
+        &self,
+        project_id: ProjectId,
+        release: &str,
+        dist: Option<&str>,
+        abs_path: &str,
+    ) -> Option<Arc<SourceMap>> {
+        if let Some(source_map) = self.load_local(project_id, release, abs_path) {
+            return Some(source_map);
+        }
+
+        self.load_upstream(GetSourceMap {
+            project_id,
+            release: release.to_owned(),
+            dist: dist.map(str::to_owned),
+            url: abs_path.to_owned(),
+        })
+    }
+}
+
+/// Parses a source map and logs failures.
+fn parse(data: &[u8], abs_path: &str) -> Option<SourceMap> {
+    match SourceMap::from_slice(data) {
+        Ok(source_map) => Some(source_map),
+        Err(error) => {
+            relay_log::debug!(
+                error = &error as &dyn Error,
+                abs_path,
+                "failed to parse source map"
+            );
+            None
+        }
+    }
+}
+
+/// Returns the directory name of a release in the source map directory.
+///
+/// Returns `None` if the release cannot be used as a single path segment.
+fn release_segment(release: &str) -> Option<&str> {
+    let invalid = release.is_empty()
+        || release == "."
+        || release == ".."
+        || release.contains('/')
+        || release.contains('\\');
+
+    (!invalid).then_some(release)
+}
+
+/// Returns the path of the source map for a minified file, relative to the release directory.
+///
+/// Returns `None` if the path of the minified file would escape the directory.
+fn relative_path(abs_path: &str) -> Option<String> {
+    let url_path;
+    let path = match Url::parse(abs_path) {
+        Ok(url) => {
+            url_path = url.path().to_owned();
+            url_path.as_str()
+        }
+        Err(_) => abs_path,
+    };
+
+    let mut segments = Vec::new();
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        if segment == "." || segment == ".." || segment.contains('\\') {
+            return None;
+        }
+
+        segments.push(segment);
+    }
+
+    if segments.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}.map", segments.join("/")))
+}
+
+/// Resolves minified JavaScript frames in the event using source maps.
+///
+/// Source maps are looked up by the project and release of the event, so events without a release
+/// are not resolved.
+pub fn process(state: &mut ProcessEnvelopeState, cache: Option<&SourceMapCache>) {
+    let Some(cache) = cache else {
+        return;
+    };
+
+    let project_id = state.project_id;
+    let Some(event) = state.event.value() else {
+        return;
+    };
+
+    let Some(release) = event.release.as_str().map(str::to_owned) else {
+        return;
+    };
+    let dist = event.dist.as_str().map(str::to_owned);
+
+    let mut processor = SourceMapProcessor::new(|abs_path: &str| {
+        cache.lookup(project_id, &release, dist.as_deref(), abs_path)
+    });
+
+    processor::process_value(&mut state.event, &mut processor, ProcessingState::root()).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCEMAP: &str =
+        r#"{"version": 3, "sources": ["app.js"], "names": [], "mappings": "AAAA"}"#;
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            relative_path("https://example.com/static/app.min.js?v=1").as_deref(),
+            Some("static/app.min.js.map")
+        );
+        assert_eq!(
+            relative_path("app:///main.js").as_deref(),
+            Some("main.js.map")
+        );
+        assert_eq!(
+            relative_path("/var/app/dist/index.js").as_deref(),
+            Some("var/app/dist/index.js.map")
+        );
+
+        assert_eq!(relative_path("https://example.com/"), None);
+        assert_eq!(relative_path("static/../../etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_release_segment() {
+        assert_eq!(release_segment("app@1.0.0"), Some("app@1.0.0"));
+        assert_eq!(release_segment(""), None);
+        assert_eq!(release_segment(".."), None);
+        assert_eq!(release_segment("feature/1.0"), None);
+    }
+
+    #[test]
+    fn test_local_source_map() {
+        let dir = tempfile::tempdir().unwrap();
+        let release_dir = dir.path().join("42/1.0/static");
+        std::fs::create_dir_all(&release_dir).unwrap();
+        std::fs::write(release_dir.join("app.min.js.map"), SOURCEMAP).unwrap();
+
+        let config = Config::from_json_value(serde_json::json!({
+            "sourcemaps": {"path": dir.path()}
+        }))
+        .unwrap();
+
+        let cache = SourceMapCache::new(&config, Addr::dummy()).unwrap();
+        let project_id = ProjectId::new(42);
+
+        let abs_path = "https://example.com/static/app.min.js";
+        let source_map = cache.lookup(project_id, "1.0", None, abs_path).unwrap();
+        assert_eq!(source_map.lookup(0, 0).unwrap().source, "app.js");
+        assert_eq!(source_map.url(), Some("~/static/app.min.js.map"));
+
+        let abs_path = "https://example.com/static/vendor.min.js";
+        assert!(cache.lookup(project_id, "1.0", None, abs_path).is_none());
+    }
+
+    #[test]
+    fn test_local_source_map_scoped() {
+        let dir = tempfile::tempdir().unwrap();
+        let release_dir = dir.path().join("42/1.0/static");
+        std::fs::create_dir_all(&release_dir).unwrap();
+        std::fs::write(release_dir.join("app.min.js.map"), SOURCEMAP).unwrap();
+
+        let config = Config::from_json_value(serde_json::json!({
+            "sourcemaps": {"path": dir.path()}
+        }))
+        .unwrap();
+
+        let cache = SourceMapCache::new(&config, Addr::dummy()).unwrap();
+        let abs_path = "https://example.com/static/app.min.js";
+
+        // Other projects and releases do not see the source map.
+        assert!(cache
+            .lookup(ProjectId::new(43), "1.0", None, abs_path)
+            .is_none());
+        assert!(cache
+            .lookup(ProjectId::new(42), "2.0", None, abs_path)
+            .is_none());
+        assert!(cache
+            .lookup(ProjectId::new(42), "1.0", None, abs_path)
+            .is_some());
+    }
+}
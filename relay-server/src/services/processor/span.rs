@@ -1,23 +1,35 @@
 //! Processor code related to standalone spans.
 
 use relay_dynamic_config::Feature;
+use relay_event_schema::protocol::Span;
+use relay_filter::{FilterStatKey, FiltersConfig};
+use relay_protocol::Annotated;
 
-use crate::{envelope::ItemType, services::processor::ProcessEnvelopeState, utils::ItemAction};
+use crate::envelope::{Item, ItemType};
+use crate::services::outcome::Outcome;
+use crate::services::processor::ProcessEnvelopeState;
+use crate::utils::ItemAction;
 
 #[cfg(feature = "processing")]
 mod processing;
 #[cfg(feature = "processing")]
 pub use processing::*;
 
+/// Drops standalone spans if ingestion is disabled and applies generic inbound filters.
+///
+/// This runs before quotas are enforced, so that filtered spans do not count towards quotas.
 pub fn filter(state: &mut ProcessEnvelopeState) {
     let standalone_span_ingestion_enabled = state
         .project_state
         .has_feature(Feature::StandaloneSpanIngestion);
+    let filter_settings = &state.project_state.config.filter_settings;
     state.managed_envelope.retain_items(|item| match item.ty() {
         ItemType::OtelSpan | ItemType::Span => {
             if !standalone_span_ingestion_enabled {
                 relay_log::warn!("dropping span because feature is disabled");
                 ItemAction::DropSilently
+            } else if let Err(filter_stat_key) = should_filter(item, filter_settings) {
+                ItemAction::Drop(Outcome::Filtered(filter_stat_key))
             } else {
                 ItemAction::Keep
             }
@@ -25,3 +37,26 @@ pub fn filter(state: &mut ProcessEnvelopeState) {
         _ => ItemAction::Keep,
     });
 }
+
+/// Applies generic inbound filters to a standalone span.
+///
+/// Spans that cannot be parsed are kept here and rejected during processing.
+fn should_filter(item: &Item, filter_settings: &FiltersConfig) -> Result<(), FilterStatKey> {
+    if !filter_settings.has_generic_filters() {
+        return Ok(());
+    }
+
+    let span = match item.ty() {
+        ItemType::OtelSpan => serde_json::from_slice::<relay_spans::OtelSpan>(&item.payload())
+            .ok()
+            .map(Span::from),
+        _ => Annotated::<Span>::from_json_bytes(&item.payload())
+            .ok()
+            .and_then(Annotated::into_value),
+    };
+
+    match span {
+        Some(span) => relay_filter::should_filter_item(&span, filter_settings),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,212 @@
+//! Symbolication of native stack traces with local debug files.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use relay_config::Config;
+use relay_event_normalization::symbolication::{NativeImage, SymbolFile, SymbolicationProcessor};
+use relay_event_schema::processor::{self, ProcessingState};
+
+use crate::services::processor::ProcessEnvelopeState;
+use crate::utils::FileCache;
+
+/// Loads debug files from a local directory and caches the parsed symbols in memory.
+pub struct SymbolCache {
+    path: PathBuf,
+    entries: FileCache<PathBuf, SymbolFile>,
+}
+
+impl SymbolCache {
+    /// Creates a symbol cache from the config, or `None` if no symbol directory is configured.
+    pub fn new(config: &Config) -> Option<Self> {
+        let path = config.symbols_path()?;
+
+        Some(Self {
+            path: path.to_path_buf(),
+            entries: FileCache::new(
+                config.symbols_max_cache_size(),
+                config.symbols_cache_expiry(),
+                SymbolFile::size,
+            ),
+        })
+    }
+
+    /// Loads and parses the debug file at the given path, or returns it from the cache.
+    ///
+    /// Concurrent loads of the same debug file wait for a single parse.
+    fn load(&self, path: PathBuf) -> Option<Arc<SymbolFile>> {
+        self.entries.get_or_load(&path, || {
+            let data = std::fs::read(&path).ok()?;
+            parse(&data, &path)
+        })
+    }
+
+    /// Looks up the symbols of a native image in the symbol directory.
+    ///
+    /// Breakpad symbol files take precedence over ELF debug files.
+    fn lookup(&self, image: &NativeImage) -> Option<Arc<SymbolFile>> {
+        candidate_paths(&self.path, image)
+            .into_iter()
+            .find_map(|path| self.load(path))
+    }
+}
+
+/// Parses a debug file and logs failures.
+fn parse(data: &[u8], path: &Path) -> Option<SymbolFile> {
+    match SymbolFile::parse(data) {
+        Ok(symbols) => Some(symbols),
+        Err(error) => {
+            relay_log::debug!(
+                error = &error as &dyn Error,
+                path = %path.display(),
+                "failed to parse debug file"
+            );
+            None
+        }
+    }
+}
+
+/// Returns the paths at which debug files for an image may be stored in the symbol directory.
+fn candidate_paths(root: &Path, image: &NativeImage) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    if let (Some(name), Some(debug_id)) = (image.debug_file_name(), &image.debug_id) {
+        // Breakpad omits the extension of PDB files in the name of the symbol file.
+        let sym_name = name
+            .len()
+            .checked_sub(4)
+            .filter(|&index| name.is_char_boundary(index))
+            .filter(|&index| name[index..].eq_ignore_ascii_case(".pdb"))
+            .map_or(name, |index| &name[..index]);
+
+        if is_safe_segment(name) {
+            paths.push(
+                root.join(name)
+                    .join(debug_id.0.breakpad().to_string())
+                    .join(format!("{sym_name}.sym")),
+            );
+        }
+    }
+
+    if let Some(code_id) = image.code_id.as_deref() {
+        if code_id.len() > 2 && code_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            let (prefix, rest) = code_id.split_at(2);
+            paths.push(
+                root.join(".build-id")
+                    .join(prefix)
+                    .join(format!("{rest}.debug")),
+            );
+        }
+    }
+
+    paths
+}
+
+/// Returns `true` if the file name can be used as a path segment without escaping the directory.
+fn is_safe_segment(name: &str) -> bool {
+    name != "." && name != ".."
+}
+
+/// Resolves native frames in the event using local debug files.
+pub fn process(state: &mut ProcessEnvelopeState, cache: Option<&SymbolCache>) {
+    let Some(cache) = cache else {
+        return;
+    };
+
+    if state.event.value().is_none() {
+        return;
+    }
+
+    let mut processor = SymbolicationProcessor::new(|image: &NativeImage| cache.lookup(image));
+    processor::process_value(&mut state.event, &mut processor, ProcessingState::root()).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use relay_event_schema::protocol::DebugImage;
+    use relay_protocol::Annotated;
+
+    use super::*;
+
+    fn image(json: &str) -> NativeImage {
+        let image = Annotated::<DebugImage>::from_json(json).unwrap();
+        NativeImage::from_debug_image(image.value().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_candidate_paths() {
+        let image = image(
+            r#"{
+                "type": "elf",
+                "code_file": "/usr/lib/libapp.so",
+                "code_id": "7dabb1b08c5e6d4eb8f1f2c5a6b7c8d9",
+                "debug_id": "b1ab7db0-5e8c-4e6d-b8f1-f2c5a6b7c8d9",
+                "image_addr": "0x1000",
+                "image_size": 4096
+            }"#,
+        );
+
+        assert_eq!(
+            candidate_paths(Path::new("/symbols"), &image),
+            vec![
+                PathBuf::from("/symbols/libapp.so/B1AB7DB05E8C4E6DB8F1F2C5A6B7C8D90/libapp.so.sym"),
+                PathBuf::from("/symbols/.build-id/7d/abb1b08c5e6d4eb8f1f2c5a6b7c8d9.debug"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_candidate_paths_pdb() {
+        let image = image(
+            r#"{
+                "type": "pe",
+                "code_file": "C:\\app\\app.exe",
+                "debug_file": "C:\\app\\app.pdb",
+                "debug_id": "3249d99d-0c40-4931-8610-f4e4fb0b6936-1",
+                "image_addr": "0x1000",
+                "image_size": 4096
+            }"#,
+        );
+
+        assert_eq!(
+            candidate_paths(Path::new("/symbols"), &image),
+            vec![PathBuf::from(
+                "/symbols/app.pdb/3249D99D0C4049318610F4E4FB0B69361/app.sym"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let sym_dir = dir
+            .path()
+            .join("libapp.so/B1AB7DB05E8C4E6DB8F1F2C5A6B7C8D90");
+        std::fs::create_dir_all(&sym_dir).unwrap();
+        std::fs::write(
+            sym_dir.join("libapp.so.sym"),
+            "MODULE Linux x86_64 B1AB7DB05E8C4E6DB8F1F2C5A6B7C8D90 libapp.so\nFUNC 0 10 0 main\n",
+        )
+        .unwrap();
+
+        let config = Config::from_json_value(serde_json::json!({
+            "symbols": {"path": dir.path()}
+        }))
+        .unwrap();
+
+        let cache = SymbolCache::new(&config).unwrap();
+        let image = image(
+            r#"{
+                "type": "elf",
+                "code_file": "/usr/lib/libapp.so",
+                "debug_id": "b1ab7db0-5e8c-4e6d-b8f1-f2c5a6b7c8d9",
+                "image_addr": "0x1000",
+                "image_size": 4096
+            }"#,
+        );
+
+        let symbols = cache.lookup(&image).unwrap();
+        assert_eq!(symbols.lookup(0x8).unwrap().function, "main");
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -13,8 +14,11 @@ use relay_metrics::{
     aggregator, Aggregator, Bucket, MergeBuckets, MetaAggregator, MetricMeta, MetricNamespace,
     MetricResourceIdentifier,
 };
+use relay_pii::PiiConfig;
 use relay_quotas::{DataCategory, ItemScoping, Quota, RateLimits, Scoping};
+use relay_sampling::config::RuleId;
 use relay_sampling::evaluation::ReservoirCounters;
+use relay_sampling::reservoir::{self, ReservoirStatus};
 use relay_statsd::metric;
 use relay_system::{Addr, BroadcastChannel};
 use serde::{Deserialize, Serialize};
@@ -27,6 +31,7 @@ use crate::services::outcome::{DiscardReason, Outcome, TrackOutcome};
 use crate::services::processor::RateLimitBuckets;
 use crate::services::processor::{EncodeMetricMeta, EnvelopeProcessor, ProjectMetrics};
 use crate::services::project_cache::{CheckedEnvelope, ProjectCache, RequestUpdate};
+use crate::services::project_updates::ProjectUpdate;
 
 use crate::extractors::RequestMeta;
 use crate::statsd::RelayCounters;
@@ -460,6 +465,8 @@ pub struct Project {
     reservoir_counters: ReservoirCounters,
     metric_meta_aggregator: MetaAggregator,
     has_pending_metric_meta: bool,
+    /// Updates pushed by the upstream that may not be part of fetched project states yet.
+    pending_update: Option<ProjectUpdate>,
 }
 
 impl Project {
@@ -477,6 +484,7 @@ impl Project {
             reservoir_counters: Arc::default(),
             metric_meta_aggregator: MetaAggregator::new(config.metrics_meta_locations_max()),
             has_pending_metric_meta: false,
+            pending_update: None,
             config,
         }
     }
@@ -496,6 +504,28 @@ impl Project {
         self.reservoir_counters.clone()
     }
 
+    /// Returns the local consumption of this project's reservoir rules.
+    pub fn reservoir_consumption(&self) -> BTreeMap<RuleId, i64> {
+        reservoir::local_consumption(&self.reservoir_counters)
+    }
+
+    /// Merges the consumption of reservoir rules returned by the upstream.
+    pub fn merge_reservoir_consumption(&self, consumption: &BTreeMap<RuleId, i64>) {
+        reservoir::merge_consumption(&self.reservoir_counters, consumption);
+    }
+
+    /// Returns the status of all reservoir rules of this project.
+    ///
+    /// Returns `None` if the project has no valid sampling config.
+    pub fn reservoir_status(&self) -> Option<Vec<ReservoirStatus>> {
+        let state = self.state_value()?;
+        let Some(ErrorBoundary::Ok(config)) = state.config.sampling.as_ref() else {
+            return None;
+        };
+
+        Some(ReservoirStatus::collect(config, &self.reservoir_counters))
+    }
+
     fn state_value(&self) -> Option<Arc<ProjectState>> {
         self.state.state_value()
     }
@@ -516,6 +546,33 @@ impl Project {
         }
     }
 
+    /// Applies a config update pushed by the upstream to the cached project state.
+    ///
+    /// Updates are only applied to valid and enabled project states, and only if they are more
+    /// recent than the cached state. The update is retained and applied again to fetched project
+    /// states that do not contain it yet.
+    ///
+    /// Returns `true` if the update was applied.
+    pub fn apply_update(&mut self, update: ProjectUpdate) -> bool {
+        let Some(state) = self.valid_state() else {
+            return false;
+        };
+
+        if state.invalid() || state.disabled() || !update.is_newer_than(&state) {
+            return false;
+        }
+
+        self.state.set_state(Arc::new(update.apply(&state)));
+        self.remove_expired_reservoir_rules();
+
+        match self.pending_update {
+            Some(ref mut pending) => pending.merge(update),
+            None => self.pending_update = Some(update),
+        }
+
+        true
+    }
+
     pub fn merge_rate_limits(&mut self, rate_limits: RateLimits) {
         self.rate_limits.merge(rate_limits);
     }
@@ -653,6 +710,8 @@ impl Project {
             return;
         }
 
+        scrub_bucket_tags(&project_state, &mut buckets);
+
         // Check rate limits if necessary:
         let quotas = project_state.config.quotas.clone();
 
@@ -999,6 +1058,17 @@ impl Project {
             return;
         };
 
+        // The fetched state may have been computed before the latest pushed update. In that case,
+        // apply the update again. Once the upstream serves the update, it is no longer needed.
+        if let Some(update) = self.pending_update.take() {
+            if state.invalid() || state.disabled() {
+                self.pending_update = Some(update);
+            } else if update.is_newer_than(&state) {
+                state = Arc::new(update.apply(&state));
+                self.pending_update = Some(update);
+            }
+        }
+
         // If the channel has `no_cache` set but we are not a `no_cache` request, we have
         // been superseeded. Put it back and let the other request take precedence.
         if channel.no_cache && !no_cache {
@@ -1237,6 +1307,53 @@ fn remove_matching_bucket_tags(metric_config: &Metrics, bucket: &mut Bucket) {
     }
 }
 
+/// Applies data scrubbing to tag values if enabled in the project's metrics config.
+///
+/// This uses both the `datascrubbing_settings` and the PII rules of the project, just like event
+/// scrubbing.
+fn scrub_bucket_tags(state: &ProjectState, buckets: &mut [Bucket]) {
+    let ErrorBoundary::Ok(metric_config) = &state.config.metrics else {
+        return;
+    };
+
+    if !metric_config.scrub_tag_values {
+        return;
+    }
+
+    // Invalid datascrubbing settings are logged when they are converted.
+    let datascrubbing_config = state
+        .config
+        .datascrubbing_settings
+        .pii_config()
+        .ok()
+        .and_then(Option::as_ref);
+
+    let configs: SmallVec<[&PiiConfig; 2]> = state
+        .config
+        .pii_config
+        .iter()
+        .chain(datascrubbing_config)
+        .collect();
+
+    if configs.is_empty() {
+        return;
+    }
+
+    for bucket in buckets {
+        let scrubbed = relay_pii::scrub_metric_tags(&configs, &mut bucket.tags);
+        if scrubbed > 0 {
+            let namespace = MetricResourceIdentifier::parse(&bucket.name)
+                .map(|mri| mri.namespace)
+                .unwrap_or(MetricNamespace::Unsupported);
+
+            metric!(
+                counter(RelayCounters::MetricTagsScrubbed) += scrubbed as i64,
+                namespace = namespace.as_str(),
+            );
+        }
+    }
+}
+
 fn is_metric_namespace_valid(state: &ProjectState, mri: &MetricResourceIdentifier) -> bool {
     match mri.namespace {
         MetricNamespace::Sessions => true,
@@ -1376,6 +1493,44 @@ mod tests {
         project.fetch_state(addr, false);
     }
 
+    #[tokio::test]
+    async fn test_apply_update() {
+        let (addr, _) = mock_service("project_cache", (), |&mut (), _| {});
+        let (aggregator, _) = mock_service("aggregator", (), |&mut (), _| {});
+        let (outcome_aggregator, _) = mock_service("outcome_aggreggator", (), |&mut (), _| {});
+        let (envelope_processor, _) = mock_service("envelope_processor", (), |&mut (), _| {});
+
+        let mut project = create_project(None);
+        let update: ProjectUpdate = serde_json::from_value(json!({
+            "lastChange": "2024-01-01T00:00:00Z",
+            "config": {"filterSettings": {"localhost": {"isEnabled": true}}}
+        }))
+        .unwrap();
+
+        assert!(project.apply_update(update.clone()));
+        let state = project.valid_state().unwrap();
+        assert!(state.config.filter_settings.localhost.is_enabled);
+
+        // The same update is not applied twice.
+        assert!(!project.apply_update(update));
+
+        // A fetched state without the update receives it again.
+        let mut fetched = ProjectState::allowed();
+        fetched.project_id = Some(ProjectId::new(42));
+        project.state_channel = Some(StateChannel::new());
+        project.update_state(
+            addr,
+            aggregator,
+            Arc::new(fetched),
+            envelope_processor,
+            outcome_aggregator,
+            false,
+        );
+
+        let state = project.valid_state().unwrap();
+        assert!(state.config.filter_settings.localhost.is_enabled);
+    }
+
     fn create_project(config: Option<serde_json::Value>) -> Project {
         let project_key = ProjectKey::parse("a94ae32be2584e0bbd7a4cbb95971fee").unwrap();
         let mut project = Project::new(project_key, Arc::new(Config::default()));
@@ -1595,6 +1750,38 @@ mod tests {
         assert_eq!(bucket.tags.len(), 1);
     }
 
+    #[test]
+    fn test_scrub_bucket_tags() {
+        let mut tags = BTreeMap::default();
+        tags.insert("email".to_string(), "jane@example.com".to_string());
+        tags.insert("environment".to_string(), "production".to_string());
+
+        let mut buckets = vec![get_test_bucket("c:custom/foo@none", tags)];
+
+        let mut project_state = ProjectState::allowed();
+        project_state.config = serde_json::from_value(json!({
+            "piiConfig": {
+                "applications": {
+                    "$string": ["@email:replace"]
+                }
+            }
+        }))
+        .unwrap();
+
+        // Scrubbing is opt-in.
+        scrub_bucket_tags(&project_state, &mut buckets);
+        assert_eq!(buckets[0].tags["email"], "jane@example.com");
+
+        project_state.config.metrics = ErrorBoundary::Ok(Metrics {
+            scrub_tag_values: true,
+            ..Default::default()
+        });
+
+        scrub_bucket_tags(&project_state, &mut buckets);
+        assert_eq!(buckets[0].tags["email"], "[email]");
+        assert_eq!(buckets[0].tags["environment"], "production");
+    }
+
     #[test]
     fn test_dont_remove_tags_if_bucket_name_not_matching() {
         let mut tags = BTreeMap::default();
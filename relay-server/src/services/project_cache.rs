@@ -1,10 +1,10 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::error::Error;
 use std::sync::Arc;
 use std::time::Duration;
 
 use relay_base_schema::project::ProjectKey;
-use relay_config::{Config, RelayMode};
+use relay_config::{Config, RelayInfo, RelayMode};
 use relay_dynamic_config::GlobalConfig;
 use relay_metrics::{Aggregator, FlushBuckets, MergeBuckets, MetricMeta};
 use relay_quotas::RateLimits;
@@ -14,6 +14,7 @@ use relay_system::{Addr, FromMessage, Interface, Sender, Service};
 use tokio::sync::mpsc;
 use tokio::time::Instant;
 
+use crate::http::StatusCode;
 use crate::services::global_config::{self, GlobalConfigManager, Subscribe};
 use crate::services::outcome::{DiscardReason, TrackOutcome};
 use crate::services::processor::{EncodeMetrics, EnvelopeProcessor, ProcessEnvelope};
@@ -21,12 +22,17 @@ use crate::services::project::{Project, ProjectSender, ProjectState};
 use crate::services::project_local::{LocalProjectSource, LocalProjectSourceService};
 #[cfg(feature = "processing")]
 use crate::services::project_redis::RedisProjectSource;
+use crate::services::project_updates::{GetProjectUpdates, GetProjectUpdatesResponse};
 use crate::services::project_upstream::{UpstreamProjectSource, UpstreamProjectSourceService};
+use crate::services::reservoir::{
+    ReservoirConsumption, ReservoirLookup, ReservoirStatusResponse, SyncReservoirs,
+    SyncReservoirsResponse,
+};
 use crate::services::spooler::{
     self, Buffer, BufferService, DequeueMany, Enqueue, QueueKey, RemoveMany, RestoreIndex,
 };
 use crate::services::test_store::TestStore;
-use crate::services::upstream::UpstreamRelay;
+use crate::services::upstream::{SendQuery, UpstreamRelay};
 
 use crate::statsd::{RelayCounters, RelayGauges, RelayHistograms, RelayTimers};
 use crate::utils::{
@@ -198,6 +204,44 @@ impl UpdateSpoolIndex {
 #[derive(Debug)]
 pub struct SpoolHealth;
 
+/// Handles the reservoir consumption reported by a downstream Relay.
+///
+/// Responds with the consumption of each reported rule known to this Relay. Projects that are not
+/// cached yet or which the downstream Relay has no access to are omitted from the response.
+#[derive(Debug)]
+pub struct SyncDownstreamReservoirs {
+    /// The downstream Relay that reported the consumption.
+    pub relay: RelayInfo,
+    /// Samples taken by the downstream Relay.
+    pub projects: ReservoirConsumption,
+}
+
+/// Merges the reservoir consumption returned by the upstream into the local counters.
+#[derive(Debug)]
+pub struct UpdateReservoirs {
+    projects: ReservoirConsumption,
+}
+
+/// The time over which cached project states are refreshed after project updates were reset.
+const PROJECT_REFRESH_WINDOW: Duration = Duration::from_secs(60);
+
+/// The interval between batches of project state refreshes after project updates were reset.
+const PROJECT_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Applies project config updates pushed by the upstream.
+///
+/// The response is `None` if the upstream request failed.
+#[derive(Debug)]
+pub struct ApplyProjectUpdates {
+    response: Option<GetProjectUpdatesResponse>,
+    /// Whether the upstream does not provide project config updates.
+    unsupported: bool,
+}
+
+/// Returns the consumption and expiry of all reservoir rules in the cache.
+#[derive(Debug)]
+pub struct GetReservoirStatus;
+
 /// The current envelopes index fetched from the underlying buffer spool.
 ///
 /// This index will be received only once shortly after startup and will trigger refresh for the
@@ -236,6 +280,10 @@ pub enum ProjectCache {
     UpdateSpoolIndex(UpdateSpoolIndex),
     SpoolHealth(Sender<bool>),
     RefreshIndexCache(RefreshIndexCache),
+    SyncDownstreamReservoirs(SyncDownstreamReservoirs, Sender<SyncReservoirsResponse>),
+    UpdateReservoirs(UpdateReservoirs),
+    GetReservoirStatus(GetReservoirStatus, Sender<ReservoirStatusResponse>),
+    ApplyProjectUpdates(ApplyProjectUpdates),
 }
 
 impl Interface for ProjectCache {}
@@ -342,6 +390,41 @@ impl FromMessage<SpoolHealth> for ProjectCache {
     }
 }
 
+impl FromMessage<SyncDownstreamReservoirs> for ProjectCache {
+    type Response = relay_system::AsyncResponse<SyncReservoirsResponse>;
+
+    fn from_message(
+        message: SyncDownstreamReservoirs,
+        sender: Sender<SyncReservoirsResponse>,
+    ) -> Self {
+        Self::SyncDownstreamReservoirs(message, sender)
+    }
+}
+
+impl FromMessage<UpdateReservoirs> for ProjectCache {
+    type Response = relay_system::NoResponse;
+
+    fn from_message(message: UpdateReservoirs, _: ()) -> Self {
+        Self::UpdateReservoirs(message)
+    }
+}
+
+impl FromMessage<GetReservoirStatus> for ProjectCache {
+    type Response = relay_system::AsyncResponse<ReservoirStatusResponse>;
+
+    fn from_message(message: GetReservoirStatus, sender: Sender<ReservoirStatusResponse>) -> Self {
+        Self::GetReservoirStatus(message, sender)
+    }
+}
+
+impl FromMessage<ApplyProjectUpdates> for ProjectCache {
+    type Response = relay_system::NoResponse;
+
+    fn from_message(message: ApplyProjectUpdates, _: ()) -> Self {
+        Self::ApplyProjectUpdates(message)
+    }
+}
+
 /// Helper type that contains all configured sources for project cache fetching.
 ///
 /// See [`RequestUpdate`] for a description on how project states are fetched.
@@ -497,6 +580,19 @@ struct ProjectCacheBroker {
     buffer_unspool_backoff: RetryBackoff,
     buffer: Addr<Buffer>,
     global_config: GlobalConfigStatus,
+    redis: Option<RedisPool>,
+    reservoir_sync_handle: SleepHandle,
+    project_updates_handle: SleepHandle,
+    project_updates_backoff: RetryBackoff,
+    /// The last version of project config updates received from the upstream.
+    project_updates_version: Option<u64>,
+    /// Set once the upstream responds that it does not provide project config updates.
+    project_updates_disabled: bool,
+    /// Projects to refresh after the upstream reset project config updates.
+    project_refresh_queue: VecDeque<ProjectKey>,
+    /// The number of projects refreshed per [`PROJECT_REFRESH_INTERVAL`].
+    project_refresh_batch_size: usize,
+    project_refresh_handle: SleepHandle,
 }
 
 /// Describes the current status of the [`GlobalConfig`]
@@ -944,6 +1040,249 @@ impl ProjectCacheBroker {
         self.schedule_unspool();
     }
 
+    /// Schedules the next reservoir synchronization with the upstream, if enabled.
+    fn schedule_reservoir_sync(&mut self) {
+        if self.config.relay_mode() != RelayMode::Managed {
+            return;
+        }
+
+        if let Some(interval) = self.config.reservoir_sync_interval() {
+            self.reservoir_sync_handle.set(interval);
+        }
+    }
+
+    /// Reports the local consumption of reservoir rules to the upstream.
+    ///
+    /// The next synchronization is scheduled once the upstream responded, so there is at most one
+    /// synchronization in flight.
+    fn handle_reservoir_sync(&mut self) {
+        self.reservoir_sync_handle.reset();
+
+        let mut projects = ReservoirConsumption::new();
+        for (project_key, project) in self.projects.iter() {
+            let consumption = project.reservoir_consumption();
+            if !consumption.is_empty() {
+                projects.insert(*project_key, consumption);
+            }
+        }
+
+        if projects.is_empty() {
+            self.schedule_reservoir_sync();
+            return;
+        }
+
+        let upstream_relay = self.services.upstream_relay.clone();
+        let project_cache = self.services.project_cache.clone();
+
+        tokio::spawn(async move {
+            let query = SyncReservoirs { projects };
+            let projects = match upstream_relay.send(SendQuery(query)).await {
+                Ok(Ok(response)) => response.projects,
+                Ok(Err(error)) => {
+                    relay_log::error!(
+                        error = &error as &dyn Error,
+                        "failed to synchronize reservoirs",
+                    );
+                    ReservoirConsumption::new()
+                }
+                Err(_) => ReservoirConsumption::new(),
+            };
+
+            // Always respond to schedule the next synchronization.
+            project_cache.send(UpdateReservoirs { projects });
+        });
+    }
+
+    fn handle_update_reservoirs(&mut self, message: UpdateReservoirs) {
+        for (project_key, consumption) in message.projects {
+            if let Some(project) = self.projects.get(&project_key) {
+                project.merge_reservoir_consumption(&consumption);
+            }
+        }
+
+        self.schedule_reservoir_sync();
+    }
+
+    /// Schedules the next request for project config updates, if enabled.
+    fn schedule_project_updates(&mut self, delay: Duration) {
+        if self.config.relay_mode() != RelayMode::Managed {
+            return;
+        }
+
+        if self.config.project_updates_timeout().is_some() && !self.project_updates_disabled {
+            self.project_updates_handle.set(delay);
+        }
+    }
+
+    /// Requests project config updates from the upstream.
+    ///
+    /// The upstream holds the request open until updates are available or the timeout elapses.
+    /// The next request is scheduled once the upstream responded, so there is at most one request
+    /// in flight.
+    fn handle_project_updates_poll(&mut self) {
+        self.project_updates_handle.reset();
+
+        let Some(timeout) = self.config.project_updates_timeout() else {
+            return;
+        };
+
+        let query = GetProjectUpdates {
+            version: self.project_updates_version,
+            timeout: timeout.as_secs(),
+        };
+
+        let upstream_relay = self.services.upstream_relay.clone();
+        let project_cache = self.services.project_cache.clone();
+
+        tokio::spawn(async move {
+            let mut unsupported = false;
+            let response = match upstream_relay.send(SendQuery(query)).await {
+                Ok(Ok(response)) => Some(response),
+                Ok(Err(error)) => {
+                    unsupported = error.status_code() == Some(StatusCode::NOT_FOUND);
+                    relay_log::debug!(
+                        error = &error as &dyn Error,
+                        "failed to fetch project config updates",
+                    );
+                    None
+                }
+                Err(_) => None,
+            };
+
+            // Always respond to schedule the next request.
+            project_cache.send(ApplyProjectUpdates {
+                response,
+                unsupported,
+            });
+        });
+    }
+
+    fn handle_apply_project_updates(&mut self, message: ApplyProjectUpdates) {
+        if message.unsupported {
+            relay_log::warn!("upstream does not provide project config updates, disabling polling");
+            self.project_updates_disabled = true;
+            return;
+        }
+
+        let Some(response) = message.response else {
+            let backoff = self.project_updates_backoff.next_backoff();
+            self.schedule_project_updates(backoff);
+            return;
+        };
+
+        self.project_updates_backoff.reset();
+
+        if response.reset {
+            // Updates since the last version are lost, so all cached states must be refreshed.
+            relay_log::debug!("refreshing all project states after project updates reset");
+            self.schedule_project_refresh();
+        }
+
+        for (project_key, update) in response.updates {
+            let applied = match self.projects.get_mut(&project_key) {
+                Some(project) => project.apply_update(update),
+                None => false,
+            };
+
+            metric!(
+                counter(RelayCounters::ProjectUpdateReceived) += 1,
+                result = if applied { "applied" } else { "skipped" },
+            );
+        }
+
+        self.project_updates_version = Some(response.version);
+        self.schedule_project_updates(Duration::ZERO);
+    }
+
+    /// Schedules a refresh of all cached project states.
+    ///
+    /// Since all Relays receive a reset at the same time, the refresh is spread over
+    /// [`PROJECT_REFRESH_WINDOW`] to avoid a spike of requests to the upstream.
+    fn schedule_project_refresh(&mut self) {
+        self.project_refresh_queue = self.projects.keys().copied().collect();
+
+        let batches = PROJECT_REFRESH_WINDOW.as_secs() / PROJECT_REFRESH_INTERVAL.as_secs();
+        self.project_refresh_batch_size =
+            self.project_refresh_queue.len().div_ceil(batches as usize);
+
+        self.project_refresh_handle.set(Duration::ZERO);
+    }
+
+    /// Refreshes the next batch of project states queued by [`Self::schedule_project_refresh`].
+    fn handle_project_refresh(&mut self) {
+        self.project_refresh_handle.reset();
+
+        let batch_size = self
+            .project_refresh_batch_size
+            .min(self.project_refresh_queue.len());
+
+        let project_cache = self.services.project_cache.clone();
+        for project_key in self.project_refresh_queue.drain(..batch_size) {
+            // Projects may have been evicted since the refresh was scheduled.
+            if let Some(project) = self.projects.get_mut(&project_key) {
+                project.prefetch(project_cache.clone(), true);
+            }
+        }
+
+        if !self.project_refresh_queue.is_empty() {
+            self.project_refresh_handle.set(PROJECT_REFRESH_INTERVAL);
+        }
+    }
+
+    fn handle_sync_downstream_reservoirs(
+        &mut self,
+        message: SyncDownstreamReservoirs,
+        sender: Sender<SyncReservoirsResponse>,
+    ) {
+        let SyncDownstreamReservoirs { relay, projects } = message;
+        let project_cache = self.services.project_cache.clone();
+
+        let mut lookups = Vec::with_capacity(projects.len());
+        for (project_key, consumption) in projects {
+            let project = self.get_or_create_project(project_key);
+            project.prefetch(project_cache.clone(), false);
+
+            let Some(state) = project.valid_state() else {
+                continue;
+            };
+
+            let has_access =
+                relay.internal || state.config.trusted_relays.contains(&relay.public_key);
+            if !has_access {
+                continue;
+            }
+
+            let lookup = ReservoirLookup {
+                state,
+                counters: project.reservoir_counters(),
+                rules: consumption.into_keys().collect(),
+            };
+            lookups.push((project_key, lookup));
+        }
+
+        // Reading from Redis performs blocking I/O, which must not block the project cache.
+        let redis = self.redis.clone();
+        tokio::task::spawn_blocking(move || {
+            let projects = lookups
+                .into_iter()
+                .map(|(project_key, lookup)| (project_key, lookup.resolve(redis.as_ref())))
+                .collect();
+
+            sender.send(SyncReservoirsResponse { projects });
+        });
+    }
+
+    fn handle_get_reservoir_status(&mut self, sender: Sender<ReservoirStatusResponse>) {
+        let projects = self
+            .projects
+            .iter()
+            .filter_map(|(project_key, project)| Some((*project_key, project.reservoir_status()?)))
+            .filter(|(_, reservoirs)| !reservoirs.is_empty())
+            .collect();
+
+        sender.send(ReservoirStatusResponse { projects });
+    }
+
     fn handle_message(&mut self, message: ProjectCache) {
         match message {
             ProjectCache::RequestUpdate(message) => self.handle_request_update(message),
@@ -962,6 +1301,14 @@ impl ProjectCacheBroker {
             ProjectCache::UpdateSpoolIndex(message) => self.handle_buffer_index(message),
             ProjectCache::SpoolHealth(sender) => self.handle_spool_health(sender),
             ProjectCache::RefreshIndexCache(message) => self.handle_refresh_index_cache(message),
+            ProjectCache::SyncDownstreamReservoirs(message, sender) => {
+                self.handle_sync_downstream_reservoirs(message, sender)
+            }
+            ProjectCache::UpdateReservoirs(message) => self.handle_update_reservoirs(message),
+            ProjectCache::GetReservoirStatus(_, sender) => self.handle_get_reservoir_status(sender),
+            ProjectCache::ApplyProjectUpdates(message) => {
+                self.handle_apply_project_updates(message)
+            }
         }
     }
 }
@@ -1067,7 +1414,7 @@ impl Service for ProjectCacheService {
                 source: ProjectSource::start(
                     config.clone(),
                     services.upstream_relay.clone(),
-                    redis,
+                    redis.clone(),
                 ),
                 services,
                 state_tx,
@@ -1078,8 +1425,20 @@ impl Service for ProjectCacheService {
                 buffer_unspool_backoff: RetryBackoff::new(config.http_max_retry_interval()),
                 buffer,
                 global_config,
+                redis,
+                reservoir_sync_handle: SleepHandle::idle(),
+                project_updates_handle: SleepHandle::idle(),
+                project_updates_backoff: RetryBackoff::new(config.http_max_retry_interval()),
+                project_updates_version: None,
+                project_updates_disabled: false,
+                project_refresh_queue: VecDeque::new(),
+                project_refresh_batch_size: 0,
+                project_refresh_handle: SleepHandle::idle(),
             };
 
+            broker.schedule_reservoir_sync();
+            broker.schedule_project_updates(Duration::ZERO);
+
             loop {
                 tokio::select! {
                     biased;
@@ -1098,6 +1457,9 @@ impl Service for ProjectCacheService {
                     Some(managed_envelope) = buffer_rx.recv() => broker.handle_processing(managed_envelope),
                     _ = ticker.tick() => broker.evict_stale_project_caches(),
                     () = &mut broker.buffer_unspool_handle => broker.handle_periodic_unspool(),
+                    () = &mut broker.reservoir_sync_handle => broker.handle_reservoir_sync(),
+                    () = &mut broker.project_updates_handle => broker.handle_project_updates_poll(),
+                    () = &mut broker.project_refresh_handle => broker.handle_project_refresh(),
                     Some(message) = rx.recv() => broker.handle_message(message),
                     else => break,
                 }
@@ -1213,6 +1575,15 @@ mod tests {
                 global_config: GlobalConfigStatus::Pending,
                 buffer_unspool_handle: SleepHandle::idle(),
                 buffer_unspool_backoff: RetryBackoff::new(Duration::from_millis(100)),
+                redis: None,
+                reservoir_sync_handle: SleepHandle::idle(),
+                project_updates_handle: SleepHandle::idle(),
+                project_updates_backoff: RetryBackoff::new(Duration::from_millis(100)),
+                project_updates_version: None,
+                project_updates_disabled: false,
+                project_refresh_queue: VecDeque::new(),
+                project_refresh_batch_size: 0,
+                project_refresh_handle: SleepHandle::idle(),
             },
             buffer,
         )
@@ -1392,4 +1763,57 @@ mod tests {
         // Make sure the last assert is tested.
         tokio::time::sleep(Duration::from_millis(100)).await;
     }
+
+    #[tokio::test]
+    async fn spreads_project_refresh() {
+        relay_log::init_test!();
+
+        let buffer_guard: Arc<_> = BufferGuard::new(5).into();
+        let (state_tx, _) = mpsc::unbounded_channel();
+        let (buffer_tx, _) = mpsc::unbounded_channel();
+        let (mut broker, _) =
+            project_cache_broker_setup(mocked_services(), buffer_guard, state_tx, buffer_tx).await;
+
+        for i in 0..120u32 {
+            let project_key = ProjectKey::parse(&format!("{i:032x}")).unwrap();
+            let project = Project::new(project_key, broker.config.clone());
+            broker.projects.insert(project_key, project);
+        }
+
+        broker.handle_apply_project_updates(ApplyProjectUpdates {
+            response: Some(GetProjectUpdatesResponse {
+                version: 1,
+                reset: true,
+                ..Default::default()
+            }),
+            unsupported: false,
+        });
+
+        // The refresh is spread over one batch per second within a minute.
+        assert_eq!(broker.project_refresh_queue.len(), 120);
+        assert_eq!(broker.project_refresh_batch_size, 2);
+
+        broker.handle_project_refresh();
+        assert_eq!(broker.project_refresh_queue.len(), 118);
+    }
+
+    #[tokio::test]
+    async fn disables_unsupported_project_updates() {
+        relay_log::init_test!();
+
+        let buffer_guard: Arc<_> = BufferGuard::new(5).into();
+        let (state_tx, _) = mpsc::unbounded_channel();
+        let (buffer_tx, _) = mpsc::unbounded_channel();
+        let (mut broker, _) =
+            project_cache_broker_setup(mocked_services(), buffer_guard, state_tx, buffer_tx).await;
+
+        broker.handle_apply_project_updates(ApplyProjectUpdates {
+            response: None,
+            unsupported: true,
+        });
+
+        assert!(broker.project_updates_disabled);
+        broker.schedule_project_updates(Duration::ZERO);
+        assert!(broker.project_updates_handle.is_idle());
+    }
 }
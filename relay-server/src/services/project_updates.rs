@@ -0,0 +1,184 @@
+//! Incremental updates of project configs pushed by the upstream.
+//!
+//! In addition to fetching full project states, Relay can long-poll the upstream for changes to
+//! individual sections of project configs, such as inbound filters, quotas, or sampling rules.
+//! This allows the upstream to apply such changes within seconds instead of waiting for the
+//! cached project states to expire.
+//!
+//! The upstream keeps a single, monotonically increasing version for all updates. Relay sends the
+//! last version it has seen and the upstream responds with all updates since that version, or
+//! holds the request open until updates are available. Updates apply only to project states that
+//! are already cached; other projects receive the changes with their next full fetch. If the
+//! upstream resets the updates, all cached project states are fetched again, spread over a minute.
+//!
+//! Only Sentry serves the updates endpoint. Relays do not serve it to downstream Relays, which
+//! therefore receive changes only with their next full fetch.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use relay_base_schema::project::ProjectKey;
+use relay_dynamic_config::ProjectConfigPatch;
+use serde::{Deserialize, Serialize};
+
+use crate::services::project::ProjectState;
+use crate::services::upstream::{Method, RequestPriority, UpstreamQuery};
+
+/// Upstream query to wait for updates to project configs.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetProjectUpdates {
+    /// The last version of updates received from the upstream.
+    ///
+    /// If `None`, the upstream responds immediately with its current version and no updates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    /// The maximum time in seconds the upstream may hold the request open.
+    pub timeout: u64,
+}
+
+/// Response of the [`GetProjectUpdates`] query.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetProjectUpdatesResponse {
+    /// The version of the latest update in this response.
+    pub version: u64,
+    /// Updates to project configs since the requested version, by project key.
+    #[serde(default)]
+    pub updates: HashMap<ProjectKey, ProjectUpdate>,
+    /// Indicates that the upstream cannot serve updates since the requested version.
+    ///
+    /// In this case, all cached project states must be fetched again.
+    #[serde(default)]
+    pub reset: bool,
+}
+
+impl UpstreamQuery for GetProjectUpdates {
+    type Response = GetProjectUpdatesResponse;
+
+    fn method(&self) -> Method {
+        Method::POST
+    }
+
+    fn path(&self) -> Cow<'static, str> {
+        Cow::Borrowed("/api/0/relays/projectconfigs/updates/")
+    }
+
+    fn priority() -> RequestPriority {
+        RequestPriority::High
+    }
+
+    fn retry() -> bool {
+        false
+    }
+
+    fn route(&self) -> &'static str {
+        "project_updates"
+    }
+}
+
+/// An update to the config of a single project.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectUpdate {
+    /// The time at which the project config was changed.
+    ///
+    /// This is compared to [`ProjectState::last_change`] to skip updates that are already part of
+    /// a cached project state.
+    pub last_change: DateTime<Utc>,
+    /// The changed sections of the project config.
+    pub config: ProjectConfigPatch,
+}
+
+impl ProjectUpdate {
+    /// Returns `true` if this update is more recent than the given project state.
+    ///
+    /// States without a change timestamp are always considered older.
+    pub fn is_newer_than(&self, state: &ProjectState) -> bool {
+        state
+            .last_change
+            .map_or(true, |last_change| last_change < self.last_change)
+    }
+
+    /// Merges a newer update into this one.
+    pub fn merge(&mut self, other: Self) {
+        self.last_change = self.last_change.max(other.last_change);
+        self.config.merge(other.config);
+    }
+
+    /// Returns a copy of the project state with this update applied.
+    ///
+    /// The fetch time of the state is retained, so that the state still expires on schedule.
+    pub fn apply(&self, state: &ProjectState) -> ProjectState {
+        let mut state = state.clone();
+        self.config.clone().apply(&mut state.config);
+        state.last_change = Some(self.last_change);
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn update(secs: i64, json: serde_json::Value) -> ProjectUpdate {
+        ProjectUpdate {
+            last_change: Utc.timestamp_opt(secs, 0).unwrap(),
+            config: serde_json::from_value(json).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_response() {
+        let json = r#"{
+            "version": 42,
+            "updates": {
+                "a94ae32be2584e0bbd7a4cbb95971fee": {
+                    "lastChange": "2024-01-01T00:00:00Z",
+                    "config": {"filterSettings": {"localhost": {"isEnabled": true}}}
+                }
+            }
+        }"#;
+
+        let response: GetProjectUpdatesResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.version, 42);
+        assert!(!response.reset);
+
+        let key = ProjectKey::parse("a94ae32be2584e0bbd7a4cbb95971fee").unwrap();
+        let filter_settings = response.updates[&key].config.filter_settings.as_ref();
+        assert!(filter_settings.unwrap().localhost.is_enabled);
+    }
+
+    #[test]
+    fn test_apply_update() {
+        let mut state = ProjectState::allowed();
+        state.last_change = Some(Utc.timestamp_opt(10, 0).unwrap());
+
+        let old = update(5, serde_json::json!({}));
+        assert!(!old.is_newer_than(&state));
+
+        let new = update(
+            20,
+            serde_json::json!({"filterSettings": {"localhost": {"isEnabled": true}}}),
+        );
+        assert!(new.is_newer_than(&state));
+
+        let updated = new.apply(&state);
+        assert!(updated.config.filter_settings.localhost.is_enabled);
+        assert_eq!(updated.last_change, Some(new.last_change));
+        assert_eq!(updated.last_fetch, state.last_fetch);
+    }
+
+    #[test]
+    fn test_merge_updates() {
+        let mut first = update(10, serde_json::json!({"quotas": []}));
+        first.merge(update(20, serde_json::json!({"txNameRules": []})));
+
+        assert_eq!(first.last_change, Utc.timestamp_opt(20, 0).unwrap());
+        assert!(first.config.quotas.is_some());
+        assert!(first.config.tx_name_rules.is_some());
+    }
+}
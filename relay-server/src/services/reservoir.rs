@@ -0,0 +1,149 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use relay_base_schema::project::ProjectKey;
+use relay_dynamic_config::ErrorBoundary;
+use relay_redis::RedisPool;
+use relay_sampling::config::RuleId;
+use relay_sampling::evaluation::{ReservoirCounters, ReservoirEvaluator};
+use relay_sampling::reservoir::ReservoirStatus;
+use serde::{Deserialize, Serialize};
+
+use crate::services::project::ProjectState;
+use crate::services::upstream::{Method, RequestPriority, UpstreamQuery};
+
+/// The amount of samples taken by reservoir rules, by project key and rule ID.
+pub type ReservoirConsumption = BTreeMap<ProjectKey, BTreeMap<RuleId, i64>>;
+
+/// Upstream query to synchronize reservoir sampling rules.
+///
+/// The request contains the amount of samples each reservoir rule has taken in this Relay. The
+/// upstream responds with the consumption it knows about, which includes the samples forwarded by
+/// all of its downstream Relays.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SyncReservoirs {
+    /// Samples taken by the requesting Relay.
+    pub projects: ReservoirConsumption,
+}
+
+/// Response of the [`SyncReservoirs`] query.
+///
+/// Projects and rules that are unknown to the upstream are omitted.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SyncReservoirsResponse {
+    /// Samples taken by each reported rule as known to the upstream.
+    pub projects: ReservoirConsumption,
+}
+
+impl UpstreamQuery for SyncReservoirs {
+    type Response = SyncReservoirsResponse;
+
+    fn method(&self) -> Method {
+        Method::POST
+    }
+
+    fn path(&self) -> Cow<'static, str> {
+        Cow::Borrowed("/api/0/relays/reservoirs/")
+    }
+
+    fn priority() -> RequestPriority {
+        RequestPriority::Low
+    }
+
+    fn retry() -> bool {
+        false
+    }
+
+    fn route(&self) -> &'static str {
+        "reservoirs"
+    }
+}
+
+/// Status of all reservoir rules, by project key.
+#[derive(Debug, Default, Serialize)]
+pub struct ReservoirStatusResponse {
+    /// Reservoir rules of every cached project with a sampling config.
+    pub projects: BTreeMap<ProjectKey, Vec<ReservoirStatus>>,
+}
+
+/// Lookup of reservoir rules reported by a downstream Relay for a single project.
+///
+/// The consumption reported by the downstream Relay is not added to the local counters, since all
+/// samples it takes are forwarded and counted by this Relay as well.
+#[derive(Debug)]
+pub struct ReservoirLookup {
+    /// The project state the reservoir rules belong to.
+    pub state: Arc<ProjectState>,
+    /// The local reservoir counters of the project.
+    pub counters: ReservoirCounters,
+    /// The reservoir rules reported by the downstream Relay.
+    pub rules: Vec<RuleId>,
+}
+
+impl ReservoirLookup {
+    /// Returns the consumption known to this Relay for every reported rule.
+    ///
+    /// If a Redis pool is given, the global consumption is read from Redis. Rules that are no
+    /// longer part of the sampling config are omitted.
+    ///
+    /// This may perform blocking I/O and must not run on the async runtime.
+    pub fn resolve(self, redis: Option<&RedisPool>) -> BTreeMap<RuleId, i64> {
+        let Some(ErrorBoundary::Ok(config)) = self.state.config.sampling.as_ref() else {
+            return BTreeMap::new();
+        };
+
+        #[allow(unused_mut)]
+        let mut evaluator = ReservoirEvaluator::new(self.counters);
+        #[cfg(feature = "processing")]
+        if let (Some(redis), Some(org_id)) = (redis, self.state.organization_id) {
+            evaluator.set_redis(org_id, redis);
+        }
+        #[cfg(not(feature = "processing"))]
+        let _ = redis;
+
+        self.rules
+            .iter()
+            .filter(|rule_id| config.rules.iter().any(|rule| rule.id == **rule_id))
+            .map(|rule_id| (*rule_id, evaluator.consumed(*rule_id)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use relay_protocol::RuleCondition;
+    use relay_sampling::config::{RuleType, SamplingRule, SamplingValue};
+    use relay_sampling::SamplingConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_lookup() {
+        let mut state = ProjectState::allowed();
+        state.config.sampling = Some(ErrorBoundary::Ok(SamplingConfig {
+            rules: vec![SamplingRule {
+                condition: RuleCondition::all(),
+                sampling_value: SamplingValue::Reservoir { limit: 10 },
+                ty: RuleType::Transaction,
+                id: RuleId(1),
+                time_range: Default::default(),
+                decaying_fn: Default::default(),
+            }],
+            ..SamplingConfig::new()
+        }));
+
+        let counters = ReservoirCounters::default();
+        counters.lock().unwrap().insert(RuleId(1), 2);
+
+        let lookup = ReservoirLookup {
+            state: Arc::new(state),
+            counters,
+            // Rule 2 is not part of the sampling config.
+            rules: vec![RuleId(1), RuleId(2)],
+        };
+
+        let consumption = lookup.resolve(None);
+        assert_eq!(consumption, BTreeMap::from([(RuleId(1), 2)]));
+    }
+}
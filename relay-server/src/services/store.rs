@@ -11,6 +11,7 @@ use bytes::Bytes;
 use once_cell::sync::OnceCell;
 use relay_base_schema::data_category::DataCategory;
 use relay_base_schema::project::ProjectId;
+use relay_cardinality::CardinalityReport;
 use relay_common::time::{instant_to_date_time, UnixTimestamp};
 use relay_config::Config;
 use relay_event_schema::protocol::{
@@ -93,11 +94,18 @@ pub struct StoreMetrics {
     pub mode: ExtractionMode,
 }
 
+/// Publishes [`CardinalityReport`]s of passive cardinality limits through Kafka.
+#[derive(Clone, Debug)]
+pub struct StoreCardinalityReports {
+    pub reports: Vec<CardinalityReport>,
+}
+
 /// Service interface for the [`StoreEnvelope`] message.
 #[derive(Debug)]
 pub enum Store {
     Envelope(StoreEnvelope),
     Metrics(StoreMetrics),
+    CardinalityReports(StoreCardinalityReports),
 }
 
 impl Interface for Store {}
@@ -118,6 +126,14 @@ impl FromMessage<StoreMetrics> for Store {
     }
 }
 
+impl FromMessage<StoreCardinalityReports> for Store {
+    type Response = NoResponse;
+
+    fn from_message(message: StoreCardinalityReports, _: ()) -> Self {
+        Self::CardinalityReports(message)
+    }
+}
+
 /// Service implementing the [`Store`] interface.
 pub struct StoreService {
     config: Arc<Config>,
@@ -142,6 +158,7 @@ impl StoreService {
         match message {
             Store::Envelope(message) => self.handle_store_envelope(message),
             Store::Metrics(message) => self.handle_store_metrics(message),
+            Store::CardinalityReports(message) => self.handle_store_cardinality_reports(message),
         }
     }
 
@@ -352,6 +369,28 @@ impl StoreService {
         }
     }
 
+    fn handle_store_cardinality_reports(&self, message: StoreCardinalityReports) {
+        for report in message.reports {
+            let organization_id = report.organization_id;
+            let message = KafkaMessage::CardinalityReport(report);
+
+            if let Err(error) =
+                self.produce(KafkaTopic::CardinalityReports, organization_id, message)
+            {
+                relay_log::error!(
+                    error = &error as &dyn Error,
+                    "failed to produce cardinality report"
+                );
+                continue;
+            }
+
+            metric!(
+                counter(RelayCounters::ProcessingMessageProduced) += 1,
+                event_type = "cardinality_report"
+            );
+        }
+    }
+
     fn extract_kafka_messages_for_event(
         event_item: Option<&Item>,
         event_id: EventId,
@@ -1299,6 +1338,7 @@ enum KafkaMessage<'a> {
     ReplayRecordingNotChunked(ReplayRecordingNotChunkedKafkaMessage),
     CheckIn(CheckInKafkaMessage),
     Span(SpanKafkaMessage<'a>),
+    CardinalityReport(CardinalityReport),
 }
 
 impl Message for KafkaMessage<'_> {
@@ -1315,6 +1355,7 @@ impl Message for KafkaMessage<'_> {
             KafkaMessage::ReplayRecordingNotChunked(_) => "replay_recording_not_chunked",
             KafkaMessage::CheckIn(_) => "check_in",
             KafkaMessage::Span(_) => "span",
+            KafkaMessage::CardinalityReport(_) => "cardinality_report",
         }
     }
 
@@ -1337,7 +1378,8 @@ impl Message for KafkaMessage<'_> {
             Self::Session(_)
             | Self::Profile(_)
             | Self::ReplayRecordingNotChunked(_)
-            | Self::Span(_) => Uuid::nil(),
+            | Self::Span(_)
+            | Self::CardinalityReport(_) => Uuid::nil(),
 
             // TODO(ja): Determine a partitioning key
             Self::Metric { .. } => Uuid::nil(),
@@ -1383,6 +1425,9 @@ impl Message for KafkaMessage<'_> {
             KafkaMessage::Span(message) => {
                 serde_json::to_vec(message).map_err(ClientError::InvalidJson)
             }
+            KafkaMessage::CardinalityReport(message) => {
+                serde_json::to_vec(message).map_err(ClientError::InvalidJson)
+            }
             _ => rmp_serde::to_vec_named(&self).map_err(ClientError::InvalidMsgPack),
         }
     }
@@ -129,7 +129,7 @@ impl UpstreamRequestError {
     /// If this error is the result of sending a request to the upstream, this method returns `Some`
     /// with the status code. If the request could not be made or the error originates elsewhere,
     /// this returns `None`.
-    fn status_code(&self) -> Option<StatusCode> {
+    pub fn status_code(&self) -> Option<StatusCode> {
         match self {
             UpstreamRequestError::ResponseError(code, _) => Some(*code),
             UpstreamRequestError::Http(HttpError::Reqwest(e)) => e.status(),
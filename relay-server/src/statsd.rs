@@ -490,6 +490,12 @@ pub enum RelayCounters {
     /// Failure can happen, for example, when there's a network error. Refer to
     /// [`UpstreamRequestError`](crate::services::upstream::UpstreamRequestError) for all cases.
     ProjectUpstreamFailed,
+    /// Number of project config updates pushed by the upstream.
+    ///
+    /// This metric is tagged with:
+    ///  - `result`: `applied` if the update was applied to a cached project state, or `skipped`
+    ///    if the project is not cached or the update is already part of its state.
+    ProjectUpdateReceived,
     /// Number of full metric data flushes.
     ///
     /// A full flush takes all contained items of the aggregator and flushes them upstream,
@@ -573,6 +579,14 @@ pub enum RelayCounters {
     MetricBucketsParsingFailed,
     /// Number of times that parsing a metric meta item from an envelope failed.
     MetricMetaParsingFailed,
+    /// Number of metric tag values modified by data scrubbing.
+    ///
+    /// Tag values are only scrubbed if `scrubTagValues` is enabled in the project's metrics
+    /// config.
+    ///
+    /// This metric is tagged with:
+    ///  - `namespace`: The namespace of the metric, such as `"custom"`.
+    MetricTagsScrubbed,
     /// Count extraction of transaction names. Tag with the decision to drop / replace / use original.
     MetricsTransactionNameExtracted,
     /// Number of Events with an OpenTelemetry Context
@@ -610,6 +624,7 @@ impl CounterMetric for RelayCounters {
             RelayCounters::ProjectStateRedis => "project_state.redis.requests",
             RelayCounters::ProjectUpstreamCompleted => "project_upstream.completed",
             RelayCounters::ProjectUpstreamFailed => "project_upstream.failed",
+            RelayCounters::ProjectUpdateReceived => "project_upstream.update_received",
             RelayCounters::ProjectCacheHit => "project_cache.hit",
             RelayCounters::ProjectCacheMiss => "project_cache.miss",
             RelayCounters::ServerStarting => "server.starting",
@@ -623,6 +638,7 @@ impl CounterMetric for RelayCounters {
             RelayCounters::EvictingStaleProjectCaches => "project_cache.eviction",
             RelayCounters::MetricBucketsParsingFailed => "metrics.buckets.parsing_failed",
             RelayCounters::MetricMetaParsingFailed => "metrics.meta.parsing_failed",
+            RelayCounters::MetricTagsScrubbed => "metrics.tags_scrubbed",
             RelayCounters::MetricsTransactionNameExtracted => "metrics.transaction_name",
             RelayCounters::OpenTelemetryEvent => "event.opentelemetry",
             RelayCounters::GlobalConfigFetched => "global_config.fetch",
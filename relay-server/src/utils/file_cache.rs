@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+use once_cell::sync::OnceCell;
+
+/// The maximum number of files remembered as missing or invalid by a [`FileCache`].
+const MAX_MISSING_ENTRIES: usize = 10_000;
+
+/// The result of a load shared by all callers that requested the same file concurrently.
+type Slot<T> = Arc<OnceCell<Option<Arc<T>>>>;
+
+/// A parsed file in the cache.
+struct Entry<T> {
+    value: Arc<T>,
+    size: usize,
+    created: Instant,
+}
+
+struct Inner<K, T> {
+    /// Parsed files, bounded by the sum of their sizes.
+    entries: LruCache<K, Entry<T>>,
+    /// The sum of the sizes of all parsed files.
+    size: usize,
+    /// Files that are missing or failed to parse, bounded by count.
+    ///
+    /// These are kept separately, so that lookups of unknown files do not evict parsed files.
+    missing: LruCache<K, Instant>,
+    /// Files that are currently being loaded.
+    loading: HashMap<K, Slot<T>>,
+}
+
+/// An in-memory LRU cache of files parsed from disk, bounded by their parsed size.
+///
+/// Files are loaded on first access and expire after a fixed time. Concurrent requests for the
+/// same file wait for a single load instead of parsing the file repeatedly. Files that are missing
+/// or fail to parse are remembered for the same time, but do not count towards the size limit.
+pub struct FileCache<K, T> {
+    max_size: usize,
+    expiry: Duration,
+    weigh: fn(&T) -> usize,
+    inner: Mutex<Inner<K, T>>,
+}
+
+impl<K, T> FileCache<K, T>
+where
+    K: Clone + Eq + Hash,
+{
+    /// Creates a cache that keeps at most `max_size` bytes of parsed files for `expiry`.
+    ///
+    /// The size of a parsed file is computed by `weigh`. Files larger than `max_size` are never
+    /// cached.
+    pub fn new(max_size: usize, expiry: Duration, weigh: fn(&T) -> usize) -> Self {
+        let missing_capacity = NonZeroUsize::new(MAX_MISSING_ENTRIES).unwrap();
+
+        Self {
+            max_size,
+            expiry,
+            weigh,
+            inner: Mutex::new(Inner {
+                entries: LruCache::unbounded(),
+                size: 0,
+                missing: LruCache::new(missing_capacity),
+                loading: HashMap::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner<K, T>> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the cached file for the key, or loads it with the given function.
+    ///
+    /// Returns `None` if the file is missing or cannot be parsed.
+    pub fn get_or_load<F>(&self, key: &K, load: F) -> Option<Arc<T>>
+    where
+        F: FnOnce() -> Option<T>,
+    {
+        let slot = {
+            let mut inner = self.lock();
+            if let Some(value) = inner.get(key, self.expiry) {
+                return value;
+            }
+
+            inner.loading.entry(key.clone()).or_default().clone()
+        };
+
+        // Load without holding the lock, since files can be large. Other callers requesting the
+        // same file block here until the load completes.
+        let mut loaded = false;
+        let value = slot
+            .get_or_init(|| {
+                loaded = true;
+                load().map(Arc::new)
+            })
+            .clone();
+
+        if loaded {
+            let mut inner = self.lock();
+            inner.loading.remove(key);
+            inner.insert(key.clone(), value.clone(), self);
+        }
+
+        value
+    }
+
+    /// Returns the cached file for the key without loading it.
+    ///
+    /// The outer option is `None` if the key is not cached or expired. The inner option is `None`
+    /// if the file was remembered as missing.
+    pub fn get(&self, key: &K) -> Option<Option<Arc<T>>> {
+        self.lock().get(key, self.expiry)
+    }
+
+    /// Inserts a file that was loaded outside of the cache, or remembers it as missing.
+    pub fn insert(&self, key: K, value: Option<T>) -> Option<Arc<T>> {
+        let value = value.map(Arc::new);
+        self.lock().insert(key, value.clone(), self);
+        value
+    }
+}
+
+impl<K, T> Inner<K, T>
+where
+    K: Clone + Eq + Hash,
+{
+    /// Returns the cached file for the key.
+    ///
+    /// The outer option is `None` if the key is not cached or expired.
+    fn get(&mut self, key: &K, expiry: Duration) -> Option<Option<Arc<T>>> {
+        if let Some(entry) = self.entries.get(key) {
+            if entry.created.elapsed() < expiry {
+                return Some(Some(entry.value.clone()));
+            }
+
+            self.remove(key);
+            return None;
+        }
+
+        match self.missing.get(key) {
+            Some(created) if created.elapsed() < expiry => Some(None),
+            _ => None,
+        }
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some(entry) = self.entries.pop(key) {
+            self.size -= entry.size;
+        }
+    }
+
+    fn insert(&mut self, key: K, value: Option<Arc<T>>, cache: &FileCache<K, T>) {
+        self.remove(&key);
+
+        let Some(value) = value else {
+            self.missing.put(key, Instant::now());
+            return;
+        };
+
+        self.missing.pop(&key);
+
+        let size = (cache.weigh)(&value);
+        if size > cache.max_size {
+            return;
+        }
+
+        let entry = Entry {
+            value,
+            size,
+            created: Instant::now(),
+        };
+
+        self.entries.put(key, entry);
+        self.size += size;
+
+        while self.size > cache.max_size {
+            let Some((_, entry)) = self.entries.pop_lru() else {
+                break;
+            };
+            self.size -= entry.size;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Barrier;
+
+    use super::*;
+
+    fn cache(max_size: usize) -> FileCache<u32, String> {
+        FileCache::new(max_size, Duration::from_secs(60), String::len)
+    }
+
+    #[test]
+    fn test_max_size() {
+        let cache = cache(10);
+
+        cache.get_or_load(&1, || Some("aaaa".to_owned()));
+        cache.get_or_load(&2, || Some("bbbb".to_owned()));
+        assert_eq!(cache.lock().size, 8);
+
+        // Evicts the least recently used file to make room.
+        cache.get_or_load(&1, || None);
+        cache.get_or_load(&3, || Some("cccc".to_owned()));
+        assert_eq!(cache.lock().size, 8);
+        assert!(cache.lock().entries.contains(&1));
+        assert!(!cache.lock().entries.contains(&2));
+
+        // Files larger than the cache are returned but not cached.
+        let large = cache.get_or_load(&4, || Some("x".repeat(11)));
+        assert_eq!(large.unwrap().len(), 11);
+        assert!(!cache.lock().entries.contains(&4));
+    }
+
+    #[test]
+    fn test_missing() {
+        let cache = cache(10);
+
+        cache.get_or_load(&1, || Some("aaaa".to_owned()));
+        for key in 2..100 {
+            assert!(cache.get_or_load(&key, || None).is_none());
+        }
+
+        // Missing files do not evict parsed files and are not loaded again.
+        assert!(cache.lock().entries.contains(&1));
+        assert!(cache.get_or_load(&2, || Some("bbbb".to_owned())).is_none());
+    }
+
+    #[test]
+    fn test_expiry() {
+        let cache = FileCache::new(10, Duration::ZERO, String::len);
+
+        cache.get_or_load(&1, || Some("aaaa".to_owned()));
+        let value = cache.get_or_load(&1, || Some("bbbb".to_owned()));
+        assert_eq!(value.unwrap().as_str(), "bbbb");
+        assert_eq!(cache.lock().size, 4);
+    }
+
+    #[test]
+    fn test_insert() {
+        let cache = cache(10);
+        assert!(cache.get(&1).is_none());
+
+        cache.insert(1, Some("aaaa".to_owned()));
+        cache.insert(2, None);
+        assert_eq!(cache.get(&1).unwrap().unwrap().as_str(), "aaaa");
+        assert!(cache.get(&2).unwrap().is_none());
+
+        // Inserted files are returned without loading them again.
+        let value = cache.get_or_load(&1, || Some("bbbb".to_owned()));
+        assert_eq!(value.unwrap().as_str(), "aaaa");
+    }
+
+    #[test]
+    fn test_coalesce_loads() {
+        let cache = cache(10);
+        let loads = AtomicUsize::new(0);
+        let barrier = Barrier::new(4);
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    barrier.wait();
+                    cache.get_or_load(&1, || {
+                        loads.fetch_add(1, Ordering::Relaxed);
+                        std::thread::sleep(Duration::from_millis(50));
+                        Some("aaaa".to_owned())
+                    })
+                });
+            }
+        });
+
+        assert_eq!(loads.load(Ordering::Relaxed), 1);
+        assert!(cache.lock().loading.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 mod api;
 mod buffer;
 mod dynamic_sampling;
+mod file_cache;
 mod garbage;
 mod managed_envelope;
 mod metrics_rate_limits;
@@ -8,6 +9,7 @@ mod multipart;
 mod param_parser;
 mod rate_limits;
 mod retry;
+mod sampling_simulation;
 mod semaphore;
 mod sizes;
 mod sleep_handle;
@@ -21,6 +23,7 @@ mod unreal;
 pub use self::api::*;
 pub use self::buffer::*;
 pub use self::dynamic_sampling::*;
+pub use self::file_cache::*;
 pub use self::garbage::*;
 pub use self::managed_envelope::*;
 pub use self::metrics_rate_limits::*;
@@ -30,6 +33,7 @@ pub use self::native::*;
 pub use self::param_parser::*;
 pub use self::rate_limits::*;
 pub use self::retry::*;
+pub use self::sampling_simulation::*;
 pub use self::semaphore::*;
 pub use self::sizes::*;
 pub use self::sleep_handle::*;
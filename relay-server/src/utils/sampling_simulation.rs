@@ -0,0 +1,124 @@
+//! Simulation of dynamic sampling configs on a corpus of transactions.
+
+use chrono::Utc;
+use relay_event_schema::protocol::Event;
+use relay_protocol::Annotated;
+use relay_sampling::simulation::{self, SimulationItem, SimulationPeriod, SimulationReport};
+use relay_sampling::{DynamicSamplingContext, SamplingConfig};
+use serde::Deserialize;
+
+/// A single transaction of a simulation corpus.
+///
+/// At least one of the event or the dynamic sampling context should be set. Transaction rules are
+/// only evaluated on events with an event ID, which is used as sampling seed.
+#[derive(Debug, Default, Deserialize)]
+pub struct CorpusItem {
+    /// The transaction event.
+    #[serde(default, deserialize_with = "Annotated::deserialize_with_meta")]
+    pub event: Annotated<Event>,
+    /// The dynamic sampling context of the trace the transaction belongs to.
+    #[serde(default)]
+    pub dsc: Option<DynamicSamplingContext>,
+}
+
+/// Request to simulate a sampling config on a corpus of transactions.
+#[derive(Debug, Deserialize)]
+pub struct SamplingSimulation {
+    /// The sampling config to simulate.
+    pub config: SamplingConfig,
+    /// The period over which to simulate the sampling config.
+    ///
+    /// Defaults to the current time.
+    #[serde(default)]
+    pub period: Option<SimulationPeriod>,
+    /// The transactions to sample.
+    pub corpus: Vec<CorpusItem>,
+}
+
+impl SamplingSimulation {
+    /// Runs the simulation and returns the report.
+    pub fn run(mut self) -> SimulationReport {
+        self.config.normalize();
+        let period = self
+            .period
+            .unwrap_or_else(|| SimulationPeriod::at(Utc::now()));
+
+        let corpus = self
+            .corpus
+            .iter()
+            .map(|item| SimulationItem {
+                event: item
+                    .event
+                    .value()
+                    .and_then(|event| Some((event.id.value()?.0, event))),
+                dsc: item.dsc.as_ref(),
+            })
+            .collect::<Vec<_>>();
+
+        simulation::simulate(&self.config, &period, &corpus)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_simulation() {
+        let simulation: SamplingSimulation = serde_json::from_value(serde_json::json!({
+            "config": {
+                "version": 2,
+                "rules": [
+                    {
+                        "id": 1,
+                        "type": "transaction",
+                        "condition": {"op": "eq", "name": "event.transaction", "value": "/health"},
+                        "samplingValue": {"type": "sampleRate", "value": 0.0}
+                    },
+                    {
+                        "id": 2,
+                        "type": "trace",
+                        "condition": {"op": "and", "inner": []},
+                        "samplingValue": {"type": "sampleRate", "value": 1.0}
+                    }
+                ]
+            },
+            "corpus": [
+                {
+                    "event": {
+                        "event_id": "52df9022835246eeb317dbd739ccd059",
+                        "type": "transaction",
+                        "transaction": "/health"
+                    }
+                },
+                {
+                    "event": {
+                        "event_id": "52df9022835246eeb317dbd739ccd05a",
+                        "type": "transaction",
+                        "transaction": "/users"
+                    },
+                    "dsc": {
+                        "trace_id": "67e5504410b1426f9247bb680e5fe0c8",
+                        "public_key": "abd0f232775f45feab79864e580d160b"
+                    }
+                },
+                {
+                    "event": {
+                        "type": "transaction",
+                        "transaction": "/health"
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+
+        let report = simulation.run();
+        let step = &report.steps[0];
+
+        assert_eq!(step.total, 3);
+        // The event without an ID and DSC is not matched.
+        assert_eq!(step.unmatched, 1);
+        assert_eq!(step.kept, 2);
+        assert_eq!(step.projected_kept, 2.0);
+    }
+}
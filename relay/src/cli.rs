@@ -1,3 +1,5 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::{env, io};
 
@@ -8,6 +10,9 @@ use dialoguer::{Confirm, Select};
 use relay_config::{
     Config, ConfigError, ConfigErrorKind, Credentials, MinimalConfig, OverridableConfig, RelayMode,
 };
+use relay_pii::Vault;
+use relay_protocol::{Annotated, Value};
+use relay_server::{CorpusItem, SamplingSimulation};
 use uuid::Uuid;
 
 use crate::cliapp::make_app;
@@ -44,6 +49,14 @@ pub fn execute() -> Result<()> {
         }
     } else if let Some(matches) = matches.subcommand_matches("generate-completions") {
         return generate_completions(matches);
+    } else if let Some(matches) = matches.subcommand_matches("sampling") {
+        if let Some(matches) = matches.subcommand_matches("simulate") {
+            return simulate_sampling(matches);
+        }
+    } else if let Some(matches) = matches.subcommand_matches("pii") {
+        if let Some(matches) = matches.subcommand_matches("reveal") {
+            return reveal_pii(matches);
+        }
     }
 
     // Commands that need a loaded config:
@@ -339,6 +352,72 @@ pub fn generate_completions(matches: &ArgMatches) -> Result<()> {
     Ok(())
 }
 
+pub fn simulate_sampling(matches: &ArgMatches) -> Result<()> {
+    let config_path = matches.get_one::<PathBuf>("sampling_config").unwrap();
+    let config = serde_json::from_reader(BufReader::new(File::open(config_path)?))
+        .map_err(|e| anyhow!("invalid sampling config: {e}"))?;
+
+    let corpus_path = matches.get_one::<PathBuf>("corpus").unwrap();
+    let mut corpus = Vec::new();
+    for (index, line) in BufReader::new(File::open(corpus_path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let item = serde_json::from_str::<CorpusItem>(&line)
+            .map_err(|e| anyhow!("invalid corpus item on line {}: {e}", index + 1))?;
+        corpus.push(item);
+    }
+
+    let period = match (
+        matches.get_one::<String>("start"),
+        matches.get_one::<String>("end"),
+    ) {
+        (Some(start), Some(end)) => Some(
+            serde_json::from_value(serde_json::json!({
+                "start": start,
+                "end": end,
+                "steps": matches.get_one::<u32>("steps").copied().unwrap_or(1),
+            }))
+            .map_err(|e| anyhow!("invalid simulation period: {e}"))?,
+        ),
+        _ => None,
+    };
+
+    let simulation = SamplingSimulation {
+        config,
+        period,
+        corpus,
+    };
+
+    serde_json::to_writer_pretty(io::stdout(), &simulation.run())?;
+    println!();
+    Ok(())
+}
+
+pub fn reveal_pii(matches: &ArgMatches) -> Result<()> {
+    let vault = Vault::new(matches.get_one::<String>("key").unwrap());
+
+    if let Some(ciphertext) = matches.get_one::<String>("ciphertext") {
+        println!("{}", vault.decrypt(ciphertext)?);
+        return Ok(());
+    }
+
+    let mut json = String::new();
+    match matches.get_one::<PathBuf>("event") {
+        Some(path) => File::open(path)?.read_to_string(&mut json)?,
+        None => io::stdin().read_to_string(&mut json)?,
+    };
+
+    let mut event =
+        Annotated::<Value>::from_json(&json).map_err(|e| anyhow!("invalid event: {e}"))?;
+    vault.reveal(&mut event)?;
+
+    println!("{}", event.to_json_pretty()?);
+    Ok(())
+}
+
 pub fn run(config: Config, _matches: &ArgMatches) -> Result<()> {
     setup::dump_spawn_infos(&config);
     setup::check_config(&config)?;
@@ -286,6 +286,106 @@ pub fn make_app() -> Command {
                         ),
                 ),
         )
+        .subcommand(
+            Command::new("sampling")
+                .about("Dynamic sampling tools")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("simulate")
+                        .about("Simulate a sampling config on a corpus of transactions")
+                        .after_help(
+                            "This evaluates the rules of a sampling config on every transaction \
+                             of the corpus and prints a JSON report with the distribution of \
+                             sample rates, the matched rules, and the projected kept volume. \
+                             The corpus is a file with one JSON object per line, containing an \
+                             \"event\" and/or a \"dsc\" (dynamic sampling context).  Time \
+                             ranges and decaying functions of rules are simulated by evaluating \
+                             the corpus at several points in time.",
+                        )
+                        .arg(
+                            Arg::new("sampling_config")
+                                .long("sampling-config")
+                                .value_name("PATH")
+                                .value_hint(ValueHint::FilePath)
+                                .value_parser(ValueParser::path_buf())
+                                .required(true)
+                                .help("Path to the JSON sampling config"),
+                        )
+                        .arg(
+                            Arg::new("corpus")
+                                .long("corpus")
+                                .value_name("PATH")
+                                .value_hint(ValueHint::FilePath)
+                                .value_parser(ValueParser::path_buf())
+                                .required(true)
+                                .help("Path to the newline-delimited JSON corpus"),
+                        )
+                        .arg(
+                            Arg::new("start")
+                                .long("start")
+                                .value_name("TIMESTAMP")
+                                .requires("end")
+                                .help("Start of the simulated period in RFC 3339 format"),
+                        )
+                        .arg(
+                            Arg::new("end")
+                                .long("end")
+                                .value_name("TIMESTAMP")
+                                .requires("start")
+                                .help("End of the simulated period in RFC 3339 format"),
+                        )
+                        .arg(
+                            Arg::new("steps")
+                                .long("steps")
+                                .value_name("COUNT")
+                                .requires("start")
+                                .value_parser(clap::value_parser!(u32))
+                                .help(
+                                    "Number of points in time to evaluate within the period, \
+                                     defaults to 1",
+                                ),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("pii")
+                .about("Data scrubbing tools")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("reveal")
+                        .about("Restore values encrypted by data scrubbing")
+                        .after_help(
+                            "This decrypts values that were scrubbed with the \"encrypt\" \
+                             redaction and replaces their ciphertexts with the original values. \
+                             The event is read as JSON from the given file or from stdin and \
+                             printed with all encrypted values restored.  Alternatively, a \
+                             single ciphertext copied from the event can be decrypted.",
+                        )
+                        .arg(
+                            Arg::new("key")
+                                .long("key")
+                                .value_name("KEY")
+                                .env("RELAY_PII_VAULT_KEY")
+                                .hide_env_values(true)
+                                .required(true)
+                                .help("The vault key configured in the PII config"),
+                        )
+                        .arg(
+                            Arg::new("ciphertext")
+                                .long("ciphertext")
+                                .value_name("CIPHERTEXT")
+                                .conflicts_with("event")
+                                .help("Decrypt a single ciphertext instead of an event"),
+                        )
+                        .arg(
+                            Arg::new("event")
+                                .value_name("PATH")
+                                .value_hint(ValueHint::FilePath)
+                                .value_parser(ValueParser::path_buf())
+                                .help("Path to the JSON event, defaults to stdin"),
+                        ),
+                ),
+        )
         .subcommand(
             Command::new("generate-completions")
                 .about("Generate shell completion file")
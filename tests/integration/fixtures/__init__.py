@@ -460,3 +460,26 @@ class SentryLike:
 
     def get(self, path, **kwargs):
         return self.request("get", path, **kwargs)
+
+    def post_signed(self, path, payload):
+        packed, signature = SecretKey.parse(self.secret_key).pack(payload)
+
+        return self.post(
+            path,
+            data=packed,
+            headers={
+                "X-Sentry-Relay-Id": self.relay_id,
+                "X-Sentry-Relay-Signature": signature,
+            },
+        )
+
+    def get_signed(self, path):
+        signature = SecretKey.parse(self.secret_key).sign(b"")
+
+        return self.get(
+            path,
+            headers={
+                "X-Sentry-Relay-Id": self.relay_id,
+                "X-Sentry-Relay-Signature": signature,
+            },
+        )
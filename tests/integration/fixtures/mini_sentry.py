@@ -4,6 +4,7 @@ import gzip
 import json
 import os
 import re
+import time
 import uuid
 from copy import deepcopy
 from queue import Queue
@@ -47,6 +48,8 @@ class Sentry(SentryLike):
         self.fail_on_relay_error = True
         self.request_log = []
         self.project_config_simulate_pending = False
+        self.project_config_updates = []
+        self.sourcemaps = {}
 
     @property
     def internal_error_dsn(self):
@@ -97,6 +100,23 @@ class Sentry(SentryLike):
 
         return key_entry
 
+    def push_project_config_update(self, public_key, config):
+        """
+        Pushes an update of project config sections to Relays polling for updates.
+
+        The version of the update is its position in the list of updates.
+        """
+        last_change = datetime.datetime.utcnow().isoformat() + "Z"
+        self.project_config_updates.append(
+            (public_key, {"lastChange": last_change, "config": config})
+        )
+
+    def add_sourcemap(self, project_id, release, url, sourcemap):
+        """
+        Adds a source map for a minified file that Relays can fetch from the upstream.
+        """
+        self.sourcemaps[(project_id, release, url)] = json.dumps(sourcemap)
+
     def basic_project_config(
         self,
         project_id,
@@ -360,6 +380,41 @@ def mini_sentry(request):  # noqa
 
         return jsonify(response)
 
+    @app.route("/api/0/relays/projectconfigs/updates/", methods=["POST"])
+    def get_project_config_updates():
+        relay_id = flask_request.headers["x-sentry-relay-id"]
+        if relay_id not in authenticated_relays:
+            abort(403, "relay not registered")
+
+        version = flask_request.json.get("version")
+        if version is None:
+            return jsonify(version=len(sentry.project_config_updates), updates={})
+
+        deadline = time.monotonic() + flask_request.json["timeout"]
+        while len(sentry.project_config_updates) <= version:
+            if time.monotonic() >= deadline:
+                return jsonify(version=version, updates={})
+            time.sleep(0.1)
+
+        updates = {}
+        for public_key, update in sentry.project_config_updates[version:]:
+            updates[public_key] = update
+
+        return jsonify(version=len(sentry.project_config_updates), updates=updates)
+
+    @app.route("/api/0/relays/sourcemaps/", methods=["POST"])
+    def get_sourcemap():
+        relay_id = flask_request.headers["x-sentry-relay-id"]
+        if relay_id not in authenticated_relays:
+            abort(403, "relay not registered")
+
+        key = (
+            flask_request.json["projectId"],
+            flask_request.json["release"],
+            flask_request.json["url"],
+        )
+        return jsonify(sourcemap=sentry.sourcemaps.get(key))
+
     @app.route("/api/0/relays/publickeys/", methods=["POST"])
     def public_keys():
         relay_id = flask_request.headers["x-sentry-relay-id"]
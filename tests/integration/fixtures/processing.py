@@ -57,6 +57,7 @@ def processing_config(get_topic_name):
                 "monitors": get_topic_name("monitors"),
                 "spans": get_topic_name("spans"),
                 "profiles": get_topic_name("profiles"),
+                "cardinality_reports": get_topic_name("cardinality_reports"),
             }
 
         if not processing.get("redis"):
@@ -310,6 +311,13 @@ def spans_consumer(kafka_consumer):
     return lambda timeout=None: SpansConsumer(timeout=timeout, *kafka_consumer("spans"))
 
 
+@pytest.fixture
+def cardinality_reports_consumer(kafka_consumer):
+    return lambda timeout=None: CardinalityReportsConsumer(
+        timeout=timeout, *kafka_consumer("cardinality_reports")
+    )
+
+
 @pytest.fixture
 def profiles_consumer(kafka_consumer):
     return lambda: ProfileConsumer(*kafka_consumer("profiles"))
@@ -462,6 +470,15 @@ class SpansConsumer(ConsumerBase):
                 yield json.loads(message.value())
 
 
+class CardinalityReportsConsumer(ConsumerBase):
+    def get_report(self):
+        message = self.poll()
+        assert message is not None
+        assert message.error() is None
+
+        return json.loads(message.value())
+
+
 class ProfileConsumer(ConsumerBase):
     def get_profile(self):
         message = self.poll()
@@ -1,3 +1,4 @@
+import json
 from datetime import datetime, timezone
 
 import pytest
@@ -111,3 +112,92 @@ def test_cardinality_limits_global_config_mode(
     else:
         metrics = metrics_by_namespace(metrics_consumer, 2)
         assert len(metrics["transactions"]) == 2
+
+
+def test_cardinality_limits_passive_limit(
+    mini_sentry, relay_with_processing, metrics_consumer, cardinality_reports_consumer
+):
+    relay = relay_with_processing(options=TEST_CONFIG)
+    metrics_consumer = metrics_consumer()
+    cardinality_reports_consumer = cardinality_reports_consumer()
+
+    project_id = 42
+    cardinality_limits = [
+        {
+            "id": "transactions_passive",
+            "passive": True,
+            "window": {"windowSeconds": 3600, "granularitySeconds": 600},
+            "limit": 1,
+            "scope": "organization",
+            "namespace": "transactions",
+        },
+        {
+            "id": "custom",
+            "window": {"windowSeconds": 3600, "granularitySeconds": 600},
+            "limit": 1,
+            "scope": "organization",
+            "namespace": "custom",
+        },
+    ]
+
+    add_project_config(mini_sentry, project_id, cardinality_limits)
+
+    metrics_payload = "\n".join(
+        [
+            "transactions/foo@second:12|c|#a:1",
+            "transactions/bar@second:23|c|#a:2",
+            "transactions/bar@second:24|c|#a:3",
+            "foo@second:12|c",
+            "bar@second:23|c",
+        ]
+    )
+    relay.send_metrics(project_id, metrics_payload)
+
+    # The passive limit does not reject anything.
+    metrics = metrics_by_namespace(metrics_consumer, 4)
+    assert len(metrics["custom"]) == 1
+    assert len(metrics["transactions"]) == 3
+
+    report = cardinality_reports_consumer.get_report()
+    assert report["organization_id"] == 1
+    assert report["project_id"] == project_id
+    assert report["limit_id"] == "transactions_passive"
+    assert report["rejected"] == 2
+    assert report["top_tags"] == [{"name": "a", "count": 2}]
+
+
+def test_cardinality_limits_local_enforcement(mini_sentry, relay):
+    relay = relay(
+        mini_sentry,
+        options={**TEST_CONFIG, "cardinality_limiter": {"local_enforcement": True}},
+    )
+
+    project_id = 42
+    cardinality_limits = [
+        {
+            "id": "custom",
+            "window": {"windowSeconds": 3600, "granularitySeconds": 600},
+            "limit": 1,
+            "scope": "organization",
+            "namespace": "custom",
+        },
+    ]
+
+    add_project_config(mini_sentry, project_id, cardinality_limits)
+
+    metrics_payload = "\n".join(
+        [
+            "transactions/foo@second:12|c",
+            "foo@second:12|c",
+            "bar@second:23|c",
+        ]
+    )
+    relay.send_metrics(project_id, metrics_payload)
+
+    envelope = mini_sentry.captured_events.get(timeout=3)
+    assert len(envelope.items) == 1
+    metrics = json.loads(envelope.items[0].get_bytes().decode())
+
+    names = sorted(metric["name"] for metric in metrics)
+    assert len(names) == 2
+    assert "c:transactions/foo@second" in names
@@ -0,0 +1,89 @@
+import queue
+
+import pytest
+
+
+GROUPING_CONFIG = {"id": "newstyle:2023-01-11", "enhancements": ""}
+
+
+def error_event(event_id, ty="TypeError"):
+    return {
+        "event_id": event_id,
+        "exception": {
+            "values": [
+                {
+                    "type": ty,
+                    "value": "x is undefined",
+                    "stacktrace": {
+                        "frames": [{"function": "render", "filename": "app.js"}]
+                    },
+                }
+            ]
+        },
+    }
+
+
+def test_dedup_event_id(mini_sentry, relay):
+    config = {"outcomes": {"emit_outcomes": True, "batch_size": 1, "batch_interval": 1}}
+    relay = relay(mini_sentry, config)
+
+    project_id = 42
+    project_config = mini_sentry.add_basic_project_config(project_id)
+    project_config["config"]["deduplication"] = {"isEnabled": True, "window": 60}
+
+    event_id = "52df9022835246eeb317dbd739ccd059"
+    relay.send_event(project_id, error_event(event_id))
+    relay.send_event(project_id, error_event(event_id))
+
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    assert event["event_id"] == event_id
+
+    with pytest.raises(queue.Empty):
+        mini_sentry.captured_events.get(timeout=1)
+
+    outcomes = mini_sentry.captured_outcomes.get(timeout=2)["outcomes"]
+    assert len(outcomes) == 1
+    assert outcomes[0]["outcome"] == 1  # Filtered
+    assert outcomes[0]["reason"] == "deduplicated"
+    assert outcomes[0]["event_id"] == event_id
+
+
+def test_dedup_content(mini_sentry, relay):
+    relay = relay(mini_sentry)
+
+    project_id = 42
+    project_config = mini_sentry.add_basic_project_config(project_id)
+    project_config["config"]["deduplication"] = {"isEnabled": True}
+    # Events are compared by their grouping hash.
+    project_config["config"]["groupingConfig"] = GROUPING_CONFIG
+
+    relay.send_event(project_id, error_event("52df9022835246eeb317dbd739ccd059"))
+    relay.send_event(project_id, error_event("a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5"))
+    relay.send_event(
+        project_id,
+        error_event("b2f1a3c4d5e6f7a8b9c0d1e2f3a4b5c6", ty="ReferenceError"),
+    )
+
+    first = mini_sentry.captured_events.get(timeout=2).get_event()
+    assert first["event_id"] == "52df9022835246eeb317dbd739ccd059"
+
+    other = mini_sentry.captured_events.get(timeout=2).get_event()
+    assert other["event_id"] == "b2f1a3c4d5e6f7a8b9c0d1e2f3a4b5c6"
+
+    with pytest.raises(queue.Empty):
+        mini_sentry.captured_events.get(timeout=1)
+
+
+def test_dedup_disabled(mini_sentry, relay):
+    relay = relay(mini_sentry)
+
+    project_id = 42
+    mini_sentry.add_basic_project_config(project_id)
+
+    event_id = "52df9022835246eeb317dbd739ccd059"
+    relay.send_event(project_id, error_event(event_id))
+    relay.send_event(project_id, error_event(event_id))
+
+    for _ in range(2):
+        event = mini_sentry.captured_events.get(timeout=2).get_event()
+        assert event["event_id"] == event_id
@@ -1,6 +1,7 @@
 from datetime import datetime
 import uuid
 import json
+import time
 
 import pytest
 from sentry_sdk.envelope import Envelope, Item, PayloadRef
@@ -653,6 +654,130 @@ def test_relay_chain_keep_unsampled_profile(
     )
 
 
+def test_reservoir_sync(mini_sentry, relay):
+    """
+    Tests that a downstream Relay synchronizes reservoir rules with its upstream and exposes the
+    consumption of its reservoirs.
+    """
+    project_id = 42
+    upstream = relay(mini_sentry)
+    downstream = relay(upstream, options={"cache": {"reservoir_sync_interval": 1}})
+
+    config = mini_sentry.add_basic_project_config(project_id)
+    public_key = config["publicKeys"][0]["publicKey"]
+    rules = _add_sampling_config(config, sample_rate=0, rule_type="transaction")
+    rules.insert(
+        0,
+        {
+            "samplingValue": {"type": "reservoir", "limit": 3},
+            "type": "transaction",
+            "condition": {"op": "and", "inner": []},
+            "id": 100,
+        },
+    )
+
+    # The downstream Relay takes its first sample.
+    envelope, _, _ = _create_transaction_envelope(public_key)
+    downstream.send_envelope(project_id, envelope)
+    mini_sentry.captured_events.get(timeout=2).get_transaction_event()
+
+    # Other Relays consume the rest of the reservoir.
+    for _ in range(2):
+        envelope, _, _ = _create_transaction_envelope(public_key)
+        upstream.send_envelope(project_id, envelope)
+        mini_sentry.captured_events.get(timeout=2).get_transaction_event()
+
+    # Wait for the downstream Relay to synchronize with the upstream.
+    time.sleep(2.5)
+
+    response = downstream.get("/api/relay/reservoirs/")
+    assert response.status_code == 401
+
+    response = downstream.get_signed("/api/relay/reservoirs/")
+    assert response.ok
+    assert response.json()["projects"][public_key] == [
+        {"ruleId": 100, "limit": 3, "consumed": 3, "expiry": None}
+    ]
+
+    # The reservoir is exhausted, so the downstream Relay drops the transaction.
+    envelope, _, _ = _create_transaction_envelope(public_key)
+    downstream.send_envelope(project_id, envelope)
+    with pytest.raises(queue.Empty):
+        mini_sentry.captured_events.get(timeout=2)
+
+
+def test_sampling_simulation(mini_sentry, relay):
+    """
+    Tests that the simulation endpoint evaluates a sampling config over a period of time.
+    """
+    relay = relay(mini_sentry)
+
+    config = {
+        "version": 2,
+        "rules": [
+            {
+                "id": 1,
+                "type": "trace",
+                "condition": {"op": "eq", "name": "trace.release", "value": ["1.0"]},
+                "samplingValue": {"type": "sampleRate", "value": 1.0},
+                "timeRange": {
+                    "start": "2024-01-01T00:00:00Z",
+                    "end": "2024-01-01T01:00:00Z",
+                },
+            },
+            {
+                "id": 2,
+                "type": "trace",
+                "condition": {"op": "and", "inner": []},
+                "samplingValue": {"type": "sampleRate", "value": 0.5},
+            },
+        ],
+    }
+
+    corpus = [
+        {
+            "dsc": {
+                "trace_id": str(uuid.uuid4()),
+                "public_key": "31a5a894b4524f74a9a8d0e27e21ba91",
+                "release": release,
+            }
+        }
+        for release in ["1.0", "1.0", "2.0"]
+    ]
+
+    simulation = {
+        "config": config,
+        "period": {
+            "start": "2024-01-01T00:00:00Z",
+            "end": "2024-01-01T02:00:00Z",
+            "steps": 2,
+        },
+        "corpus": corpus,
+    }
+
+    response = relay.post("/api/relay/sampling/simulate/", json=simulation)
+    assert response.status_code == 401
+
+    response = relay.post_signed("/api/relay/sampling/simulate/", simulation)
+    assert response.ok
+
+    during, after = response.json()["steps"]
+    assert during["total"] == 3
+    assert during["projectedKept"] == 2.5
+    assert during["matchedRules"] == {"1": 2, "2": 1}
+    assert during["sampleRates"] == [
+        {"sampleRate": 0.5, "count": 1},
+        {"sampleRate": 1.0, "count": 2},
+    ]
+
+    assert after["projectedKept"] == 1.5
+    assert after["matchedRules"] == {"2": 3}
+
+    simulation["corpus"] = corpus * 334
+    response = relay.post_signed("/api/relay/sampling/simulate/", simulation)
+    assert response.status_code == 400
+
+
 def get_profile_payload(transaction):
     return {
         "debug_meta": {"images": []},
@@ -1,6 +1,9 @@
 import datetime
+import uuid
 from time import sleep
+
 import pytest
+from sentry_sdk.envelope import Envelope, Item, PayloadRef
 
 
 @pytest.mark.parametrize(
@@ -282,3 +285,236 @@ def test_ignore_transactions_filters_are_applied(
     else:
         event, _ = transactions_consumer.get_event()
         assert event["transaction"] == transaction_name
+
+
+def test_abuse_filter(mini_sentry, relay_with_processing, events_consumer):
+    events_consumer = events_consumer()
+    relay = relay_with_processing()
+
+    project_id = 42
+    project_config = mini_sentry.add_full_project_config(project_id)
+    filter_settings = project_config["config"]["filterSettings"]
+    filter_settings["abuse"] = {"fingerprintLimit": 1}
+    # The fingerprint limit counts events by their grouping hash.
+    project_config["config"]["groupingConfig"] = {
+        "id": "newstyle:2023-01-11",
+        "enhancements": "",
+    }
+
+    event = {
+        "exception": {
+            "values": [{"type": "Panic", "value": "infinite loop"}],
+        },
+    }
+
+    relay.send_event(project_id, event)
+    events_consumer.get_event()
+
+    # Events of the same group are filtered for the rest of the minute.
+    relay.send_event(project_id, event)
+    events_consumer.assert_empty()
+
+
+def test_bot_score_filter(mini_sentry, relay_with_processing, events_consumer):
+    events_consumer = events_consumer()
+    relay = relay_with_processing()
+
+    project_id = 42
+    project_config = mini_sentry.add_full_project_config(project_id)
+    filter_settings = project_config["config"]["filterSettings"]
+    filter_settings["botScore"] = {"isEnabled": True, "threshold": 50}
+
+    def event_with_user_agent(user_agent):
+        return {"request": {"headers": {"User-Agent": user_agent}}}
+
+    chrome = (
+        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 "
+        "(KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"
+    )
+    relay.send_event(project_id, event_with_user_agent(chrome))
+    event, _ = events_consumer.get_event()
+    assert ["bot_score", "0"] in event["tags"]
+
+    relay.send_event(project_id, event_with_user_agent("python-requests/2.31.0"))
+    events_consumer.assert_empty()
+
+    # Server-side SDKs send events without the user agent of an end user.
+    relay.send_event(project_id, headers={"User-Agent": "sentry.python/1.39.1"})
+    event, _ = events_consumer.get_event()
+    assert ["bot_score", "0"] in event["tags"]
+
+
+def test_generic_filter_check_ins(
+    mini_sentry, relay_with_processing, monitors_consumer, outcomes_consumer
+):
+    monitors_consumer = monitors_consumer()
+    outcomes_consumer = outcomes_consumer()
+    relay = relay_with_processing()
+
+    project_id = 42
+    project_config = mini_sentry.add_full_project_config(project_id)
+    filter_settings = project_config["config"]["filterSettings"]
+    filter_settings["generic"] = {
+        "version": 1,
+        "filters": [
+            {
+                "id": "healthChecks",
+                "isEnabled": True,
+                "condition": {
+                    "op": "glob",
+                    "name": "check_in.monitor_slug",
+                    "value": ["healthcheck-*"],
+                },
+            }
+        ],
+    }
+
+    def check_in(slug):
+        return {
+            "check_in_id": "a460c25ff2554577b920fcfacae4e5eb",
+            "monitor_slug": slug,
+            "status": "ok",
+        }
+
+    relay.send_check_in(project_id, check_in("healthcheck-api"))
+    outcome = outcomes_consumer.get_outcome()
+    assert outcome["outcome"] == 1  # Filtered
+    assert outcome["reason"] == "healthChecks"
+    assert outcome["category"] == 10  # Monitor
+    monitors_consumer.assert_empty()
+
+    relay.send_check_in(project_id, check_in("nightly-backup"))
+    _, message = monitors_consumer.get_check_in()
+    assert message["project_id"] == project_id
+
+
+def test_generic_filter_spans_before_quotas(
+    mini_sentry, relay_with_processing, spans_consumer, outcomes_consumer
+):
+    spans_consumer = spans_consumer()
+    outcomes_consumer = outcomes_consumer()
+    relay = relay_with_processing()
+
+    project_id = 42
+    project_config = mini_sentry.add_full_project_config(project_id)
+    project_config["config"].setdefault("features", []).append(
+        "organizations:standalone-span-ingestion"
+    )
+    project_config["config"]["quotas"] = [
+        {
+            "id": f"test_spans_{uuid.uuid4().hex}",
+            "categories": ["span_indexed"],
+            "limit": 1,
+            "window": 3600,
+            "reasonCode": "spans_exceeded",
+        }
+    ]
+    filter_settings = project_config["config"]["filterSettings"]
+    filter_settings["generic"] = {
+        "version": 1,
+        "filters": [
+            {
+                "id": "healthChecks",
+                "isEnabled": True,
+                "condition": {
+                    "op": "glob",
+                    "name": "span.description",
+                    "value": ["GET /health*"],
+                },
+            }
+        ],
+    }
+
+    def send_span(description):
+        now = datetime.datetime.now(tz=datetime.timezone.utc)
+        span = {
+            "op": "http.server",
+            "description": description,
+            "span_id": "968cff94913ebb07",
+            "segment_id": "968cff94913ebb07",
+            "start_timestamp": now.timestamp(),
+            "timestamp": now.timestamp() + 1,
+            "exclusive_time": 1000.0,
+            "trace_id": "a0fa8803753e40fd8124b21eeb2986b5",
+        }
+        envelope = Envelope()
+        envelope.add_item(Item(payload=PayloadRef(json=span), type="span"))
+        relay.send_envelope(project_id, envelope)
+
+    send_span("GET /health")
+    outcome = outcomes_consumer.get_outcome()
+    assert outcome["outcome"] == 1  # Filtered
+    assert outcome["reason"] == "healthChecks"
+    assert outcome["category"] == 16  # SpanIndexed
+    spans_consumer.assert_empty()
+
+    # The filtered span did not consume the quota of one span.
+    send_span("GET /api/users")
+    span = spans_consumer.get_span()
+    assert span["description"] == "GET /api/users"
+
+
+def test_pushed_filter_update(mini_sentry, relay):
+    relay = relay(mini_sentry, options={"cache": {"project_updates_timeout": 1}})
+
+    project_id = 42
+    project_config = mini_sentry.add_basic_project_config(project_id)
+    public_key = project_config["publicKeys"][0]["publicKey"]
+
+    relay.send_event(project_id, {"release": "1.0"})
+    mini_sentry.captured_events.get(timeout=2)
+
+    # The project config is cached, so the update can only take effect if it is pushed.
+    mini_sentry.push_project_config_update(
+        public_key, {"filterSettings": {"releases": {"releases": ["1.0"]}}}
+    )
+    sleep(1.5)
+
+    relay.send_event(project_id, {"release": "1.0"})
+    relay.send_event(project_id, {"release": "2.0"})
+
+    # Only the second event passes the filter.
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    assert event["release"] == "2.0"
+    assert mini_sentry.captured_events.empty()
+
+
+def test_generic_filter_sessions(mini_sentry, relay_with_processing, outcomes_consumer):
+    outcomes_consumer = outcomes_consumer()
+    relay = relay_with_processing()
+
+    project_id = 42
+    project_config = mini_sentry.add_full_project_config(project_id)
+    filter_settings = project_config["config"]["filterSettings"]
+    filter_settings["generic"] = {
+        "version": 1,
+        "filters": [
+            {
+                "id": "oldReleases",
+                "isEnabled": True,
+                "condition": {
+                    "op": "glob",
+                    "name": "session.release",
+                    "value": ["sentry-test@1.*"],
+                },
+            }
+        ],
+    }
+
+    timestamp = datetime.datetime.now(tz=datetime.timezone.utc)
+    relay.send_session(
+        project_id,
+        {
+            "sid": "8333339f-5675-4f89-a9a0-1c935255ab58",
+            "timestamp": timestamp.isoformat(),
+            "started": timestamp.isoformat(),
+            "status": "exited",
+            "attrs": {"release": "sentry-test@1.0.0"},
+        },
+    )
+
+    outcome = outcomes_consumer.get_outcome()
+    assert outcome["outcome"] == 1  # Filtered
+    assert outcome["reason"] == "oldReleases"
+    assert outcome["category"] == 5  # Session
+    assert outcome["quantity"] == 1
@@ -0,0 +1,69 @@
+import hashlib
+import queue
+
+import pytest
+
+
+def grouping_hash(*components):
+    return hashlib.md5("".join(c + "\n" for c in components).encode()).hexdigest()
+
+
+def test_grouping_hash_fingerprint(mini_sentry, relay):
+    relay = relay(mini_sentry)
+
+    project_id = 42
+    project_config = mini_sentry.add_basic_project_config(project_id)
+    project_config["config"]["groupingConfig"] = {"id": "newstyle:2023-01-11"}
+
+    relay.send_event(
+        project_id, {"message": "connection refused", "fingerprint": ["db-down"]}
+    )
+
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    assert event["grouping_hash"] == grouping_hash("db-down")
+
+
+def test_grouping_hash_legacy_config(mini_sentry, relay):
+    relay = relay(mini_sentry)
+
+    project_id = 42
+    mini_sentry.add_basic_project_config(project_id)
+
+    # Relay does not implement legacy grouping and removes hashes sent by clients.
+    relay.send_event(
+        project_id, {"message": "connection refused", "grouping_hash": "a" * 32}
+    )
+
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    assert "grouping_hash" not in event
+
+
+def test_grouping_hash_filter(mini_sentry, relay):
+    relay = relay(mini_sentry)
+
+    project_id = 42
+    project_config = mini_sentry.add_basic_project_config(project_id)
+    project_config["config"]["groupingConfig"] = {"id": "newstyle:2023-01-11"}
+    project_config["config"]["filterSettings"]["generic"] = {
+        "version": 1,
+        "filters": [
+            {
+                "id": "noisyIssue",
+                "isEnabled": True,
+                "condition": {
+                    "op": "eq",
+                    "name": "event.grouping_hash",
+                    "value": grouping_hash("noisy"),
+                },
+            }
+        ],
+    }
+
+    relay.send_event(project_id, {"message": "noisy", "fingerprint": ["noisy"]})
+    relay.send_event(project_id, {"message": "other", "fingerprint": ["other"]})
+
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    assert event["fingerprint"] == ["other"]
+
+    with pytest.raises(queue.Empty):
+        mini_sentry.captured_events.get(timeout=1)
@@ -0,0 +1,42 @@
+def test_pii_dry_run(mini_sentry, relay):
+    """
+    Tests that the dry run endpoint reports fields that are scrubbed differently.
+    """
+    relay = relay(mini_sentry)
+
+    response = relay.post_signed(
+        "/api/relay/pii/dry-run/",
+        {
+            "old": {
+                "datascrubbingSettings": {"scrubData": True, "scrubDefaults": True},
+            },
+            "new": {
+                "datascrubbingSettings": {"scrubData": True, "scrubDefaults": True},
+                "piiConfig": {"applications": {"$user.email": ["@email:replace"]}},
+            },
+            "event": {
+                "user": {"email": "jane@example.com"},
+                "extra": {"password": "hunter2"},
+            },
+        },
+    )
+    assert response.ok
+
+    assert response.json() == {
+        "changes": [
+            {
+                "path": "user.email",
+                "original": "jane@example.com",
+                "old": {"value": "jane@example.com", "ruleIds": []},
+                "new": {"value": "[email]", "ruleIds": ["@email:replace"]},
+            }
+        ]
+    }
+
+
+
+def test_pii_dry_run_unsigned(mini_sentry, relay):
+    relay = relay(mini_sentry)
+
+    response = relay.post("/api/relay/pii/dry-run/", json={"event": {}})
+    assert response.status_code == 401
@@ -0,0 +1,110 @@
+import json
+from time import sleep
+
+# Source map for `function a(b,c){return b+c}` minified from `src/app.js`.
+SOURCEMAP = {
+    "version": 3,
+    "file": "app.min.js",
+    "sourceRoot": "webpack:///",
+    "sources": ["src/app.js"],
+    "sourcesContent": [
+        "// Adds two numbers.\nfunction add(left, right) {\n  return left + right;\n}\n"
+    ],
+    "names": ["add", "left", "right"],
+    "mappings": "AACA,SAASA,EAAIC,EAAMC,GACjB,OAAOD,EAAOC",
+}
+
+
+def minified_event():
+    return {
+        "platform": "javascript",
+        "release": "1.0",
+        "exception": {
+            "values": [
+                {
+                    "type": "Error",
+                    "value": "failed",
+                    "stacktrace": {
+                        "frames": [
+                            {
+                                "function": "a",
+                                "abs_path": "https://example.com/static/app.min.js",
+                                "lineno": 1,
+                                "colno": 10,
+                            }
+                        ]
+                    },
+                }
+            ]
+        },
+    }
+
+
+def get_frame(event):
+    return event["exception"]["values"][0]["stacktrace"]["frames"][0]
+
+
+def test_local_sourcemap(mini_sentry, relay, tmp_path):
+    release_dir = tmp_path / "42" / "1.0" / "static"
+    release_dir.mkdir(parents=True)
+    (release_dir / "app.min.js.map").write_text(json.dumps(SOURCEMAP))
+
+    relay = relay(mini_sentry, options={"sourcemaps": {"path": str(tmp_path)}})
+
+    project_id = 42
+    mini_sentry.add_basic_project_config(project_id)
+    relay.send_event(project_id, minified_event())
+
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    frame = get_frame(event)
+
+    assert frame["function"] == "add"
+    assert frame["raw_function"] == "a"
+    assert frame["abs_path"] == "webpack:///src/app.js"
+    assert frame["lineno"] == 2
+    assert frame["colno"] == 10
+    assert frame["context_line"] == "function add(left, right) {"
+    assert frame["pre_context"] == ["// Adds two numbers."]
+    assert frame["post_context"] == ["  return left + right;", "}"]
+    assert frame["data"]["orig_lineno"] == 1
+    assert frame["data"]["sourcemap"] == "~/static/app.min.js.map"
+
+
+
+def test_local_sourcemap_other_project(mini_sentry, relay, tmp_path):
+    release_dir = tmp_path / "42" / "1.0" / "static"
+    release_dir.mkdir(parents=True)
+    (release_dir / "app.min.js.map").write_text(json.dumps(SOURCEMAP))
+
+    relay = relay(mini_sentry, options={"sourcemaps": {"path": str(tmp_path)}})
+
+    # Source maps of project 42 are not used for other projects.
+    project_id = 43
+    mini_sentry.add_basic_project_config(project_id)
+    relay.send_event(project_id, minified_event())
+
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    assert get_frame(event)["function"] == "a"
+
+
+def test_upstream_sourcemap(mini_sentry, relay):
+    relay = relay(mini_sentry, options={"sourcemaps": {"fetch_upstream": True}})
+
+    project_id = 42
+    mini_sentry.add_basic_project_config(project_id)
+    mini_sentry.add_sourcemap(
+        project_id, "1.0", "https://example.com/static/app.min.js", SOURCEMAP
+    )
+
+    # The source map is fetched in the background, so the first event remains minified.
+    relay.send_event(project_id, minified_event())
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    assert get_frame(event)["function"] == "a"
+
+    sleep(0.5)
+
+    relay.send_event(project_id, minified_event())
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    frame = get_frame(event)
+    assert frame["function"] == "add"
+    assert frame["data"]["sourcemap"] == "https://example.com/static/app.min.js.map"
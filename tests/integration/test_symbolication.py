@@ -0,0 +1,62 @@
+SYMBOLS = """\
+MODULE Linux x86_64 B1AB7DB05E8C4E6DB8F1F2C5A6B7C8D90 libapp.so
+FILE 0 /src/app/main.c
+FUNC 1000 20 0 main
+1000 10 12 0
+1010 10 13 0
+FUNC 1020 10 0 crash
+1020 10 7 0
+"""
+
+
+def test_breakpad_symbolication(mini_sentry, relay, tmp_path):
+    sym_dir = tmp_path / "libapp.so" / "B1AB7DB05E8C4E6DB8F1F2C5A6B7C8D90"
+    sym_dir.mkdir(parents=True)
+    (sym_dir / "libapp.so.sym").write_text(SYMBOLS)
+
+    relay = relay(mini_sentry, options={"symbols": {"path": str(tmp_path)}})
+
+    project_id = 42
+    mini_sentry.add_basic_project_config(project_id)
+    relay.send_event(
+        project_id,
+        {
+            "platform": "native",
+            "debug_meta": {
+                "images": [
+                    {
+                        "type": "elf",
+                        "code_file": "/usr/lib/libapp.so",
+                        "debug_id": "b1ab7db0-5e8c-4e6d-b8f1-f2c5a6b7c8d9",
+                        "image_addr": "0x7f0000000000",
+                        "image_size": 65536,
+                    }
+                ]
+            },
+            "exception": {
+                "values": [
+                    {
+                        "type": "SIGSEGV",
+                        "stacktrace": {
+                            "frames": [
+                                {"instruction_addr": "0x7f0000001015"},
+                                {"instruction_addr": "0x7f0000001024"},
+                            ]
+                        },
+                    }
+                ]
+            },
+        },
+    )
+
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    frames = event["exception"]["values"][0]["stacktrace"]["frames"]
+
+    assert frames[0]["function"] == "main"
+    assert frames[0]["lineno"] == 13
+    assert frames[0]["abs_path"] == "/src/app/main.c"
+    assert frames[0]["package"] == "/usr/lib/libapp.so"
+
+    assert frames[1]["function"] == "crash"
+    assert frames[1]["lineno"] == 7
+    assert frames[1]["symbol_addr"] == "0x7f0000001020"
@@ -0,0 +1,45 @@
+def test_tag_rules(mini_sentry, relay):
+    relay = relay(mini_sentry)
+
+    project_id = 42
+    project_config = mini_sentry.add_basic_project_config(project_id)
+    project_config["config"]["tagRules"] = [
+        {"type": "rename", "from": "srv", "to": "server"},
+        {"type": "lowercase", "tags": ["region"]},
+        {
+            "type": "fromContext",
+            "context": "os",
+            "field": "version",
+            "pattern": "^(\\d+)\\.",
+            "tag": "os.major",
+        },
+        {"type": "drop", "tags": ["debug.*"]},
+        {
+            "type": "fromContext",
+            "context": "os",
+            "field": "name",
+            "pattern": "(",
+            "tag": "invalid",
+        },
+    ]
+
+    relay.send_event(
+        project_id,
+        {
+            "message": "hello",
+            "tags": {"srv": "web-1", "region": "EU-West", "debug.id": "1"},
+            "contexts": {"os": {"name": "Android", "version": "14.1"}},
+        },
+    )
+
+    event = mini_sentry.captured_events.get(timeout=2).get_event()
+    tags = dict(event["tags"])
+
+    assert tags["server"] == "web-1"
+    assert tags["region"] == "eu-west"
+    assert tags["os.major"] == "14"
+    assert "srv" not in tags
+    assert "debug.id" not in tags
+    assert "invalid" not in tags
+
+    assert event["_meta"]["tags"][""]["err"][0][0] == "invalid_data"